@@ -0,0 +1,124 @@
+//! Overlays drawn over the scene by `draw_scene` to inspect the rendering:
+//! what is repainted, what is culled and what is cached.
+
+use bitflags::bitflags;
+use indextree::Arena;
+
+use crate::{
+    engine::{
+        node::{SceneNode, SceneNodeRenderable},
+        storage::{FlatStorageData, TreeStorageId},
+        Engine, NodeRef,
+    },
+    types::Color,
+};
+
+bitflags! {
+    /// The debug overlays drawn over the scene, set with `Engine::set_debug_overlays`
+    #[derive(Default)]
+    pub struct DebugOverlays: u32 {
+        /// Fills the areas damaged in the current frame
+        const DAMAGE = 1 << 0;
+        /// Tints the layers culled by occlusion
+        const OCCLUSION = 1 << 1;
+        /// Outlines the image-cached layers in orange and the
+        /// picture-cached layers in green
+        const CACHE = 1 << 2;
+        /// Outlines the layout bounds of the layers and marks their anchor point
+        const LAYOUT = 1 << 3;
+    }
+}
+
+const DAMAGE_COLOR: &str = "#FF000040";
+const OCCLUDED_COLOR: &str = "#FF00FF50";
+const IMAGE_CACHED_COLOR: &str = "#FF9500";
+const PICTURE_CACHED_COLOR: &str = "#34C759";
+const LAYOUT_COLOR: &str = "#007AFF";
+const ANCHOR_RADIUS: f32 = 3.0;
+
+/// Draws the debug overlays enabled on the engine over the scene,
+/// the canvas is expected in screen coordinates.
+pub(crate) fn draw_debug_overlays(
+    engine: &Engine,
+    root_id: NodeRef,
+    scene_arena: &Arena<SceneNode>,
+    renderables_arena: &FlatStorageData<SceneNodeRenderable>,
+    canvas: &skia_safe::Canvas,
+) {
+    let overlays = engine.debug_overlays();
+    if overlays.is_empty() {
+        return;
+    }
+    let occluded = overlays
+        .contains(DebugOverlays::OCCLUSION)
+        .then(|| engine.scene.occluded_set(root_id))
+        .flatten()
+        .unwrap_or_default();
+
+    let root: TreeStorageId = root_id.into();
+    for node_id in root.descendants(scene_arena) {
+        let Some(node) = scene_arena.get(node_id).filter(|node| !node.is_removed()) else {
+            continue;
+        };
+        let scene_node = node.get();
+        // the hidden subtrees are not drawn
+        if node_id
+            .ancestors(scene_arena)
+            .any(|id| scene_arena[id].get().hidden())
+        {
+            continue;
+        }
+        let render_layer = &scene_node.render_layer;
+        let node_ref = NodeRef(node_id);
+
+        canvas.save();
+        canvas.concat(&render_layer.transform_33);
+        if occluded.contains(&node_ref) {
+            let paint = fill_paint(OCCLUDED_COLOR);
+            canvas.draw_rect(render_layer.bounds, &paint);
+        }
+        if overlays.contains(DebugOverlays::CACHE) {
+            if scene_node.is_image_cached() {
+                let paint = stroke_paint(IMAGE_CACHED_COLOR, 3.0);
+                canvas.draw_rect(render_layer.bounds_with_children, &paint);
+            } else if scene_node.is_picture_cached()
+                && renderables_arena
+                    .get(&usize::from(node_id))
+                    .is_some_and(|renderable| renderable.draw_cache.is_some())
+            {
+                let paint = stroke_paint(PICTURE_CACHED_COLOR, 1.0);
+                canvas.draw_rect(render_layer.bounds, &paint);
+            }
+        }
+        if overlays.contains(DebugOverlays::LAYOUT) {
+            let paint = stroke_paint(LAYOUT_COLOR, 1.0);
+            canvas.draw_rect(render_layer.bounds, &paint);
+            let anchor = skia_safe::Point::new(
+                render_layer.anchor_point.x * render_layer.size.width,
+                render_layer.anchor_point.y * render_layer.size.height,
+            );
+            canvas.draw_circle(anchor, ANCHOR_RADIUS, &fill_paint(LAYOUT_COLOR));
+        }
+        canvas.restore();
+    }
+
+    if overlays.contains(DebugOverlays::DAMAGE) {
+        let damage = engine.damage_region();
+        if !damage.is_empty() {
+            canvas.draw_region(&damage, &fill_paint(DAMAGE_COLOR));
+        }
+    }
+}
+
+fn fill_paint(color: &str) -> skia_safe::Paint {
+    let mut paint = skia_safe::Paint::default();
+    paint.set_color4f(Color::new_hex(color).c4f(), None);
+    paint
+}
+
+fn stroke_paint(color: &str, width: f32) -> skia_safe::Paint {
+    let mut paint = fill_paint(color);
+    paint.set_stroke(true);
+    paint.set_stroke_width(width);
+    paint
+}
@@ -2,10 +2,12 @@ use std::cell::RefCell;
 
 use skia_safe::*;
 
+use crate::{
+    easing::gradient_stop_positions,
+    engine::node::SceneNodeRenderable,
+    types::{BackgroundImage, BackgroundImageMode, Filter, GradientDirection, PaintColor},
+};
 use crate::{engine::draw_to_picture::DrawDebugInfo, layers::layer::render_layer::RenderLayer};
-use crate::{engine::node::SceneNodeRenderable, types::PaintColor};
-
-use super::scene::BACKGROUND_BLUR_SIGMA;
 
 // Cached noise tile: (image, width, height).
 // Re-rendered only when a larger tile is needed.
@@ -41,6 +43,252 @@ fn get_noise_image(canvas: &Canvas, w: i32, h: i32) -> Option<Image> {
     })
 }
 
+/// Compiles a list of declarative filters into a chain of Skia image filters,
+/// applied in order after `input`. None when there is nothing to apply.
+pub(crate) fn filters_image_filter(
+    filters: &[Filter],
+    input: Option<ImageFilter>,
+) -> Option<ImageFilter> {
+    filters.iter().fold(input, |input, filter| {
+        let compiled = match filter {
+            Filter::Blur(sigma) => {
+                if *sigma <= 0.0 {
+                    return input;
+                }
+                image_filters::blur((*sigma, *sigma), TileMode::Decal, input.clone(), None)
+            }
+            Filter::DropShadow {
+                offset,
+                radius,
+                color,
+            } => image_filters::drop_shadow(
+                (offset.x, offset.y),
+                (*radius, *radius),
+                Color4f::from(*color),
+                None,
+                input.clone(),
+                None,
+            ),
+            _ => filter.color_matrix().and_then(|matrix| {
+                let color_filter = color_filters::matrix_row_major(&matrix, None);
+                image_filters::color_filter(color_filter, input.clone(), None)
+            }),
+        };
+        compiled.or(input)
+    })
+}
+
+/// Builds the shader of a gradient `PaintColor` filling `bounds`, None for solid colors.
+/// Like animated colors, the gradient colors are interpolated in OKLab.
+pub(crate) fn paint_color_shader(color: &PaintColor, bounds: Rect) -> Option<Shader> {
+    let to_skia = |colors: &[crate::types::Color], points: &[f32]| {
+        let positions = gradient_stop_positions(colors, points);
+        let colors: Vec<Color4f> = colors.iter().map(|c| Color4f::from(*c)).collect();
+        (colors, positions)
+    };
+    let interpolation = gradient_shader::Interpolation {
+        in_premul: gradient_shader::interpolation::InPremul::Yes,
+        color_space: gradient_shader::interpolation::ColorSpace::OKLab,
+        hue_method: gradient_shader::interpolation::HueMethod::Shorter,
+    };
+    if color.colors().is_empty() {
+        return None;
+    }
+    match color {
+        PaintColor::Solid { .. } => None,
+        PaintColor::GradientLinear(gradient) => {
+            let (start, end) = match gradient.direction {
+                GradientDirection::Points { start, end } => {
+                    (skia_safe::Point::from(start), skia_safe::Point::from(end))
+                }
+                GradientDirection::Angle(angle) => {
+                    // css gradient line: through the center, long enough for the
+                    // corners to get the first and last colors
+                    let (sin, cos) = angle.to_radians().sin_cos();
+                    let length = (bounds.width() * sin).abs() + (bounds.height() * cos).abs();
+                    let direction = skia_safe::Vector::new(sin, -cos) * (length / 2.0);
+                    (bounds.center() - direction, bounds.center() + direction)
+                }
+            };
+            let (colors, positions) = to_skia(&gradient.colors, &gradient.points);
+            gradient_shader::linear_with_interpolation(
+                (start, end),
+                (colors.as_slice(), None),
+                positions.as_slice(),
+                gradient.tile_mode.into(),
+                interpolation,
+                None,
+            )
+        }
+        PaintColor::GradientRadial(gradient) => {
+            let (colors, positions) = to_skia(&gradient.colors, &gradient.points);
+            gradient_shader::radial_with_interpolation(
+                (gradient.center, gradient.radius),
+                (colors.as_slice(), None),
+                positions.as_slice(),
+                gradient.tile_mode.into(),
+                interpolation,
+                None,
+            )
+        }
+        PaintColor::GradientConic(gradient) => {
+            let (colors, positions) = to_skia(&gradient.colors, &gradient.points);
+            let rotation = Matrix::rotate_deg_pivot(gradient.start_angle, gradient.center);
+            gradient_shader::sweep_with_interpolation(
+                gradient.center,
+                (colors.as_slice(), None),
+                positions.as_slice(),
+                gradient.tile_mode.into(),
+                None,
+                interpolation,
+                &rotation,
+            )
+        }
+        PaintColor::GradientTwoPointConical(gradient) => {
+            let (colors, positions) = to_skia(&gradient.colors, &gradient.points);
+            gradient_shader::two_point_conical_with_interpolation(
+                (gradient.start, gradient.start_radius),
+                (gradient.end, gradient.end_radius),
+                (colors.as_slice(), None),
+                positions.as_slice(),
+                gradient.tile_mode.into(),
+                interpolation,
+                None,
+            )
+        }
+    }
+}
+
+// The layer shape offset by `offset` and grown by `spread` (shrunk when negative).
+fn shadow_path(layer: &RenderLayer, offset: crate::types::Point, spread: f32) -> Path {
+    let path = match &layer.shape {
+        crate::shape::Shape::RoundRect => {
+            Path::rrect(layer.rbounds.with_outset((spread, spread)), None)
+        }
+        _ => {
+            let path = layer.shape_path();
+            if spread == 0.0 {
+                path
+            } else {
+                // grow or shrink the path by its outline stroked with the spread
+                let mut stroke_paint = Paint::default();
+                stroke_paint.set_style(PaintStyle::Stroke);
+                stroke_paint.set_stroke_width(spread.abs() * 2.0);
+                let mut outline = Path::new();
+                skia_safe::path_utils::fill_path_with_paint(
+                    &path,
+                    &stroke_paint,
+                    &mut outline,
+                    None,
+                    None,
+                );
+                let op = if spread > 0.0 {
+                    PathOp::Union
+                } else {
+                    PathOp::Difference
+                };
+                path.op(&outline, op).unwrap_or(path)
+            }
+        }
+    };
+    path.with_offset((offset.x, offset.y))
+}
+
+// Draw a shadow outside of the layer shape.
+// Returns the damage rect in the layer's coordinate space.
+fn draw_outer_shadow(
+    canvas: &Canvas,
+    layer: &RenderLayer,
+    shadow: &crate::types::Shadow,
+    opacity: f32,
+) -> Rect {
+    let path = shadow_path(layer, shadow.offset, shadow.spread);
+    let mut paint = Paint::new(Color4f::from(shadow.color), None);
+    paint.set_anti_alias(true);
+    paint.set_mask_filter(MaskFilter::blur(BlurStyle::Normal, shadow.radius, false));
+    paint.set_alpha_f(opacity * shadow.color.alpha);
+
+    let save_count = canvas.save();
+    layer.clip_to_shape(canvas, ClipOp::Difference, true);
+    canvas.draw_path(&path, &paint);
+    canvas.restore_to_count(save_count);
+
+    path.bounds().with_outset((shadow.radius, shadow.radius))
+}
+
+// Draw a shadow inside of the layer shape: everything around the offset
+// (and shrunk by the spread) shape is shadowed, clipped to the layer shape.
+// Returns the damage rect in the layer's coordinate space.
+fn draw_inset_shadow(
+    canvas: &Canvas,
+    layer: &RenderLayer,
+    shadow: &crate::types::Shadow,
+    opacity: f32,
+) -> Rect {
+    let hole = shadow_path(layer, shadow.offset, -shadow.spread);
+    let margin = shadow.radius * 3.0 + shadow.offset.x.abs().max(shadow.offset.y.abs());
+    let mut builder = PathBuilder::new_with_fill_type(PathFillType::EvenOdd);
+    builder.add_rect(layer.shape_bounds.with_outset((margin, margin)), None, None);
+    builder.add_path(&hole);
+    let path = builder.snapshot();
+
+    let mut paint = Paint::new(Color4f::from(shadow.color), None);
+    paint.set_anti_alias(true);
+    paint.set_mask_filter(MaskFilter::blur(BlurStyle::Normal, shadow.radius, false));
+    paint.set_alpha_f(opacity * shadow.color.alpha);
+
+    let save_count = canvas.save();
+    layer.clip_to_shape(canvas, ClipOp::Intersect, true);
+    canvas.draw_path(&path, &paint);
+    canvas.restore_to_count(save_count);
+
+    layer.shape_bounds
+}
+
+// Fill the bounds with the background image, the caller clips it to the layer shape.
+fn draw_background_image(canvas: &Canvas, background: &BackgroundImage, bounds: Rect) {
+    let image = &background.image;
+    let sampling = SamplingOptions::new(FilterMode::Linear, MipmapMode::None);
+    let mut paint = Paint::default();
+    paint.set_anti_alias(true);
+    match background.mode {
+        BackgroundImageMode::Stretch => {
+            canvas.draw_image_rect_with_sampling_options(image, None, bounds, sampling, &paint);
+        }
+        BackgroundImageMode::Tile => {
+            paint.set_shader(image.to_shader((TileMode::Repeat, TileMode::Repeat), sampling, None));
+            canvas.draw_rect(bounds, &paint);
+        }
+        BackgroundImageMode::NineSlice {
+            top,
+            right,
+            bottom,
+            left,
+        } => {
+            let (center_left, center_right) = nine_slice_span(left, right, image.width());
+            let (center_top, center_bottom) = nine_slice_span(top, bottom, image.height());
+            let center = IRect::from_ltrb(center_left, center_top, center_right, center_bottom);
+            canvas.draw_image_nine(image, center, bounds, FilterMode::Linear, Some(&paint));
+        }
+    }
+}
+
+// The span of the center slice along one side of the image. Insets that
+// leave no center are scaled down: Skia draws a nine-slice with an empty
+// or inverted center as a plain stretched image.
+fn nine_slice_span(start: f32, end: f32, size: i32) -> (i32, i32) {
+    let (start, end) = (start.max(0.0), end.max(0.0));
+    let available = (size - 1).max(0) as f32;
+    let scale = if start + end > available {
+        available / (start + end)
+    } else {
+        1.0
+    };
+    let start = (start * scale).round() as i32;
+    let end = (end * scale).floor().min(available - start as f32) as i32;
+    (start, size - end)
+}
+
 /// Draw a layer into a skia::Canvas.
 /// Returns the damage rect in the layer's coordinate space.
 #[profiling::function]
@@ -59,10 +307,26 @@ pub fn draw_layer(
 
     // let bounds = Rect::from_xywh(0.0, 0.0, layer.size.width, layer.size.height);
     let bounds = layer.bounds;
+    let background_shader = paint_color_shader(&layer.background_color, bounds);
     let background_color = match layer.background_color {
         PaintColor::Solid { color } => Color4f::from(color),
-        _ => Color4f::new(1.0, 1.0, 1.0, opacity),
+        // the colors come from the gradient shader
+        _ if background_shader.is_some() => Color4f::new(1.0, 1.0, 1.0, 1.0),
+        _ => Color4f::new(1.0, 1.0, 1.0, 0.0),
     };
+    // Tint the blurred backdrop
+    if layer.blend_mode == crate::types::BlendMode::BackgroundBlur
+        && layer.backdrop_effect.tint.alpha * opacity > 0.0
+    {
+        let mut tint_paint = Paint::new(Color4f::from(layer.backdrop_effect.tint), None);
+        tint_paint.set_anti_alias(true);
+        tint_paint.set_alpha_f(layer.backdrop_effect.tint.alpha * opacity);
+        let save_count = canvas.save();
+        layer.clip_to_shape(canvas, skia_safe::ClipOp::Intersect, false);
+        layer.draw_shape(canvas, &tint_paint);
+        canvas.restore_to_count(save_count);
+        draw_damage.join(bounds);
+    }
     {
         if (background_color.a * opacity) > 0.0 {
             let save_count = canvas.save();
@@ -71,6 +335,7 @@ pub fn draw_layer(
             // Draw the background color.
 
             let mut background_paint = Paint::new(background_color, None);
+            background_paint.set_shader(background_shader);
             background_paint.set_anti_alias(true);
             background_paint.set_style(PaintStyle::Fill);
             if layer.blend_mode == crate::types::BlendMode::BackgroundBlur {
@@ -80,13 +345,15 @@ pub fn draw_layer(
                 layer.draw_shape(canvas, &background_paint);
             }
 
-            if layer.blend_mode == crate::types::BlendMode::BackgroundBlur {
+            if layer.blend_mode == crate::types::BlendMode::BackgroundBlur
+                && layer.backdrop_effect.noise > 0.0
+            {
                 let w = layer.size.width.ceil() as i32;
                 let h = layer.size.height.ceil() as i32;
                 if let Some(noise_img) = get_noise_image(canvas, w, h) {
                     let mut paint = Paint::default();
                     paint.set_blend_mode(skia_safe::BlendMode::SoftLight);
-                    paint.set_alpha(50);
+                    paint.set_alpha_f(layer.backdrop_effect.noise.min(1.0));
                     canvas.draw_image(&noise_img, (0.0, 0.0), Some(&paint));
                 }
             }
@@ -95,6 +362,14 @@ pub fn draw_layer(
             draw_damage.join(bounds);
         }
     }
+    // Draw the background image over the background color
+    if let Some(background_image) = layer.background_image.as_ref() {
+        let save_count = canvas.save();
+        layer.clip_to_shape(canvas, ClipOp::Intersect, true);
+        draw_background_image(canvas, background_image, bounds);
+        canvas.restore_to_count(save_count);
+        draw_damage.join(bounds);
+    }
     // Draw shadow
     if layer.shadow_color.alpha > 0.0 {
         let mut shadow_paint = Paint::new(Color4f::from(layer.shadow_color), None);
@@ -127,6 +402,19 @@ pub fn draw_layer(
         draw_damage.join(damage_rect);
     }
 
+    // Draw the shadows list, the first shadow is on top like css box-shadow
+    for shadow in layer.shadows.iter().rev() {
+        if shadow.color.alpha <= 0.0 {
+            continue;
+        }
+        let damage_rect = if shadow.inset {
+            draw_inset_shadow(canvas, layer, shadow, opacity)
+        } else {
+            draw_outer_shadow(canvas, layer, shadow, opacity)
+        };
+        draw_damage.join(damage_rect);
+    }
+
     // Draw content if any
     if let Some(content) = renderable.content_cache.as_ref() {
         let save_count = canvas.save();
@@ -150,15 +438,24 @@ pub fn draw_layer(
 
     // Draw border
     if layer.border_width > 0.0 {
+        let border_shader = paint_color_shader(&layer.border_color, bounds);
         let mut border_color = match layer.border_color {
             PaintColor::Solid { color } => Color4f::from(color),
-            _ => Color4f::new(1.0, 1.0, 1.0, opacity),
+            _ if border_shader.is_some() => Color4f::new(1.0, 1.0, 1.0, 1.0),
+            _ => Color4f::new(1.0, 1.0, 1.0, 0.0),
         };
         border_color.a *= opacity;
         let mut border_paint = Paint::new(border_color, None);
+        border_paint.set_shader(border_shader);
         border_paint.set_anti_alias(true);
         border_paint.set_style(PaintStyle::Stroke);
         border_paint.set_stroke_width(layer.border_width);
+        if let Some(intervals) = layer.border_dash_intervals() {
+            if matches!(layer.border_style, crate::types::BorderStyle::Dotted) {
+                border_paint.set_stroke_cap(PaintCap::Round);
+            }
+            border_paint.set_path_effect(PathEffect::dash(&intervals, layer.border_dash_phase));
+        }
         layer.draw_shape(canvas, &border_paint);
         draw_damage.join(bounds.with_outset((layer.border_width / 2.0, layer.border_width / 2.0)));
     }
@@ -166,7 +463,9 @@ pub fn draw_layer(
     if layer.blend_mode == crate::types::BlendMode::BackgroundBlur
         && (background_color.a * opacity) > 0.0
     {
-        draw_damage.outset((BACKGROUND_BLUR_SIGMA, BACKGROUND_BLUR_SIGMA));
+        // the blur reaches about three sigmas around the layer
+        let blur_extent = layer.backdrop_effect.blur_sigma.max(0.0) * 3.0;
+        draw_damage.outset((blur_extent, blur_extent));
     }
 
     draw_damage
@@ -2,10 +2,14 @@
 //! The functions in this module are used to draw into a Skia canvas
 //! independent from the backend
 
+pub(crate) mod debug_overlays;
 pub(crate) mod layer;
 pub(crate) mod scene;
+pub(crate) mod surface_cache;
+pub use debug_overlays::DebugOverlays;
 pub use layer::draw_layer;
 pub use scene::{
-    draw_scene, node_tree_list, node_tree_list_visible, paint_node_tree, print_scene,
-    render_node_tree,
+    draw_scene, node_tree_list, node_tree_list_visible, paint_node_tree,
+    paint_node_tree_with_cache, print_scene, render_node_tree, render_node_tree_with_cache,
 };
+pub use surface_cache::{SurfaceCache, SurfaceCacheStats, DEFAULT_SURFACE_CACHE_BUDGET};
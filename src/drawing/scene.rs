@@ -1,6 +1,7 @@
 #![allow(warnings)]
 
 use indextree::{Arena, NodeId};
+use once_cell::sync::Lazy;
 use skia::{gpu::ganesh::gl::direct_contexts, FontStyle, Surface};
 use skia_safe::Canvas;
 use skia_safe::Contains;
@@ -12,36 +13,28 @@ use crate::{
         node::{SceneNode, SceneNodeRenderable},
         scene::Scene,
         storage::{FlatStorage, FlatStorageData, TreeStorageId},
-        NodeRef,
+        Engine, NodeRef,
     },
     layers::layer::render_layer::{self, RenderLayer},
-    types::Color,
+    types::{BackdropEffect, Color},
     utils::{self, save_image},
 };
 
-use super::layer::{draw_debug, draw_layer};
-use std::{
-    collections::{HashMap, HashSet},
-    iter::IntoIterator,
-};
+use super::debug_overlays::draw_debug_overlays;
+use super::layer::{draw_debug, draw_layer, filters_image_filter};
+use super::surface_cache::{SurfaceCache, SurfaceKey, DEFAULT_SURFACE_CACHE_BUDGET};
+use std::{collections::HashSet, iter::IntoIterator};
 
-/// Manual filter-chain downsample factor (scale-down → blur → scale-up).
-///
-/// Now that the backdrop downscaling is done by the save_layer itself via
-/// [`set_backdrop_scale`] (Skia's experimental small-save-layer feature), the filter
-/// chain blurs at full resolution — set to 1.0 so we don't downscale twice. The
-/// `matrix_transform` stages collapse to identity and the blur uses the full sigma.
-pub(crate) const BACKGROUND_BLUR_DOWNSAMPLE: f32 = 1.0;
+/// The surface cache used by `render_node_tree` and `paint_node_tree`,
+/// which draw the nodes without their scene
+static SHARED_SURFACE_CACHE: Lazy<SurfaceCache> =
+    Lazy::new(|| SurfaceCache::new(DEFAULT_SURFACE_CACHE_BUDGET));
 
-/// Backdrop snapshot scale for the `BackgroundBlur` save_layer.
-///
-/// Passed to Skia's experimental `fExperimentalBackdropScale`: the backdrop is captured
-/// and filtered into an offscreen buffer this much smaller in each dimension (so
-/// `BACKGROUND_BLUR_SAVE_SCALE²` of the pixels). Since the result is heavily blurred the
-/// reduced resolution is imperceptible, but the layer is far cheaper to allocate and blur.
-pub(crate) const BACKGROUND_BLUR_SAVE_SCALE: f32 = 0.1;
+/// Smallest backdrop snapshot scale, below it the snapshot is a few pixels at most.
+const MIN_BACKDROP_SCALE: f32 = 0.01;
 
 /// Sets the experimental backdrop downscale factor on a [`skia_safe::canvas::SaveLayerRec`].
+/// The scale is clamped to `(0.0, 1.0]`; non finite values keep the full resolution.
 ///
 /// skia-safe exposes no setter for `SkCanvas::SaveLayerRec::fExperimentalBackdropScale`
 /// (the field is private and `NativeAccess` is not re-exported), so we mirror the
@@ -76,115 +69,105 @@ fn set_backdrop_scale(rec: &mut skia_safe::canvas::SaveLayerRec, scale: f32) {
     );
     // SAFETY: identical #[repr(C)] layout (size asserted above); we only write the trailing f32.
     let mirror = unsafe { &mut *(rec as *mut _ as *mut SaveLayerRecLayout) };
-    mirror.experimental_backdrop_scale = scale;
+    mirror.experimental_backdrop_scale = if scale.is_finite() {
+        scale.clamp(MIN_BACKDROP_SCALE, 1.0)
+    } else {
+        1.0
+    };
 }
 
-// Thread-local caches so the filter objects are built once per rendering thread
+/// Number of distinct backdrop filters kept per rendering thread.
+const BACKDROP_FILTER_CACHE_SIZE: usize = 8;
+
+// Thread-local cache so the filter objects are built once per rendering thread
 // and reused every frame. Skia ImageFilters are immutable ref-counted descriptors
 // (no GPU state), so cloning is just an atomic ref-count bump.
+// Entries are keyed by (blur_sigma, saturation, brightness), the most recently
+// used first.
 thread_local! {
-    static BACKDROP_FILTER_CACHE: std::cell::RefCell<Option<skia_safe::ImageFilter>> =
-        const { std::cell::RefCell::new(None) };
-    static BACKDROP_FILTER_VIBRANCY_CACHE: std::cell::RefCell<Option<skia_safe::ImageFilter>> =
-        const { std::cell::RefCell::new(None) };
+    static BACKDROP_FILTER_CACHE: std::cell::RefCell<Vec<((f32, f32, f32), skia_safe::ImageFilter)>> =
+        const { std::cell::RefCell::new(Vec::new()) };
 }
 
-/// Returns the backdrop filter for `BackgroundBlur`, creating it on first call per thread.
+/// Returns the backdrop filter for a `BackgroundBlur` layer with the given effect,
+/// creating it on first use per thread.
 ///
-/// The filter chain is: scale_down → blur (smaller kernel) → scale_up [→ vibrancy].
-/// Parameters are derived from `BACKGROUND_BLUR_SIGMA` and `BACKGROUND_BLUR_DOWNSAMPLE`.
-fn backdrop_filter(apply_vibrancy: bool) -> Option<skia_safe::ImageFilter> {
-    let cache = if apply_vibrancy {
-        &BACKDROP_FILTER_VIBRANCY_CACHE
-    } else {
-        &BACKDROP_FILTER_CACHE
-    };
-    cache.with(|cell| {
-        let mut opt = cell.borrow_mut();
-        if opt.is_none() {
-            *opt = build_backdrop_filter(BACKGROUND_BLUR_SIGMA, apply_vibrancy);
+/// While an effect is animated a new filter is built every frame; the cache keeps
+/// the filters of the resting materials around.
+fn backdrop_filter(effect: &BackdropEffect) -> Option<skia_safe::ImageFilter> {
+    let key = (effect.blur_sigma, effect.saturation, effect.brightness);
+    BACKDROP_FILTER_CACHE.with(|cell| {
+        let mut cache = cell.borrow_mut();
+        if let Some(index) = cache.iter().position(|(k, _)| *k == key) {
+            let entry = cache.remove(index);
+            let filter = entry.1.clone();
+            cache.insert(0, entry);
+            return Some(filter);
         }
-        opt.clone()
+        let filter =
+            build_backdrop_filter(effect.blur_sigma, effect.saturation, effect.brightness)?;
+        cache.insert(0, (key, filter.clone()));
+        cache.truncate(BACKDROP_FILTER_CACHE_SIZE);
+        Some(filter)
     })
 }
 
-/// Builds the backdrop `ImageFilter` from scratch.
+/// Builds the backdrop `ImageFilter` from scratch: blur → saturation and brightness.
+/// Either step is left out when it has no effect; `None` when neither has.
 ///
 /// Prefer `backdrop_filter()` over calling this directly so the result is cached.
-fn build_backdrop_filter(blur_sigma: f32, apply_vibrancy: bool) -> Option<skia_safe::ImageFilter> {
-    let s = BACKGROUND_BLUR_DOWNSAMPLE;
-
-    // Scale the backdrop down, blur with a proportionally smaller sigma, then scale back
-    // up. The save_layer bounds already constrain the output region so no explicit
-    // crop_rect is needed. When `s == 1.0` the scale stages are identity and the blur
-    // runs at full resolution — downsampling is delegated to the save_layer's backdrop
-    // scale (see [`set_backdrop_scale`]) instead, so skip building the no-op transforms.
-    let blurred = if s == 1.0 {
-        skia_safe::image_filters::blur(
+/// Downsampling is delegated to the save_layer's backdrop scale
+/// (see [`set_backdrop_scale`]), so the blur runs at the snapshot resolution.
+fn build_backdrop_filter(
+    blur_sigma: f32,
+    saturation: f32,
+    brightness: f32,
+) -> Option<skia_safe::ImageFilter> {
+    let blurred = if blur_sigma > 0.0 {
+        Some(skia_safe::image_filters::blur(
             (blur_sigma, blur_sigma),
             skia_safe::TileMode::Mirror,
             None,
             None,
-        )?
+        )?)
     } else {
-        let sampling = skia_safe::SamplingOptions::new(
-            skia_safe::FilterMode::Linear,
-            skia_safe::MipmapMode::None,
-        );
-        let scale_down = skia_safe::image_filters::matrix_transform(
-            &skia_safe::Matrix::scale((s, s)),
-            sampling,
-            None,
-        )?;
-        let blur = skia_safe::image_filters::blur(
-            (blur_sigma * s, blur_sigma * s),
-            skia_safe::TileMode::Mirror,
-            scale_down,
-            None,
-        )?;
-        skia_safe::image_filters::matrix_transform(
-            &skia_safe::Matrix::scale((1.0 / s, 1.0 / s)),
-            sampling,
-            blur,
-        )?
+        None
     };
 
-    if apply_vibrancy {
-        // Add a mild tone map to feel more "material".
-        // Slightly increase contrast and saturation.
-        let sat = 1.10_f32;
-        let con = 1.06_f32;
-
-        let matrix = skia_safe::ColorMatrix::new(
-            con * (0.213 + 0.787 * sat),
-            con * (0.715 - 0.715 * sat),
-            con * (0.072 - 0.072 * sat),
-            0.0,
-            0.0,
-            con * (0.213 - 0.213 * sat),
-            con * (0.715 + 0.285 * sat),
-            con * (0.072 - 0.072 * sat),
-            0.0,
-            0.0,
-            con * (0.213 - 0.213 * sat),
-            con * (0.715 - 0.715 * sat),
-            con * (0.072 + 0.928 * sat),
-            0.0,
-            0.0,
-            0.0,
-            0.0,
-            0.0,
-            1.0,
-            0.0,
-        );
+    if saturation == 1.0 && brightness == 1.0 {
+        return blurred;
+    }
 
-        let tone_filter = skia_safe::color_filters::matrix(&matrix, None);
+    // Tone map the blurred backdrop to feel more "material".
+    let sat = saturation;
+    let con = brightness;
+
+    let matrix = skia_safe::ColorMatrix::new(
+        con * (0.213 + 0.787 * sat),
+        con * (0.715 - 0.715 * sat),
+        con * (0.072 - 0.072 * sat),
+        0.0,
+        0.0,
+        con * (0.213 - 0.213 * sat),
+        con * (0.715 + 0.285 * sat),
+        con * (0.072 - 0.072 * sat),
+        0.0,
+        0.0,
+        con * (0.213 - 0.213 * sat),
+        con * (0.715 - 0.715 * sat),
+        con * (0.072 + 0.928 * sat),
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        0.0,
+        1.0,
+        0.0,
+    );
 
-        // Apply vibrancy on top of the upscaled blur.
-        skia_safe::image_filters::color_filter(tone_filter, blurred, None)
-    } else {
-        Some(blurred)
-    }
+    let tone_filter = skia_safe::color_filters::matrix(&matrix, None);
+
+    skia_safe::image_filters::color_filter(tone_filter, blurred, None)
 }
 
 pub trait DrawScene {
@@ -198,13 +181,14 @@ pub trait DrawScene {
 
 /// Draw the scene to the given skia::Canvas
 pub fn draw_scene(canvas: &skia::Canvas, scene: std::sync::Arc<Scene>, root_id: NodeRef) {
+    scene.surface_cache.release_removed();
     scene.with_arena(|scene_arena| {
         scene.with_renderable_arena(|renderables_arena| {
             if let Some(root) = scene_arena.get(root_id.into()) {
                 let node = root.get();
                 let restore_point = canvas.save();
                 set_node_transform(node, canvas);
-                render_node_tree(
+                render_node_tree_with_cache(
                     root_id,
                     scene_arena,
                     renderables_arena,
@@ -212,8 +196,12 @@ pub fn draw_scene(canvas: &skia::Canvas, scene: std::sync::Arc<Scene>, root_id:
                     1.0,
                     None,
                     None,
+                    Some(scene.surface_cache()),
                 );
                 canvas.restore_to_count(restore_point);
+                if let Some(engine) = node.engine_id.and_then(Engine::get_by_id) {
+                    draw_debug_overlays(&engine, root_id, scene_arena, renderables_arena, canvas);
+                }
             }
         });
     });
@@ -234,6 +222,10 @@ pub fn node_tree_list(
         let children = node_id.children(arena).collect::<Vec<NodeId>>();
         for child_id in children.iter() {
             let child_ref = NodeRef(child_id.clone());
+            // the mask is not drawn as a child
+            if node.render_layer.mask == Some(child_ref) {
+                continue;
+            }
 
             nodes.extend(node_tree_list(child_ref, arena, context_opacity));
         }
@@ -260,7 +252,7 @@ pub fn node_tree_list_visible<'a>(
             visible_nodes.push((node_ref.clone(), context_opacity.clone()));
 
             if context_opacity.to_bits() == 1_f32.to_bits()
-                && node.render_layer.blend_mode != crate::prelude::BlendMode::BackgroundBlur
+                && node.render_layer.blend_mode == crate::prelude::BlendMode::Normal
             {
                 damage.push(rbounds);
             }
@@ -269,11 +261,6 @@ pub fn node_tree_list_visible<'a>(
     visible_nodes
 }
 
-use std::sync::{Mutex, Once};
-
-static INIT_SURFACES: Once = Once::new();
-static mut NODE_SURFACES: Option<Mutex<HashMap<NodeRef, (usize, Surface, skia::Image)>>> = None;
-
 fn surface_offset_for_render_layer(render_layer: &RenderLayer) -> skia_safe::Point {
     skia_safe::Point::new(
         render_layer.border_width / 2.0,
@@ -286,62 +273,64 @@ fn surface_size_for_render_layer(render_layer: &RenderLayer) -> skia_safe::Point
         .with_outset((render_layer.border_width, render_layer.border_width));
     skia_safe::Point::new(bounds.width(), bounds.height())
 }
+/// The area drawn by the node and its descendants in the node coordinates,
+/// grown by the reach of its filters
+fn group_bounds_for_render_layer(render_layer: &RenderLayer) -> skia_safe::Rect {
+    let outset = render_layer.border_width + RenderLayer::filters_outset(&render_layer.filters);
+    render_layer
+        .bounds_with_children
+        .with_outset((outset, outset))
+}
 pub fn set_surface_for_node(
+    cache: &SurfaceCache,
     node_ref: &NodeRef,
     surface: Surface,
     image: skia::Image,
     frame: usize,
 ) {
-    unsafe {
-        if let Some(ref surfaces) = NODE_SURFACES {
-            let mut surfaces = surfaces.lock().unwrap();
-            surfaces.insert(node_ref.clone(), (frame, surface, image));
-        }
-    }
+    cache.insert(*node_ref, frame, surface, image);
 }
 pub fn surface_for_node(
+    cache: &SurfaceCache,
     node_ref: &NodeRef,
     node: &SceneNode,
     render_layer: &RenderLayer,
-    context: &mut skia_safe::gpu::DirectContext,
+    context: Option<&mut skia_safe::gpu::DirectContext>,
 ) -> Option<(usize, Surface, skia::Image)> {
-    INIT_SURFACES.call_once(|| unsafe {
-        NODE_SURFACES = Some(Mutex::new(HashMap::new()));
-    });
-
-    unsafe {
-        if let Some(ref surfaces) = NODE_SURFACES {
-            let mut surfaces = surfaces.lock().unwrap();
-            if let Some((frame, surface, image)) = surfaces.get(node_ref) {
-                if surface.direct_context().unwrap().id() == context.id() {
-                    let size = surface_size_for_render_layer(render_layer);
-                    if surface.width() >= size.x as i32 && surface.height() >= size.y as i32 {
-                        return Some((*frame, surface.clone(), image.clone()));
-                    } else {
-                        // Surface size is not enough, remove the surface
-                        drop(surface);
-                        surfaces.remove(&node_ref);
-                    }
-                } else {
-                    // Surface is not created with the current context, remove the surface
-                    drop(surface);
-                    surfaces.remove(&node_ref);
-                }
-            }
-            // Create a new surface for the node
-            let mut surface = create_surface_for_node(node, render_layer, context)?;
-            let image = surface.image_snapshot();
-
-            surfaces.insert(node_ref.clone(), (0, surface.clone(), image.clone()));
-            return Some((0, surface, image));
-        }
+    // reuse the surface if it was created with the current context and is large enough
+    let size = surface_size_for_render_layer(render_layer);
+    let context_id = context.as_ref().map(|context| context.id());
+    if let Some(cached) = cache.get(node_ref, (size.x as i32, size.y as i32), context_id) {
+        return Some(cached);
     }
-    None
+    // Create a new surface for the node
+    let mut surface = create_cache_surface_for_node(node, render_layer, context)?;
+    let image = surface.image_snapshot();
+
+    cache.insert(*node_ref, 0, surface.clone(), image.clone());
+    Some((0, surface, image))
 }
+/// Image-cached layers larger than this, in pixels, in either dimension
+/// are cached in tiles
+pub const MAX_CACHE_SURFACE_SIZE: f32 = 4096.0;
+/// The size, in pixels, of the tiles of the large image-cached layers
+pub const CACHE_TILE_SIZE: i32 = 512;
+
+/// Creates the GPU offscreen surface of an image-cached node
 pub fn create_surface_for_node(
     node: &SceneNode,
     render_layer: &RenderLayer,
     context: &mut skia_safe::gpu::DirectContext,
+) -> Option<Surface> {
+    create_cache_surface_for_node(node, render_layer, Some(context))
+}
+
+/// Creates the offscreen surface of an image-cached node, on the GPU when a
+/// context is provided, in CPU memory otherwise.
+pub(crate) fn create_cache_surface_for_node(
+    node: &SceneNode,
+    render_layer: &RenderLayer,
+    context: Option<&mut skia_safe::gpu::DirectContext>,
 ) -> Option<Surface> {
     let bounds = surface_size_for_render_layer(render_layer);
     const safe_multiplier: f32 = 1.2;
@@ -355,7 +344,14 @@ pub fn create_surface_for_node(
         // );
         return None;
     }
+    create_offscreen_surface(width, height, context)
+}
 
+fn create_offscreen_surface(
+    width: i32,
+    height: i32,
+    context: Option<&mut skia_safe::gpu::DirectContext>,
+) -> Option<Surface> {
     let image_info = skia_safe::ImageInfo::new(
         (width, height),
         skia_safe::ColorType::RGBA8888,
@@ -363,23 +359,230 @@ pub fn create_surface_for_node(
         None,
     );
 
-    let surface = skia_safe::gpu::surfaces::render_target(
-        context,
-        skia_safe::gpu::Budgeted::No,
-        &image_info,
-        None,
-        skia_safe::gpu::SurfaceOrigin::TopLeft,
-        None,
-        false,
-        false,
-    )
-    .unwrap();
+    match context {
+        Some(context) => skia_safe::gpu::surfaces::render_target(
+            context,
+            skia_safe::gpu::Budgeted::No,
+            &image_info,
+            None,
+            skia_safe::gpu::SurfaceOrigin::TopLeft,
+            None,
+            false,
+            false,
+        ),
+        None => skia_safe::surfaces::raster(&image_info, None, None),
+    }
+}
 
-    Some(surface)
+/// True if the image-cached layer is too large for a single surface
+/// and is cached in tiles instead
+fn needs_tiled_cache(render_layer: &RenderLayer) -> bool {
+    let size = surface_size_for_render_layer(render_layer);
+    size.x.max(size.y) > MAX_CACHE_SURFACE_SIZE
 }
 
-#[profiling::function]
-/// paint a node and his subtree in the provided canvas
+// True if the cached image of the node, drawn at the content frame
+// `recorded_frame`, is out of date
+fn cached_frame_is_stale(
+    scene_node: &SceneNode,
+    scene_arena: &Arena<SceneNode>,
+    recorded_frame: usize,
+) -> bool {
+    let current_frame = scene_node.content_frame;
+    // If this node is following another node (replicate_node),
+    // we need to check BOTH:
+    // 1. Has the follower itself changed? (its own frame)
+    // 2. Has the followed node's content changed? (source frame)
+    if let Some(following_ref) = scene_node.following {
+        // Guard against a freed leader node — indextree's Node::get() panics
+        // on freed slots, so we must check is_removed() first.
+        let followed_frame = scene_arena
+            .get(following_ref.0)
+            .filter(|n| !n.is_removed())
+            .map(|n| n.get().content_frame)
+            .unwrap_or(0);
+        // Repaint if either the follower or the followed node changed
+        current_frame != recorded_frame || followed_frame != recorded_frame
+    } else {
+        current_frame != recorded_frame
+    }
+}
+
+// Rounds the edges of the rect to device pixels, when the matrix keeps it
+// axis aligned
+fn snap_rect_to_device(rect: skia_safe::Rect, to_device: &skia_safe::Matrix) -> skia_safe::Rect {
+    if !to_device.rect_stays_rect() {
+        return rect;
+    }
+    let Some(to_local) = to_device.invert() else {
+        return rect;
+    };
+    let (device_rect, _) = to_device.map_rect(rect);
+    let (snapped, _) = to_local.map_rect(skia_safe::Rect::from(device_rect.round()));
+    snapped
+}
+
+/// Draws an image-cached node too large for a single surface from tiles of
+/// `CACHE_TILE_SIZE` pixels. Only the tiles visible in the canvas are drawn,
+/// and after a change only the damaged ones are repainted; the tiles are in
+/// layer coordinates and drawn at the content frame of the node, so they are
+/// reused without repainting when the layer scrolls.
+/// Returns false if the canvas can't host offscreen surfaces.
+fn draw_tiled_node(
+    cache: &SurfaceCache,
+    node_ref: NodeRef,
+    scene_node: &SceneNode,
+    scene_arena: &Arena<SceneNode>,
+    renderables_arena: &FlatStorageData<SceneNodeRenderable>,
+    render_canvas: &skia_safe::Canvas,
+    context_opacity: f32,
+    occluded: Option<&HashSet<NodeRef>>,
+    damage_region: Option<&skia_safe::Region>,
+    mask: Option<NodeRef>,
+) -> bool {
+    let Some(rendering_surface) = (unsafe { render_canvas.surface() }) else {
+        return false;
+    };
+    let mut direct_context = rendering_surface
+        .recording_context()
+        .map(|mut recording_ctx| recording_ctx.as_direct_context().unwrap());
+    let context_id = direct_context.as_ref().map(|context| context.id());
+    let Some(device_clip) = render_canvas.device_clip_bounds() else {
+        // nothing is visible
+        return true;
+    };
+    let device_clip = skia_safe::Rect::from(device_clip);
+
+    let render_layer = &scene_node.render_layer;
+    let dbg_info = scene_node._debug_info.as_ref();
+    let current_frame = scene_node.content_frame;
+    let surface_size = surface_size_for_render_layer(render_layer);
+    let surface_offset = surface_offset_for_render_layer(render_layer);
+    // the cached area, in layer coordinates
+    let content = skia_safe::Rect::from_xywh(
+        render_layer.bounds_with_children.x() - surface_offset.x,
+        render_layer.bounds_with_children.y() - surface_offset.y,
+        surface_size.x,
+        surface_size.y,
+    );
+    let tile_size = CACHE_TILE_SIZE as f32;
+    let columns = (content.width() / tile_size).ceil() as i32;
+    let rows = (content.height() / tile_size).ceil() as i32;
+    let to_device = render_canvas.local_to_device_as_3x3();
+
+    // the layer effects apply to the tiles as a group
+    let mut paint = skia_safe::Paint::default();
+    paint.set_alpha_f(context_opacity * render_layer.opacity);
+    if let Some(blend_mode) = render_layer.blend_mode.to_skia() {
+        paint.set_blend_mode(blend_mode);
+    }
+    let image_filter =
+        filters_image_filter(&render_layer.filters, render_layer.image_filter.clone());
+    if image_filter.is_some() || render_layer.color_filter.is_some() {
+        if let Some(filter_bounds) = render_layer.image_filter_bounds.as_ref() {
+            render_canvas.clip_rect(filter_bounds, None, None);
+        }
+    }
+    paint.set_image_filter(image_filter);
+    paint.set_color_filter(render_layer.color_filter.clone());
+    // the group only covers the tiled content
+    render_canvas.save_layer(
+        &skia_safe::canvas::SaveLayerRec::default()
+            .bounds(&content)
+            .paint(&paint),
+    );
+
+    for row in 0..rows {
+        for column in 0..columns {
+            let tile_rect = skia_safe::Rect::from_xywh(
+                content.x() + column as f32 * tile_size,
+                content.y() + row as f32 * tile_size,
+                tile_size,
+                tile_size,
+            );
+            let (device_rect, _) = to_device.map_rect(tile_rect);
+            if !device_rect.intersects(device_clip) {
+                continue;
+            }
+            let key = SurfaceKey::tile(node_ref, (column, row));
+            let cached = cache.get(key, (CACHE_TILE_SIZE, CACHE_TILE_SIZE), context_id);
+            let is_new = cached.is_none();
+            let Some((recorded_frame, mut surface, mut image)) = cached.or_else(|| {
+                let mut surface = create_offscreen_surface(
+                    CACHE_TILE_SIZE,
+                    CACHE_TILE_SIZE,
+                    direct_context.as_mut(),
+                )?;
+                let image = surface.image_snapshot();
+                Some((0, surface, image))
+            }) else {
+                continue;
+            };
+
+            let is_stale = cached_frame_is_stale(scene_node, scene_arena, recorded_frame);
+            // changes outside of the damage did not affect the tile
+            let is_damaged = damage_region.map_or(true, |damage| {
+                damage.intersects_rect(crate::engine::occlusion::rect_to_irect(device_rect))
+            });
+            if is_new || (is_stale && is_damaged) || dbg_info.is_some() {
+                let tile_canvas = surface.canvas();
+                tile_canvas.clear(skia_safe::Color::TRANSPARENT);
+                tile_canvas.save();
+                tile_canvas.set_matrix(&skia_safe::M44::translate(
+                    -tile_rect.x(),
+                    -tile_rect.y(),
+                    0.0,
+                ));
+                paint_node_tree_with_cache(
+                    node_ref,
+                    scene_arena,
+                    renderables_arena,
+                    tile_canvas,
+                    render_layer,
+                    context_opacity,
+                    true,
+                    dbg_info,
+                    false,
+                    occluded,
+                    None, // damage is in screen-space, not tile-space
+                    Some(cache),
+                );
+                tile_canvas.restore();
+                image = surface.image_snapshot();
+                cache.insert(key, current_frame, surface, image.clone());
+            } else if is_stale {
+                cache.set_frame(key, current_frame);
+            }
+
+            // adjacent tiles share their snapped edges, and the strict source
+            // rect keeps the filtering inside the tile: no seams between them
+            let tile_bounds = skia_safe::Rect::from_wh(tile_size, tile_size);
+            render_canvas.draw_image_rect_with_sampling_options(
+                &image,
+                Some((&tile_bounds, skia_safe::canvas::SrcRectConstraint::Strict)),
+                snap_rect_to_device(tile_rect, &to_device),
+                skia_safe::SamplingOptions::new(
+                    skia_safe::FilterMode::Linear,
+                    skia_safe::MipmapMode::None,
+                ),
+                &skia_safe::Paint::default(),
+            );
+        }
+    }
+    if let Some(mask_ref) = mask {
+        draw_mask(
+            mask_ref,
+            scene_arena,
+            renderables_arena,
+            render_canvas,
+            Some(cache),
+        );
+    }
+    true
+}
+
+/// paint a node and his subtree in the provided canvas, the image-cached
+/// nodes are drawn through a surface cache shared by all the scenes
 pub fn paint_node_tree(
     node_ref: NodeRef,
     scene_arena: &Arena<SceneNode>,
@@ -392,6 +595,40 @@ pub fn paint_node_tree(
     skip_self: bool,
     occluded: Option<&HashSet<NodeRef>>,
     damage_region: Option<&skia_safe::Region>,
+) {
+    paint_node_tree_with_cache(
+        node_ref,
+        scene_arena,
+        renderables_arena,
+        render_canvas,
+        render_layer,
+        context_opacity,
+        offscreen,
+        dbg_info,
+        skip_self,
+        occluded,
+        damage_region,
+        Some(&SHARED_SURFACE_CACHE),
+    );
+}
+
+#[profiling::function]
+/// paint a node and his subtree in the provided canvas, the image-cached
+/// nodes are drawn through the surfaces of `surface_cache`, usually the
+/// cache of the scene
+pub fn paint_node_tree_with_cache(
+    node_ref: NodeRef,
+    scene_arena: &Arena<SceneNode>,
+    renderables_arena: &FlatStorageData<SceneNodeRenderable>,
+    render_canvas: &skia_safe::Canvas,
+    render_layer: &RenderLayer,
+    context_opacity: f32,
+    offscreen: bool,
+    dbg_info: Option<&DrawDebugInfo>,
+    skip_self: bool,
+    occluded: Option<&HashSet<NodeRef>>,
+    damage_region: Option<&skia_safe::Region>,
+    surface_cache: Option<&SurfaceCache>,
 ) {
     let node_id: TreeStorageId = node_ref.into();
 
@@ -420,10 +657,29 @@ pub fn paint_node_tree(
     }
     // let bounds = skia_safe::Rect::from_wh(render_layer.size.x, render_layer.size.y);
     // canvas.clip_rect(bounds, None, None);
-    node_id.children(scene_arena).for_each(|child_id| {
+    let mut children: Vec<_> = node_id.children(scene_arena).collect();
+    if render_layer.preserve_3d {
+        // draw the sublayers back to front by the depth of their center
+        let depth = |child_id: &TreeStorageId| {
+            let child = scene_arena.get(*child_id).unwrap().get();
+            let bounds = child.render_layer.bounds;
+            child
+                .render_layer
+                .local_transform
+                .map(bounds.center_x(), bounds.center_y(), 0.0, 1.0)
+                .z
+        };
+        children.sort_by(|a, b| depth(a).total_cmp(&depth(b)));
+    }
+    children.into_iter().for_each(|child_id| {
         let child_ref = NodeRef(child_id);
         let child = scene_arena.get(child_id).unwrap().get();
 
+        // The mask layer is drawn by render_node_tree over the whole subtree
+        if render_layer.mask == Some(child_ref) {
+            return;
+        }
+
         // Damage-based subtree culling: if the child (including all its
         // descendants) falls entirely outside the damage region, skip it.
         if let Some(region) = damage_region {
@@ -435,8 +691,13 @@ pub fn paint_node_tree(
         }
 
         let restore_point = render_canvas.save();
-        set_node_transform(child, render_canvas);
-        render_node_tree(
+        if render_layer.preserve_3d {
+            // keep the depth so the sublayers share the parent 3D space
+            render_canvas.concat_44(&child.render_layer.local_transform);
+        } else {
+            set_node_transform(child, render_canvas);
+        }
+        render_node_tree_with_cache(
             child_ref,
             scene_arena,
             renderables_arena,
@@ -444,6 +705,7 @@ pub fn paint_node_tree(
             context_opacity,
             occluded,
             damage_region,
+            surface_cache,
         );
         render_canvas.restore_to_count(restore_point);
     });
@@ -456,8 +718,8 @@ pub fn set_node_transform(node: &SceneNode, canvas: &Canvas) {
 }
 
 /// Render a node and his subtree in the provided canvas
-/// taking care of the image caching if needed
-#[profiling::function]
+/// taking care of the image caching if needed, through a surface cache
+/// shared by all the scenes
 pub fn render_node_tree(
     node_ref: NodeRef,
     scene_arena: &Arena<SceneNode>,
@@ -466,6 +728,33 @@ pub fn render_node_tree(
     context_opacity: f32,
     occluded: Option<&HashSet<NodeRef>>,
     damage_region: Option<&skia_safe::Region>,
+) {
+    render_node_tree_with_cache(
+        node_ref,
+        scene_arena,
+        renderables_arena,
+        render_canvas,
+        context_opacity,
+        occluded,
+        damage_region,
+        Some(&SHARED_SURFACE_CACHE),
+    );
+}
+
+/// Render a node and his subtree in the provided canvas
+/// taking care of the image caching if needed: the image-cached nodes are
+/// drawn through the surfaces of `surface_cache`, usually the cache of the
+/// scene, or directly without it
+#[profiling::function]
+pub fn render_node_tree_with_cache(
+    node_ref: NodeRef,
+    scene_arena: &Arena<SceneNode>,
+    renderables_arena: &FlatStorageData<SceneNodeRenderable>,
+    render_canvas: &skia_safe::Canvas,
+    context_opacity: f32,
+    occluded: Option<&HashSet<NodeRef>>,
+    damage_region: Option<&skia_safe::Region>,
+    surface_cache: Option<&SurfaceCache>,
 ) {
     let node_id: TreeStorageId = node_ref.into();
     #[cfg(feature = "profile-with-puffin")]
@@ -487,157 +776,189 @@ pub fn render_node_tree(
     let is_self_occluded = occluded.map_or(false, |set| set.contains(&node_ref));
 
     let render_layer = &scene_node.render_layer;
+    // a removed mask layer no longer masks the subtree
+    let mask = render_layer.mask.filter(|mask_ref| {
+        scene_arena
+            .get(mask_ref.0)
+            .map_or(false, |mask_node| !mask_node.is_removed())
+    });
     let restore_point = render_canvas.save();
     // render_canvas.concat(&render_layer.local_transform.to_m33());
     let dbg_info = scene_node._debug_info.as_ref();
-    if scene_node.is_image_cached() && !is_self_occluded {
+    let image_cache = surface_cache.filter(|_| scene_node.is_image_cached() && !is_self_occluded);
+    if let Some(cache) = image_cache {
         #[cfg(feature = "profile-with-puffin")]
         profiling::puffin::profile_scope!("image_cached");
-        // in this case the layer is ping-ponged
-        if let Some(rendering_surface) = unsafe { render_canvas.surface() } {
-            if let Some(mut recording_ctx) = rendering_surface.recording_context() {
-                if let Some((recorded_frame, mut recording_surface, mut image)) = surface_for_node(
-                    &node_ref,
+        if needs_tiled_cache(render_layer) {
+            // the backdrop effects of the descendants are applied to the whole
+            // cached image, such large layers are drawn directly
+            if render_layer.backdrop_blur_region.is_none()
+                && draw_tiled_node(
+                    cache,
+                    node_ref,
                     scene_node,
-                    &render_layer,
-                    &mut recording_ctx.as_direct_context().unwrap(),
-                ) {
-                    // if there is a surface for image caching draw in it
-                    let surface_size = surface_size_for_render_layer(&render_layer);
-                    let surface_offset = surface_offset_for_render_layer(&render_layer);
-
-                    let surface_position_x = render_layer.bounds_with_children.x();
-                    let surface_position_y = render_layer.bounds_with_children.y();
-
-                    let bounds = render_layer.bounds;
-
-                    let offset = skia_safe::M44::translate(
-                        surface_offset.x - surface_position_x,
-                        surface_offset.y - surface_position_y,
-                        0.0,
+                    scene_arena,
+                    renderables_arena,
+                    render_canvas,
+                    context_opacity,
+                    occluded,
+                    damage_region,
+                    mask,
+                )
+            {
+                render_canvas.restore_to_count(restore_point);
+                return;
+            }
+        } else if let Some(rendering_surface) = unsafe { render_canvas.surface() } {
+            // in this case the layer is ping-ponged
+            // without a recording context the screen is a raster surface,
+            // the layer is then cached in CPU memory
+            let mut direct_context = rendering_surface
+                .recording_context()
+                .map(|mut recording_ctx| recording_ctx.as_direct_context().unwrap());
+            if let Some((recorded_frame, mut recording_surface, mut image)) = surface_for_node(
+                cache,
+                &node_ref,
+                scene_node,
+                &render_layer,
+                direct_context.as_mut(),
+            ) {
+                // if there is a surface for image caching draw in it
+                let surface_size = surface_size_for_render_layer(&render_layer);
+                let surface_offset = surface_offset_for_render_layer(&render_layer);
+
+                let surface_position_x = render_layer.bounds_with_children.x();
+                let surface_position_y = render_layer.bounds_with_children.y();
+
+                let bounds = render_layer.bounds;
+
+                let offset = skia_safe::M44::translate(
+                    surface_offset.x - surface_position_x,
+                    surface_offset.y - surface_position_y,
+                    0.0,
+                );
+
+                // draw into the offscreen surface
+                let current_frame = scene_node.content_frame;
+
+                let needs_repaint = cached_frame_is_stale(scene_node, scene_arena, recorded_frame);
+
+                if needs_repaint || dbg_info.is_some() {
+                    let surface_bounds = skia::Rect::from_wh(
+                        recording_surface.width() as f32,
+                        recording_surface.height() as f32,
+                    );
+                    let recording_canvas = recording_surface.canvas();
+                    // recording_canvas.clip_rect(scene_node.bounds_with_children(), Some(skia::ClipOp::Intersect), false);
+
+                    recording_canvas.clear(skia_safe::Color::TRANSPARENT);
+                    recording_canvas.save();
+                    recording_canvas.set_matrix(&offset);
+
+                    paint_node_tree_with_cache(
+                        node_ref,
+                        scene_arena,
+                        renderables_arena,
+                        &recording_canvas,
+                        &render_layer,
+                        context_opacity,
+                        true,
+                        dbg_info,
+                        false, // not self-occluded (checked above)
+                        occluded,
+                        None, // damage is in screen-space, not offscreen-surface-space
+                        Some(cache),
                     );
+                    // debug drawing
+                    // let mut paint = skia_safe::Paint::default();
+                    // paint.set_color4f(Color::new_hex("#27AC12").c4f(), None);
+                    // paint.set_stroke(true);
+                    // paint.set_stroke_width(4.0);
+                    // recording_canvas.draw_rect(render_layer.bounds_with_children, &paint);
+                    // let font_mgr = skia_safe::FontMgr::new();
+                    // let typeface = font_mgr
+                    //     .match_family_style("Inter", FontStyle::normal())
+                    //     .unwrap();
+                    // let font = skia::Font::from_typeface_with_params(typeface, 20.0, 1.0, 0.0);
+                    // paint.set_stroke(false);
+                    // recording_canvas.draw_str(
+                    //     format!("{} | {}", render_layer.opacity, current_frame),
+                    //     (
+                    //         render_layer.bounds_with_children.x() + 5.0,
+                    //         render_layer.bounds_with_children.y() + 25.0,
+                    //     ),
+                    //     &font,
+                    //     &paint,
+                    // );
+
+                    recording_canvas.restore();
+
+                    image = recording_surface.image_snapshot();
+                    // Store the current frame as the recorded frame
+                    // (we've now rendered with both our state and the followed state)
+                    set_surface_for_node(
+                        cache,
+                        &node_ref,
+                        recording_surface.clone(),
+                        image.clone(),
+                        current_frame,
+                    );
+                } // end draw into the offscreen surface
 
-                    // draw into the offscreen surface
-                    let current_frame = scene_node.frame_number;
-
-                    // If this node is following another node (replicate_node),
-                    // we need to check BOTH:
-                    // 1. Has the follower itself changed? (its own frame)
-                    // 2. Has the followed node's content changed? (source frame)
-                    let needs_repaint = if let Some(following_ref) = scene_node.following {
-                        // Guard against a freed leader node — indextree's Node::get() panics
-                        // on freed slots, so we must check is_removed() first.
-                        let followed_frame = scene_arena
-                            .get(following_ref.0)
-                            .filter(|n| !n.is_removed())
-                            .map(|n| n.get().frame_number)
-                            .unwrap_or(0);
-                        // Repaint if either the follower or the followed node changed
-                        current_frame != recorded_frame || followed_frame != recorded_frame
-                    } else {
-                        current_frame != recorded_frame
-                    };
-
-                    if needs_repaint || dbg_info.is_some() {
-                        let surface_bounds = skia::Rect::from_wh(
-                            recording_surface.width() as f32,
-                            recording_surface.height() as f32,
-                        );
-                        let recording_canvas = recording_surface.canvas();
-                        // recording_canvas.clip_rect(scene_node.bounds_with_children(), Some(skia::ClipOp::Intersect), false);
-
-                        recording_canvas.clear(skia_safe::Color::TRANSPARENT);
-                        recording_canvas.save();
-                        recording_canvas.set_matrix(&offset);
-
-                        paint_node_tree(
-                            node_ref,
-                            scene_arena,
-                            renderables_arena,
-                            &recording_canvas,
-                            &render_layer,
-                            context_opacity,
-                            true,
-                            dbg_info,
-                            false, // not self-occluded (checked above)
-                            occluded,
-                            None, // damage is in screen-space, not offscreen-surface-space
-                        );
-                        // debug drawing
-                        // let mut paint = skia_safe::Paint::default();
-                        // paint.set_color4f(Color::new_hex("#27AC12").c4f(), None);
-                        // paint.set_stroke(true);
-                        // paint.set_stroke_width(4.0);
-                        // recording_canvas.draw_rect(render_layer.bounds_with_children, &paint);
-                        // let font_mgr = skia_safe::FontMgr::new();
-                        // let typeface = font_mgr
-                        //     .match_family_style("Inter", FontStyle::normal())
-                        //     .unwrap();
-                        // let font = skia::Font::from_typeface_with_params(typeface, 20.0, 1.0, 0.0);
-                        // paint.set_stroke(false);
-                        // recording_canvas.draw_str(
-                        //     format!("{} | {}", render_layer.opacity, current_frame),
-                        //     (
-                        //         render_layer.bounds_with_children.x() + 5.0,
-                        //         render_layer.bounds_with_children.y() + 25.0,
-                        //     ),
-                        //     &font,
-                        //     &paint,
-                        // );
-
-                        recording_canvas.restore();
-
-                        image = recording_surface.image_snapshot();
-                        // Store the current frame as the recorded frame
-                        // (we've now rendered with both our state and the followed state)
-                        set_surface_for_node(
-                            &node_ref,
-                            recording_surface.clone(),
-                            image.clone(),
-                            current_frame,
-                        );
-                    } // end draw into the offscreen surface
-
-                    let mut paint = skia_safe::Paint::default();
-                    paint.set_color4f(skia_safe::Color4f::new(1.0, 0.0, 0.0, 1.0), None);
-
-                    let width = recording_surface.width() as f32;
-                    let height = recording_surface.height() as f32;
-                    let x = render_layer.bounds.x();
-                    let y = render_layer.bounds.y();
-
-                    paint.set_alpha_f(context_opacity * render_layer.opacity);
-
-                    if let Some(filter) = render_layer.image_filter.as_ref() {
-                        if let Some(filter_bounds) = render_layer.image_filter_bounds.as_ref() {
-                            render_canvas.clip_rect(&filter_bounds, None, None);
-                        }
-                        paint.set_image_filter(filter.clone());
-                    }
-                    if let Some(filter) = render_layer.color_filter.as_ref() {
-                        if let Some(filter_bounds) = render_layer.image_filter_bounds.as_ref() {
-                            render_canvas.clip_rect(&filter_bounds, None, None);
-                        }
-                        paint.set_color_filter(filter.clone());
-                    }
-                    // the render_canvas has already the transform applied
-                    let x = render_layer.bounds_with_children.x() - surface_offset.x;
-                    let y = render_layer.bounds_with_children.y() - surface_offset.y;
+                let mut paint = skia_safe::Paint::default();
+                paint.set_color4f(skia_safe::Color4f::new(1.0, 0.0, 0.0, 1.0), None);
 
-                    let opacity = context_opacity * render_layer.opacity;
+                let width = recording_surface.width() as f32;
+                let height = recording_surface.height() as f32;
+                let x = render_layer.bounds.x();
+                let y = render_layer.bounds.y();
 
-                    // Apply backdrop effects for descendant regions that need it
-                    // Converts Vec<RRect> to Path for clipping with rounded rectangles
-                    if let Some(backdrop_rrects) = &render_layer.backdrop_blur_region {
-                        profiling::scope!("background_blur_image_cached_descendants");
+                paint.set_alpha_f(context_opacity * render_layer.opacity);
+                if let Some(blend_mode) = render_layer.blend_mode.to_skia() {
+                    paint.set_blend_mode(blend_mode);
+                }
 
-                        let before_backdrop = render_canvas.save();
+                let image_filter =
+                    filters_image_filter(&render_layer.filters, render_layer.image_filter.clone());
+                if let Some(filter) = image_filter {
+                    if let Some(filter_bounds) = render_layer.image_filter_bounds.as_ref() {
+                        render_canvas.clip_rect(&filter_bounds, None, None);
+                    }
+                    paint.set_image_filter(filter);
+                }
+                if let Some(filter) = render_layer.color_filter.as_ref() {
+                    if let Some(filter_bounds) = render_layer.image_filter_bounds.as_ref() {
+                        render_canvas.clip_rect(&filter_bounds, None, None);
+                    }
+                    paint.set_color_filter(filter.clone());
+                }
+                // the render_canvas has already the transform applied
+                let x = render_layer.bounds_with_children.x() - surface_offset.x;
+                let y = render_layer.bounds_with_children.y() - surface_offset.y;
+
+                let opacity = context_opacity * render_layer.opacity;
+
+                // Apply backdrop effects for descendant regions that need it
+                // Converts Vec<RRect> to Path for clipping with rounded rectangles
+                if let Some(backdrop_rrects) = &render_layer.backdrop_blur_region {
+                    profiling::scope!("background_blur_image_cached_descendants");
+
+                    // Group the rounded rects by effect, each group is blurred
+                    // with its own filter
+                    let mut groups: Vec<(BackdropEffect, skia_safe::PathBuilder)> = Vec::new();
+                    for (rrect, effect) in backdrop_rrects {
+                        let index = match groups.iter().position(|(e, _)| e == effect) {
+                            Some(index) => index,
+                            None => {
+                                groups.push((*effect, skia_safe::PathBuilder::new()));
+                                groups.len() - 1
+                            }
+                        };
+                        groups[index].1.add_rrect(*rrect, None, 0);
+                    }
 
-                        // Build a path from all the rounded rects
-                        let mut backdrop_builder = skia_safe::PathBuilder::new();
-                        for rrect in backdrop_rrects {
-                            backdrop_builder.add_rrect(*rrect, None, 0);
-                        }
+                    for (effect, mut backdrop_builder) in groups {
+                        let before_backdrop = render_canvas.save();
                         let backdrop_path = backdrop_builder.snapshot();
 
                         // Clip to the backdrop path (supports rounded rects)
@@ -645,9 +966,9 @@ pub fn render_node_tree(
 
                         let path_bounds = backdrop_path.bounds();
 
-                        // Use cached filter — same vibrancy as direct-rendered layers.
+                        // Use cached filter — same material as direct-rendered layers.
                         // The save_layer bounds constrains the output, no crop_rect needed.
-                        if let Some(filter) = backdrop_filter(true) {
+                        if let Some(filter) = backdrop_filter(&effect) {
                             profiling::scope!("apply backdrop descendants");
                             let mut backdrop_paint = skia_safe::Paint::default();
                             backdrop_paint.set_alpha_f(opacity);
@@ -655,30 +976,70 @@ pub fn render_node_tree(
                             save_layer_rec =
                                 save_layer_rec.bounds(&path_bounds).paint(&backdrop_paint);
                             save_layer_rec = save_layer_rec.backdrop(&filter);
-                            set_backdrop_scale(&mut save_layer_rec, BACKGROUND_BLUR_SAVE_SCALE);
+                            set_backdrop_scale(&mut save_layer_rec, effect.downsample);
                             render_canvas.save_layer(&save_layer_rec);
                         }
 
                         render_canvas.restore_to_count(before_backdrop);
                     }
+                }
 
+                if let Some(mask_ref) = mask {
+                    // composite the masked image as a group
+                    render_canvas
+                        .save_layer(&skia_safe::canvas::SaveLayerRec::default().paint(&paint));
+                    render_canvas.draw_image_with_sampling_options(
+                        &image,
+                        (x, y),
+                        skia_safe::SamplingOptions::from(skia_safe::CubicResampler::catmull_rom()),
+                        None,
+                    );
+                    draw_mask(
+                        mask_ref,
+                        scene_arena,
+                        renderables_arena,
+                        render_canvas,
+                        surface_cache,
+                    );
+                } else {
                     render_canvas.draw_image_with_sampling_options(
                         &image,
                         (x, y),
                         skia_safe::SamplingOptions::from(skia_safe::CubicResampler::catmull_rom()),
                         Some(&paint),
                     );
-
-                    render_canvas.restore_to_count(restore_point);
-                    return;
                 }
+
+                render_canvas.restore_to_count(restore_point);
+                return;
             }
         }
     }
 
     // here is when the layer is directly rendered to the screen
 
-    paint_node_tree(
+    let blend_mode = render_layer.blend_mode.to_skia();
+    let image_filter = filters_image_filter(&render_layer.filters, None);
+    if blend_mode.is_some() || image_filter.is_some() {
+        // the subtree is filtered and composited as a group onto the backdrop
+        let mut paint = skia_safe::Paint::default();
+        if let Some(blend_mode) = blend_mode {
+            paint.set_blend_mode(blend_mode);
+        }
+        paint.set_image_filter(image_filter);
+        // bounded, so that modes like SrcIn or Clear only affect the area of the subtree
+        let bounds = group_bounds_for_render_layer(render_layer);
+        let save_layer_rec = skia_safe::canvas::SaveLayerRec::default()
+            .bounds(&bounds)
+            .paint(&paint);
+        render_canvas.save_layer(&save_layer_rec);
+    }
+    if mask.is_some() {
+        // the subtree is drawn in a layer so that the mask applies to all of it
+        render_canvas.save_layer(&skia_safe::canvas::SaveLayerRec::default());
+    }
+
+    paint_node_tree_with_cache(
         node_ref,
         scene_arena,
         renderables_arena,
@@ -690,11 +1051,51 @@ pub fn render_node_tree(
         is_self_occluded,
         occluded,
         damage_region,
+        surface_cache,
     );
+    if let Some(mask_ref) = mask {
+        draw_mask(
+            mask_ref,
+            scene_arena,
+            renderables_arena,
+            render_canvas,
+            surface_cache,
+        );
+    }
 
     render_canvas.restore_to_count(restore_point);
 }
-pub(crate) const BACKGROUND_BLUR_SIGMA: f32 = 40.0;
+
+/// Draws the mask layer of a masked node over the current save layer:
+/// only the pixels where the mask is opaque are kept.
+fn draw_mask(
+    mask_ref: NodeRef,
+    scene_arena: &Arena<SceneNode>,
+    renderables_arena: &FlatStorageData<SceneNodeRenderable>,
+    render_canvas: &skia_safe::Canvas,
+    surface_cache: Option<&SurfaceCache>,
+) {
+    let mask_id: TreeStorageId = mask_ref.into();
+    let Some(mask_node) = scene_arena.get(mask_id) else {
+        return;
+    };
+    let mut paint = skia_safe::Paint::default();
+    paint.set_blend_mode(skia_safe::BlendMode::DstIn);
+    let restore_point =
+        render_canvas.save_layer(&skia_safe::canvas::SaveLayerRec::default().paint(&paint));
+    set_node_transform(mask_node.get(), render_canvas);
+    render_node_tree_with_cache(
+        mask_ref,
+        scene_arena,
+        renderables_arena,
+        render_canvas,
+        1.0,
+        None,
+        None,
+        surface_cache,
+    );
+    render_canvas.restore_to_count(restore_point);
+}
 
 // paint a single node in the provided canvas
 #[profiling::function]
@@ -747,12 +1148,12 @@ pub(crate) fn paint_node(
         profiling::scope!("background_blur");
         render_layer.clip_to_shape(canvas, skia_safe::ClipOp::Intersect, true);
 
-        if let Some(blur) = backdrop_filter(true) {
+        if let Some(blur) = backdrop_filter(&render_layer.backdrop_effect) {
             profiling::scope!("apply backdrop");
             let mut save_layer_rec = skia_safe::canvas::SaveLayerRec::default();
             save_layer_rec = save_layer_rec.bounds(&bounds_to_origin).paint(&paint);
             save_layer_rec = save_layer_rec.backdrop(&blur);
-            set_backdrop_scale(&mut save_layer_rec, BACKGROUND_BLUR_SAVE_SCALE);
+            set_backdrop_scale(&mut save_layer_rec, render_layer.backdrop_effect.downsample);
             canvas.save_layer(&save_layer_rec);
             canvas.restore_to_count(before_backdrop);
         }
@@ -827,3 +1228,61 @@ pub fn debug_node(node_id: NodeRef, arena: &Arena<SceneNode>, context_opacity: f
         render_layer.opacity,
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::skia_image::SkiaImageRenderer;
+    use crate::types::Size;
+
+    fn tile_image_id(engine: &Engine, layer: NodeRef, tile: (i32, i32)) -> u32 {
+        let (_, _, image) = engine
+            .surface_cache
+            .get(
+                SurfaceKey::tile(layer, tile),
+                (CACHE_TILE_SIZE, CACHE_TILE_SIZE),
+                None,
+            )
+            .unwrap();
+        image.unique_id()
+    }
+
+    #[test]
+    fn tiles_are_not_repainted_when_the_layer_moves() {
+        let engine = Engine::create(1000.0, 1000.0);
+        let layer = engine.new_layer();
+        engine.add_layer(&layer).unwrap();
+        layer.set_size(Size::points(5000.0, 600.0), None);
+        layer.set_background_color(Color::new_hex("#ff0000"), None);
+        layer.set_image_cached(true);
+        let child = engine.new_layer();
+        child.set_size(Size::points(100.0, 100.0), None);
+        child.set_background_color(Color::new_hex("#0000ff"), None);
+        layer.add_sublayer(&child.id).unwrap();
+        engine.update(0.01);
+
+        let renderer = SkiaImageRenderer::new(1000, 1000, "");
+        renderer.draw_scene(engine.scene(), layer.id, None);
+        let tile = tile_image_id(&engine, layer.id, (1, 0));
+
+        // the layer and its sublayer move, the content of the tiles is the same
+        layer.set_position((-100.0, 0.0), None);
+        engine.update(0.01);
+        renderer.draw_scene(engine.scene(), layer.id, None);
+        assert_eq!(tile_image_id(&engine, layer.id, (1, 0)), tile);
+
+        // a change of the content repaints the tile
+        layer.set_background_color(Color::new_hex("#00ff00"), None);
+        engine.update(0.01);
+        renderer.draw_scene(engine.scene(), layer.id, None);
+        assert_ne!(tile_image_id(&engine, layer.id, (1, 0)), tile);
+    }
+
+    #[test]
+    fn backdrop_filter_without_blur_keeps_the_color_adjustment() {
+        assert!(build_backdrop_filter(0.0, 1.0, 1.0).is_none());
+        let filter = build_backdrop_filter(0.0, 0.0, 1.2).unwrap();
+        assert!(filter.color_filter_node().is_some());
+        assert!(build_backdrop_filter(10.0, 1.0, 1.0).is_some());
+    }
+}
@@ -0,0 +1,393 @@
+//! Offscreen surfaces of the image-cached layers.
+//!
+//! Each engine owns a cache of the surfaces its image-cached layers are drawn
+//! into, bounded by a byte budget: the least recently used surfaces are evicted
+//! when the budget is exceeded. Large layers are cached in tiles, each tile
+//! is a separate entry of the cache.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use serde::Serialize;
+use skia_safe::{Image, Surface};
+
+use crate::engine::NodeRef;
+
+/// The default memory budget of the image-cache surfaces: 256 MiB
+pub const DEFAULT_SURFACE_CACHE_BUDGET: usize = 256 * 1024 * 1024;
+
+/// Statistics of an engine surface cache
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct SurfaceCacheStats {
+    /// Lookups that found a reusable surface
+    pub hits: usize,
+    /// Lookups that had to create a new surface
+    pub misses: usize,
+    /// Surfaces dropped to stay within the budget
+    pub evictions: usize,
+    /// The number of cached surfaces
+    pub surfaces: usize,
+    /// The memory used by the cached surfaces
+    pub bytes: usize,
+    /// The memory budget of the cache
+    pub budget: usize,
+}
+
+/// The column and row of a tile of a large cached layer
+pub(crate) type TileIndex = (i32, i32);
+
+/// Identifies a cached surface: the whole layer or one of its tiles
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct SurfaceKey {
+    pub(crate) node: NodeRef,
+    pub(crate) tile: Option<TileIndex>,
+}
+
+impl SurfaceKey {
+    pub(crate) fn tile(node: NodeRef, tile: TileIndex) -> Self {
+        Self {
+            node,
+            tile: Some(tile),
+        }
+    }
+}
+
+impl From<NodeRef> for SurfaceKey {
+    fn from(node: NodeRef) -> Self {
+        Self { node, tile: None }
+    }
+}
+
+impl From<&NodeRef> for SurfaceKey {
+    fn from(node: &NodeRef) -> Self {
+        (*node).into()
+    }
+}
+
+struct CachedSurface {
+    /// The frame number of the node when the surface was drawn
+    frame: usize,
+    surface: Surface,
+    image: Image,
+    bytes: usize,
+    last_used: u64,
+}
+
+// SAFETY: the surfaces are only created and drawn by the thread rendering with
+// their GPU context. The surfaces removed from the cache, also by the engine
+// updates, `Engine::purge_caches` and `Engine::set_surface_cache_budget` on
+// other threads, are not dropped there: they are queued and released by
+// `SurfaceCache::release_removed`, called when a scene is drawn. The surfaces
+// still cached are dropped with the scene, on the thread dropping the engine.
+unsafe impl Send for CachedSurface {}
+
+struct SurfaceCacheInner {
+    surfaces: HashMap<SurfaceKey, CachedSurface>,
+    /// The surfaces removed from the cache, waiting to be released
+    removed: Vec<CachedSurface>,
+    /// Incremented on every access, orders the surfaces by last use
+    clock: u64,
+    stats: SurfaceCacheStats,
+}
+
+/// The offscreen surfaces of the image-cached layers of a scene, see
+/// [`Scene::surface_cache`](crate::engine::scene::Scene::surface_cache)
+pub struct SurfaceCache {
+    inner: Mutex<SurfaceCacheInner>,
+}
+
+impl SurfaceCache {
+    pub(crate) fn new(budget: usize) -> Self {
+        Self {
+            inner: Mutex::new(SurfaceCacheInner {
+                surfaces: HashMap::new(),
+                removed: Vec::new(),
+                clock: 0,
+                stats: SurfaceCacheStats {
+                    budget,
+                    ..Default::default()
+                },
+            }),
+        }
+    }
+
+    /// The cached surface of the node or tile, if it was created with the given
+    /// context (None for raster surfaces) and is at least `min_size` large.
+    /// Unusable surfaces are dropped.
+    pub(crate) fn get(
+        &self,
+        key: impl Into<SurfaceKey>,
+        min_size: (i32, i32),
+        context_id: Option<skia_safe::gpu::DirectContextId>,
+    ) -> Option<(usize, Surface, Image)> {
+        let key = key.into();
+        let mut inner = self.inner.lock().unwrap();
+        inner.clock += 1;
+        let clock = inner.clock;
+        let reusable = inner.surfaces.get_mut(&key).and_then(|cached| {
+            let same_context =
+                cached.surface.direct_context().map(|context| context.id()) == context_id;
+            let large_enough =
+                cached.surface.width() >= min_size.0 && cached.surface.height() >= min_size.1;
+            if same_context && large_enough {
+                cached.last_used = clock;
+                Some((cached.frame, cached.surface.clone(), cached.image.clone()))
+            } else {
+                None
+            }
+        });
+        match reusable {
+            Some(cached) => {
+                inner.stats.hits += 1;
+                Some(cached)
+            }
+            None => {
+                inner.stats.misses += 1;
+                inner.remove(&key);
+                None
+            }
+        }
+    }
+
+    /// Stores the surface of the node or tile drawn at `frame`, evicting the
+    /// least recently used surfaces if the budget is exceeded.
+    pub(crate) fn insert(
+        &self,
+        key: impl Into<SurfaceKey>,
+        frame: usize,
+        surface: Surface,
+        image: Image,
+    ) {
+        let key = key.into();
+        let mut inner = self.inner.lock().unwrap();
+        inner.clock += 1;
+        let bytes = surface.width().max(0) as usize * surface.height().max(0) as usize * 4;
+        let cached = CachedSurface {
+            frame,
+            surface,
+            image,
+            bytes,
+            last_used: inner.clock,
+        };
+        if let Some(previous) = inner.surfaces.insert(key, cached) {
+            inner.stats.bytes -= previous.bytes;
+            inner.removed.push(previous);
+        }
+        inner.stats.bytes += bytes;
+        inner.evict_to_budget(Some(key));
+    }
+
+    /// Marks a cached tile as up to date at `frame`, when it was not affected
+    /// by the changes of the node
+    pub(crate) fn set_frame(&self, key: impl Into<SurfaceKey>, frame: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(cached) = inner.surfaces.get_mut(&key.into()) {
+            cached.frame = frame;
+        }
+    }
+
+    /// Drops the surface and the tiles of the node
+    pub(crate) fn remove(&self, node_ref: &NodeRef) {
+        let mut inner = self.inner.lock().unwrap();
+        let keys: Vec<_> = inner
+            .surfaces
+            .keys()
+            .filter(|key| key.node == *node_ref)
+            .copied()
+            .collect();
+        for key in keys {
+            inner.remove(&key);
+        }
+    }
+
+    /// Drops all the surfaces
+    pub(crate) fn purge(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        let surfaces: Vec<_> = inner.surfaces.drain().map(|(_, cached)| cached).collect();
+        inner.removed.extend(surfaces);
+        inner.stats.bytes = 0;
+    }
+
+    /// Releases the surfaces removed from the cache. Called by the thread
+    /// rendering with their GPU context, `draw_scene` calls it before drawing.
+    pub fn release_removed(&self) {
+        let removed = std::mem::take(&mut self.inner.lock().unwrap().removed);
+        drop(removed);
+    }
+
+    pub(crate) fn set_budget(&self, budget: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.stats.budget = budget;
+        inner.evict_to_budget(None);
+    }
+
+    pub(crate) fn stats(&self) -> SurfaceCacheStats {
+        let inner = self.inner.lock().unwrap();
+        SurfaceCacheStats {
+            surfaces: inner.surfaces.len(),
+            ..inner.stats
+        }
+    }
+}
+
+impl SurfaceCacheInner {
+    fn remove(&mut self, key: &SurfaceKey) {
+        if let Some(cached) = self.surfaces.remove(key) {
+            self.stats.bytes -= cached.bytes;
+            self.removed.push(cached);
+        }
+    }
+
+    // Evicts the least recently used surfaces, except `keep`, until the cache
+    // fits the budget
+    fn evict_to_budget(&mut self, keep: Option<SurfaceKey>) {
+        while self.stats.bytes > self.stats.budget {
+            let lru = self
+                .surfaces
+                .iter()
+                .filter(|(key, _)| Some(**key) != keep)
+                .min_by_key(|(_, cached)| cached.last_used)
+                .map(|(key, _)| *key);
+            let Some(lru) = lru else {
+                break;
+            };
+            self.remove(&lru);
+            self.stats.evictions += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node_refs(count: usize) -> Vec<NodeRef> {
+        let mut arena = indextree::Arena::new();
+        (0..count).map(|_| NodeRef(arena.new_node(()))).collect()
+    }
+
+    fn surface(width: i32, height: i32) -> (Surface, Image) {
+        let mut surface = skia_safe::surfaces::raster_n32_premul((width, height)).unwrap();
+        let image = surface.image_snapshot();
+        (surface, image)
+    }
+
+    #[test]
+    fn insert_accounts_the_surface_bytes() {
+        let cache = SurfaceCache::new(DEFAULT_SURFACE_CACHE_BUDGET);
+        let nodes = node_refs(2);
+        let (s, i) = surface(10, 10);
+        cache.insert(nodes[0], 1, s, i);
+        let (s, i) = surface(20, 10);
+        cache.insert(nodes[1], 1, s, i);
+
+        let stats = cache.stats();
+        assert_eq!(stats.surfaces, 2);
+        assert_eq!(stats.bytes, (100 + 200) * 4);
+
+        // replacing a surface releases the previous one
+        let (s, i) = surface(5, 5);
+        cache.insert(nodes[1], 2, s, i);
+        assert_eq!(cache.stats().bytes, (100 + 25) * 4);
+
+        cache.remove(&nodes[0]);
+        let stats = cache.stats();
+        assert_eq!(stats.surfaces, 1);
+        assert_eq!(stats.bytes, 25 * 4);
+
+        cache.purge();
+        let stats = cache.stats();
+        assert_eq!(stats.surfaces, 0);
+        assert_eq!(stats.bytes, 0);
+    }
+
+    #[test]
+    fn raster_surfaces_are_reused_when_large_enough() {
+        let cache = SurfaceCache::new(DEFAULT_SURFACE_CACHE_BUDGET);
+        let nodes = node_refs(1);
+        let (s, i) = surface(10, 10);
+        cache.insert(nodes[0], 3, s, i);
+
+        let (frame, surface, _) = cache.get(&nodes[0], (10, 8), None).unwrap();
+        assert_eq!(frame, 3);
+        assert_eq!(surface.width(), 10);
+
+        // a larger node needs a new surface, the old one is dropped
+        assert!(cache.get(&nodes[0], (20, 10), None).is_none());
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.surfaces, 0);
+    }
+
+    #[test]
+    fn least_recently_used_surfaces_are_evicted() {
+        // room for two 10x10 surfaces
+        let cache = SurfaceCache::new(800);
+        let nodes = node_refs(3);
+        for node in &nodes {
+            let (s, i) = surface(10, 10);
+            cache.insert(*node, 0, s, i);
+        }
+        let stats = cache.stats();
+        assert_eq!(stats.surfaces, 2);
+        assert_eq!(stats.evictions, 1);
+        assert_eq!(stats.bytes, 800);
+
+        // the inserted surface is kept even if larger than the budget
+        let (s, i) = surface(20, 20);
+        cache.insert(nodes[0], 0, s, i);
+        let stats = cache.stats();
+        assert_eq!(stats.surfaces, 1);
+        assert_eq!(stats.bytes, 1600);
+
+        cache.set_budget(400);
+        let stats = cache.stats();
+        assert_eq!(stats.surfaces, 0);
+        assert_eq!(stats.budget, 400);
+    }
+
+    #[test]
+    fn tiles_are_removed_with_their_node() {
+        let cache = SurfaceCache::new(DEFAULT_SURFACE_CACHE_BUDGET);
+        let nodes = node_refs(2);
+        for tile in [(0, 0), (1, 0), (0, 1)] {
+            let (s, i) = surface(10, 10);
+            cache.insert(SurfaceKey::tile(nodes[0], tile), 1, s, i);
+        }
+        let (s, i) = surface(10, 10);
+        cache.insert(nodes[1], 1, s, i);
+
+        cache.set_frame(SurfaceKey::tile(nodes[0], (1, 0)), 2);
+        let (frame, _, _) = cache
+            .get(SurfaceKey::tile(nodes[0], (1, 0)), (10, 10), None)
+            .unwrap();
+        assert_eq!(frame, 2);
+        assert_eq!(cache.stats().surfaces, 4);
+
+        cache.remove(&nodes[0]);
+        let stats = cache.stats();
+        assert_eq!(stats.surfaces, 1);
+        assert_eq!(stats.bytes, 100 * 4);
+    }
+
+    #[test]
+    fn removed_surfaces_are_released_when_drawing() {
+        let cache = SurfaceCache::new(DEFAULT_SURFACE_CACHE_BUDGET);
+        let nodes = node_refs(2);
+        for node in &nodes {
+            let (s, i) = surface(10, 10);
+            cache.insert(*node, 0, s, i);
+        }
+        let removed = |cache: &SurfaceCache| cache.inner.lock().unwrap().removed.len();
+
+        // the removed surfaces are kept until the next draw
+        cache.remove(&nodes[0]);
+        assert_eq!(removed(&cache), 1);
+        cache.purge();
+        assert_eq!(removed(&cache), 2);
+        assert_eq!(cache.stats().bytes, 0);
+
+        cache.release_removed();
+        assert_eq!(removed(&cache), 0);
+    }
+}
@@ -1,9 +1,11 @@
 use std::fmt::Debug;
 
 use crate::types::{
-    BorderRadius, Color, GradientLinear, GradientRadial, PaintColor, Point, Point3d, Size,
+    BackdropEffect, BackgroundImage, BlendMode, BorderRadius, BorderStyle, Color, Filter,
+    GradientConic, GradientDirection, GradientLinear, GradientRadial, GradientTwoPointConical,
+    PaintColor, Point, Point3d, Shadow, Size, TileMode, Transform, TransformOperation,
 };
-use skia_safe::Picture;
+use skia_safe::{Picture, M44};
 use taffy::style::Dimension;
 
 #[allow(dead_code)]
@@ -157,7 +159,7 @@ impl std::ops::Add for Color {
     }
 }
 // implementation of Mul<f32> trait for PaintColor
-// TODO incomplete for GradientLinear and GradientRadial
+// only solid colors are scaled, gradients are interpolated through Interpolate
 impl std::ops::Mul<f32> for PaintColor {
     type Output = PaintColor;
 
@@ -166,41 +168,24 @@ impl std::ops::Mul<f32> for PaintColor {
             PaintColor::Solid { color } => PaintColor::Solid {
                 color: color * other,
             },
-            PaintColor::GradientLinear(gradient) => PaintColor::GradientLinear(gradient),
-            PaintColor::GradientRadial(gradient) => PaintColor::GradientRadial(gradient),
+            gradient => gradient,
         }
     }
 }
 
 // implementation of Add trait for PaintColor
-// TODO incomplete for GradientLinear and GradientRadial
-
+// only solid colors are added, gradients are interpolated through Interpolate
 impl std::ops::Add for PaintColor {
     type Output = PaintColor;
 
     fn add(self, other: PaintColor) -> PaintColor {
-        match self {
-            PaintColor::Solid { color } => match other {
-                PaintColor::Solid { color: other_color } => PaintColor::Solid {
+        match (self, other) {
+            (PaintColor::Solid { color }, PaintColor::Solid { color: other_color }) => {
+                PaintColor::Solid {
                     color: color + other_color,
-                },
-                PaintColor::GradientLinear { .. } => other,
-                PaintColor::GradientRadial { .. } => other,
-            },
-            PaintColor::GradientLinear { .. } => match other {
-                PaintColor::Solid { color: other_color } => {
-                    PaintColor::Solid { color: other_color }
                 }
-                PaintColor::GradientLinear { .. } => other,
-                PaintColor::GradientRadial { .. } => other,
-            },
-            PaintColor::GradientRadial { .. } => match other {
-                PaintColor::Solid { color: other_color } => {
-                    PaintColor::Solid { color: other_color }
-                }
-                PaintColor::GradientLinear { .. } => other,
-                PaintColor::GradientRadial { .. } => other,
-            },
+            }
+            (_, other) => other,
         }
     }
 }
@@ -211,14 +196,29 @@ impl PartialEq for Color {
     }
 }
 // implementation of PartiallyEq trait for PaintColor
-// TODO incomplete for GradientLinear and GradientRadial
-
 impl std::cmp::PartialEq for PaintColor {
     fn eq(&self, other: &PaintColor) -> bool {
         match (self, other) {
             (PaintColor::Solid { color: c1 }, PaintColor::Solid { color: c2 }) => c1 == c2,
             (PaintColor::GradientLinear(g1), PaintColor::GradientLinear(g2)) => **g1 == **g2,
             (PaintColor::GradientRadial(g1), PaintColor::GradientRadial(g2)) => **g1 == **g2,
+            (PaintColor::GradientConic(g1), PaintColor::GradientConic(g2)) => **g1 == **g2,
+            (PaintColor::GradientTwoPointConical(g1), PaintColor::GradientTwoPointConical(g2)) => {
+                **g1 == **g2
+            }
+            _ => false,
+        }
+    }
+}
+// implementation of PartiallyEq trait for GradientDirection
+impl std::cmp::PartialEq for GradientDirection {
+    fn eq(&self, other: &GradientDirection) -> bool {
+        match (self, other) {
+            (
+                GradientDirection::Points { start: s1, end: e1 },
+                GradientDirection::Points { start: s2, end: e2 },
+            ) => s1 == s2 && e1 == e2,
+            (GradientDirection::Angle(a1), GradientDirection::Angle(a2)) => a1 == a2,
             _ => false,
         }
     }
@@ -226,14 +226,68 @@ impl std::cmp::PartialEq for PaintColor {
 // implementation of PartiallyEq trait for GradientLinear
 impl std::cmp::PartialEq for GradientLinear {
     fn eq(&self, other: &GradientLinear) -> bool {
-        self.colors == other.colors && self.points == other.points
+        self.colors == other.colors
+            && self.points == other.points
+            && self.direction == other.direction
+            && self.tile_mode == other.tile_mode
     }
 }
 
 // implementation of PartiallyEq trait for GradientRadial
 impl std::cmp::PartialEq for GradientRadial {
     fn eq(&self, other: &GradientRadial) -> bool {
-        self.center == other.center && self.radius == other.radius && self.points == other.points
+        self.center == other.center
+            && self.radius == other.radius
+            && self.colors == other.colors
+            && self.points == other.points
+            && self.tile_mode == other.tile_mode
+    }
+}
+
+// implementation of PartiallyEq trait for GradientConic
+impl std::cmp::PartialEq for GradientConic {
+    fn eq(&self, other: &GradientConic) -> bool {
+        self.center == other.center
+            && self.start_angle == other.start_angle
+            && self.colors == other.colors
+            && self.points == other.points
+            && self.tile_mode == other.tile_mode
+    }
+}
+
+// implementation of PartiallyEq trait for GradientTwoPointConical
+impl std::cmp::PartialEq for GradientTwoPointConical {
+    fn eq(&self, other: &GradientTwoPointConical) -> bool {
+        self.start == other.start
+            && self.start_radius == other.start_radius
+            && self.end == other.end
+            && self.end_radius == other.end_radius
+            && self.colors == other.colors
+            && self.points == other.points
+            && self.tile_mode == other.tile_mode
+    }
+}
+
+// implementation of PartialEq trait for Shadow
+impl std::cmp::PartialEq for Shadow {
+    fn eq(&self, other: &Shadow) -> bool {
+        self.offset == other.offset
+            && self.radius == other.radius
+            && self.spread == other.spread
+            && self.color == other.color
+            && self.inset == other.inset
+    }
+}
+
+// implementation of PartialEq trait for BackdropEffect
+impl std::cmp::PartialEq for BackdropEffect {
+    fn eq(&self, other: &BackdropEffect) -> bool {
+        self.blur_sigma == other.blur_sigma
+            && self.saturation == other.saturation
+            && self.brightness == other.brightness
+            && self.tint == other.tint
+            && self.noise == other.noise
+            && self.downsample == other.downsample
     }
 }
 
@@ -251,17 +305,243 @@ impl<V: Interpolable> Interpolate for V {
     }
 }
 
+/// The stop positions of a gradient, evenly spaced when `points`
+/// doesn't match the number of colors
+pub(crate) fn gradient_stop_positions(colors: &[Color], points: &[f32]) -> Vec<f32> {
+    if points.len() == colors.len() {
+        return points.to_vec();
+    }
+    let steps = colors.len();
+    (0..steps)
+        .map(|step| {
+            if steps > 1 {
+                step as f32 / (steps - 1) as f32
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+// the color of a gradient at `position`, interpolated in OKLab between the stops
+fn gradient_color_at(colors: &[Color], positions: &[f32], position: f32) -> Color {
+    let (Some(first), Some(last)) = (colors.first(), colors.last()) else {
+        return Color::default();
+    };
+    match positions.iter().position(|p| *p >= position) {
+        None => *last,
+        Some(0) => *first,
+        Some(i) => {
+            let (p0, p1) = (positions[i - 1], positions[i]);
+            let t = if p1 > p0 {
+                (position - p0) / (p1 - p0)
+            } else {
+                1.0
+            };
+            colors[i - 1].interpolate(&colors[i], t)
+        }
+    }
+}
+
+// Interpolates the stops of two gradients. Lists of the same size are interpolated
+// stop by stop; otherwise both gradients are sampled on the union of their stop
+// positions, so that no stop appears or disappears abruptly.
+fn interpolate_gradient_stops(
+    (from_colors, from_points): (&[Color], &[f32]),
+    (to_colors, to_points): (&[Color], &[f32]),
+    f: f32,
+) -> (Vec<Color>, Vec<f32>) {
+    let from_positions = gradient_stop_positions(from_colors, from_points);
+    let to_positions = gradient_stop_positions(to_colors, to_points);
+
+    if from_colors.len() == to_colors.len() {
+        let colors = from_colors
+            .iter()
+            .zip(to_colors)
+            .map(|(c1, c2)| c1.interpolate(c2, f))
+            .collect();
+        let points = from_positions
+            .iter()
+            .zip(to_positions.iter())
+            .map(|(p1, p2)| p1.interpolate(p2, f))
+            .collect();
+        return (colors, points);
+    }
+
+    let mut points: Vec<f32> = from_positions
+        .iter()
+        .chain(to_positions.iter())
+        .copied()
+        .collect();
+    points.sort_by(|a, b| a.total_cmp(b));
+    points.dedup_by(|a, b| (*a - *b).abs() < f32::EPSILON);
+    let colors = points
+        .iter()
+        .map(|p| {
+            let c1 = gradient_color_at(from_colors, &from_positions, *p);
+            let c2 = gradient_color_at(to_colors, &to_positions, *p);
+            c1.interpolate(&c2, f)
+        })
+        .collect();
+    (colors, points)
+}
+
+impl Interpolate for TileMode {
+    fn interpolate(&self, other: &TileMode, f: f32) -> TileMode {
+        if f < 0.5 {
+            *self
+        } else {
+            *other
+        }
+    }
+}
+
+impl Interpolate for GradientDirection {
+    fn interpolate(&self, other: &GradientDirection, f: f32) -> GradientDirection {
+        match (self, other) {
+            (
+                GradientDirection::Points { start: s1, end: e1 },
+                GradientDirection::Points { start: s2, end: e2 },
+            ) => GradientDirection::Points {
+                start: s1.interpolate(s2, f),
+                end: e1.interpolate(e2, f),
+            },
+            (GradientDirection::Angle(a1), GradientDirection::Angle(a2)) => {
+                GradientDirection::Angle(a1.interpolate(a2, f))
+            }
+            _ => {
+                if f < 0.5 {
+                    *self
+                } else {
+                    *other
+                }
+            }
+        }
+    }
+}
+
+impl PaintColor {
+    // the same paint with the stops replaced, a solid color stays solid
+    fn with_stops(&self, colors: Vec<Color>, points: Vec<f32>) -> PaintColor {
+        match self {
+            PaintColor::Solid { .. } => PaintColor::Solid {
+                color: colors.first().copied().unwrap_or_default(),
+            },
+            PaintColor::GradientLinear(g) => PaintColor::GradientLinear(Box::new(GradientLinear {
+                colors,
+                points,
+                ..(**g).clone()
+            })),
+            PaintColor::GradientRadial(g) => PaintColor::GradientRadial(Box::new(GradientRadial {
+                colors,
+                points,
+                ..(**g).clone()
+            })),
+            PaintColor::GradientConic(g) => PaintColor::GradientConic(Box::new(GradientConic {
+                colors,
+                points,
+                ..(**g).clone()
+            })),
+            PaintColor::GradientTwoPointConical(g) => {
+                PaintColor::GradientTwoPointConical(Box::new(GradientTwoPointConical {
+                    colors,
+                    points,
+                    ..(**g).clone()
+                }))
+            }
+        }
+    }
+    fn points(&self) -> &[f32] {
+        match self {
+            PaintColor::Solid { .. } => &[],
+            PaintColor::GradientLinear(g) => &g.points,
+            PaintColor::GradientRadial(g) => &g.points,
+            PaintColor::GradientConic(g) => &g.points,
+            PaintColor::GradientTwoPointConical(g) => &g.points,
+        }
+    }
+    // a gradient with the same geometry and stops as `gradient`, painted with a single color
+    fn as_gradient_like(&self, gradient: &PaintColor) -> PaintColor {
+        let color = self.colors().first().copied().unwrap_or_default();
+        gradient.with_stops(
+            vec![color; gradient.colors().len()],
+            gradient.points().to_vec(),
+        )
+    }
+}
+
 impl Interpolate for PaintColor {
     fn interpolate(&self, other: &PaintColor, f: f32) -> PaintColor {
+        if f <= 0.0 {
+            return self.clone();
+        }
+        if f >= 1.0 {
+            return other.clone();
+        }
+        let stops = |from: &PaintColor, to: &PaintColor| {
+            interpolate_gradient_stops(
+                (from.colors(), from.points()),
+                (to.colors(), to.points()),
+                f,
+            )
+        };
         match (self, other) {
             (PaintColor::Solid { color: c1 }, PaintColor::Solid { color: c2 }) => {
                 PaintColor::Solid {
                     color: c1.interpolate(c2, f),
                 }
             }
+            // a solid color is interpolated as a gradient of a single color
+            (PaintColor::Solid { .. }, gradient) => {
+                self.as_gradient_like(gradient).interpolate(other, f)
+            }
+            (gradient, PaintColor::Solid { .. }) => {
+                self.interpolate(&other.as_gradient_like(gradient), f)
+            }
+            (PaintColor::GradientLinear(g1), PaintColor::GradientLinear(g2)) => {
+                let (colors, points) = stops(self, other);
+                PaintColor::GradientLinear(Box::new(GradientLinear {
+                    colors,
+                    points,
+                    direction: g1.direction.interpolate(&g2.direction, f),
+                    tile_mode: g1.tile_mode.interpolate(&g2.tile_mode, f),
+                }))
+            }
+            (PaintColor::GradientRadial(g1), PaintColor::GradientRadial(g2)) => {
+                let (colors, points) = stops(self, other);
+                PaintColor::GradientRadial(Box::new(GradientRadial {
+                    center: g1.center.interpolate(&g2.center, f),
+                    radius: g1.radius.interpolate(&g2.radius, f),
+                    colors,
+                    points,
+                    tile_mode: g1.tile_mode.interpolate(&g2.tile_mode, f),
+                }))
+            }
+            (PaintColor::GradientConic(g1), PaintColor::GradientConic(g2)) => {
+                let (colors, points) = stops(self, other);
+                PaintColor::GradientConic(Box::new(GradientConic {
+                    center: g1.center.interpolate(&g2.center, f),
+                    start_angle: g1.start_angle.interpolate(&g2.start_angle, f),
+                    colors,
+                    points,
+                    tile_mode: g1.tile_mode.interpolate(&g2.tile_mode, f),
+                }))
+            }
+            (PaintColor::GradientTwoPointConical(g1), PaintColor::GradientTwoPointConical(g2)) => {
+                let (colors, points) = stops(self, other);
+                PaintColor::GradientTwoPointConical(Box::new(GradientTwoPointConical {
+                    start: g1.start.interpolate(&g2.start, f),
+                    start_radius: g1.start_radius.interpolate(&g2.start_radius, f),
+                    end: g1.end.interpolate(&g2.end, f),
+                    end_radius: g1.end_radius.interpolate(&g2.end_radius, f),
+                    colors,
+                    points,
+                    tile_mode: g1.tile_mode.interpolate(&g2.tile_mode, f),
+                }))
+            }
             _ => {
-                // if we are not interpolating between two solid colors, we just return the first
-                // or the second based on the value of f
+                // different kinds of gradients can't be interpolated, we just return
+                // the first or the second based on the value of f
                 if f < 0.5 {
                     self.to_owned()
                 } else {
@@ -272,6 +552,374 @@ impl Interpolate for PaintColor {
     }
 }
 
+impl Interpolate for Shadow {
+    fn interpolate(&self, other: &Shadow, f: f32) -> Shadow {
+        Shadow {
+            offset: self.offset.interpolate(&other.offset, f),
+            radius: self.radius.interpolate(&other.radius, f),
+            spread: self.spread.interpolate(&other.spread, f),
+            color: self.color.interpolate(&other.color, f),
+            inset: self.inset.interpolate(&other.inset, f),
+        }
+    }
+}
+
+impl Interpolate for BackdropEffect {
+    fn interpolate(&self, other: &BackdropEffect, f: f32) -> BackdropEffect {
+        BackdropEffect {
+            blur_sigma: self.blur_sigma.interpolate(&other.blur_sigma, f),
+            saturation: self.saturation.interpolate(&other.saturation, f),
+            brightness: self.brightness.interpolate(&other.brightness, f),
+            tint: self.tint.interpolate(&other.tint, f),
+            noise: self.noise.interpolate(&other.noise, f),
+            downsample: self.downsample.interpolate(&other.downsample, f),
+        }
+    }
+}
+
+impl Interpolate for Filter {
+    fn interpolate(&self, other: &Filter, f: f32) -> Filter {
+        match (self, other) {
+            (Filter::Blur(a), Filter::Blur(b)) => Filter::Blur(a.interpolate(b, f)),
+            (
+                Filter::DropShadow {
+                    offset,
+                    radius,
+                    color,
+                },
+                Filter::DropShadow {
+                    offset: other_offset,
+                    radius: other_radius,
+                    color: other_color,
+                },
+            ) => Filter::DropShadow {
+                offset: offset.interpolate(other_offset, f),
+                radius: radius.interpolate(other_radius, f),
+                color: color.interpolate(other_color, f),
+            },
+            (Filter::ColorMatrix(a), Filter::ColorMatrix(b)) => {
+                let mut matrix = *a;
+                for (value, other) in matrix.iter_mut().zip(b.iter()) {
+                    *value = value.interpolate(other, f);
+                }
+                Filter::ColorMatrix(matrix)
+            }
+            (Filter::Brightness(a), Filter::Brightness(b)) => {
+                Filter::Brightness(a.interpolate(b, f))
+            }
+            (Filter::Contrast(a), Filter::Contrast(b)) => Filter::Contrast(a.interpolate(b, f)),
+            (Filter::Saturate(a), Filter::Saturate(b)) => Filter::Saturate(a.interpolate(b, f)),
+            (Filter::HueRotate(a), Filter::HueRotate(b)) => Filter::HueRotate(a.interpolate(b, f)),
+            (Filter::Grayscale(a), Filter::Grayscale(b)) => Filter::Grayscale(a.interpolate(b, f)),
+            // different kinds of filters can't be interpolated
+            _ => {
+                if f < 0.5 {
+                    *self
+                } else {
+                    *other
+                }
+            }
+        }
+    }
+}
+
+// Filter lists interpolate item by item, like CSS filters: when a list is
+// shorter, the missing filters animate from (or to) their identity.
+impl Interpolate for Vec<Filter> {
+    fn interpolate(&self, other: &Vec<Filter>, f: f32) -> Vec<Filter> {
+        if f <= 0.0 {
+            return self.clone();
+        }
+        if f >= 1.0 {
+            return other.clone();
+        }
+        (0..self.len().max(other.len()))
+            .filter_map(|i| match (self.get(i), other.get(i)) {
+                (Some(a), Some(b)) => Some(a.interpolate(b, f)),
+                (Some(a), None) => Some(a.interpolate(&a.identity(), f)),
+                (None, Some(b)) => Some(b.identity().interpolate(b, f)),
+                (None, None) => None,
+            })
+            .collect()
+    }
+}
+
+// Operations of the same kind interpolate their parameters, None when the pair
+// can only be animated through the matrices.
+fn interpolate_operation(
+    from: &TransformOperation,
+    to: &TransformOperation,
+    f: f32,
+) -> Option<TransformOperation> {
+    use TransformOperation::*;
+    match (from, to) {
+        (Translate(a), Translate(b)) => Some(Translate(a.interpolate(b, f))),
+        (Scale(a), Scale(b)) => Some(Scale(a.interpolate(b, f))),
+        (
+            Rotate {
+                axis: axis_a,
+                angle: a,
+            },
+            Rotate {
+                axis: axis_b,
+                angle: b,
+            },
+        ) if axis_a == axis_b => Some(Rotate {
+            axis: *axis_a,
+            angle: a.interpolate(b, f),
+        }),
+        (Skew(a), Skew(b)) => Some(Skew(a.interpolate(b, f))),
+        // the perspective animates its inverse, 0.0 being no perspective
+        (Perspective(a), Perspective(b)) => {
+            let inverse = |d: f32| if d > 0.0 { 1.0 / d } else { 0.0 };
+            let p = inverse(*a).interpolate(&inverse(*b), f);
+            Some(Perspective(inverse(p)))
+        }
+        _ => None,
+    }
+}
+
+// Decomposition of a 4x4 matrix as in the CSS transforms specification:
+// perspective * translate * rotate * skew * scale
+#[derive(Clone, Copy, Debug)]
+struct DecomposedTransform {
+    perspective: [f32; 4],
+    translate: [f32; 3],
+    quaternion: [f32; 4],
+    skew: [f32; 3],
+    scale: [f32; 3],
+}
+
+fn dot3(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn combine3(a: &[f32; 3], b: &[f32; 3], scale_b: f32) -> [f32; 3] {
+    [
+        a[0] + b[0] * scale_b,
+        a[1] + b[1] * scale_b,
+        a[2] + b[2] * scale_b,
+    ]
+}
+
+impl DecomposedTransform {
+    fn decompose(matrix: &M44) -> Option<Self> {
+        let w = matrix.rc(3, 3);
+        if w == 0.0 {
+            return None;
+        }
+        let m = |r: usize, c: usize| matrix.rc(r, c) / w;
+
+        // the perspective is the bottom row, once the upper part is factored out
+        let mut perspective = [0.0, 0.0, 0.0, 1.0];
+        if m(3, 0) != 0.0 || m(3, 1) != 0.0 || m(3, 2) != 0.0 {
+            let mut perspective_matrix = M44::new_identity();
+            for r in 0..3 {
+                for c in 0..4 {
+                    perspective_matrix.set_rc(r, c, m(r, c));
+                }
+            }
+            let inverse = perspective_matrix.invert()?;
+            for (c, value) in perspective.iter_mut().enumerate() {
+                *value = (0..4).map(|r| m(3, r) * inverse.rc(r, c)).sum();
+            }
+        }
+
+        let translate = [m(0, 3), m(1, 3), m(2, 3)];
+
+        // Gram-Schmidt on the columns of the upper 3x3 matrix
+        let mut columns = [
+            [m(0, 0), m(1, 0), m(2, 0)],
+            [m(0, 1), m(1, 1), m(2, 1)],
+            [m(0, 2), m(1, 2), m(2, 2)],
+        ];
+        let length = |v: &[f32; 3]| dot3(v, v).sqrt();
+        let normalize = |v: &[f32; 3], l: f32| {
+            if l == 0.0 {
+                *v
+            } else {
+                [v[0] / l, v[1] / l, v[2] / l]
+            }
+        };
+        let mut scale = [0.0; 3];
+        let mut skew = [0.0; 3];
+
+        scale[0] = length(&columns[0]);
+        columns[0] = normalize(&columns[0], scale[0]);
+
+        skew[0] = dot3(&columns[0], &columns[1]);
+        columns[1] = combine3(&columns[1], &columns[0], -skew[0]);
+        scale[1] = length(&columns[1]);
+        columns[1] = normalize(&columns[1], scale[1]);
+        if scale[1] != 0.0 {
+            skew[0] /= scale[1];
+        }
+
+        skew[1] = dot3(&columns[0], &columns[2]);
+        columns[2] = combine3(&columns[2], &columns[0], -skew[1]);
+        skew[2] = dot3(&columns[1], &columns[2]);
+        columns[2] = combine3(&columns[2], &columns[1], -skew[2]);
+        scale[2] = length(&columns[2]);
+        columns[2] = normalize(&columns[2], scale[2]);
+        if scale[2] != 0.0 {
+            skew[1] /= scale[2];
+            skew[2] /= scale[2];
+        }
+
+        // a negative determinant is a flip, kept in the scale
+        let cross = [
+            columns[1][1] * columns[2][2] - columns[1][2] * columns[2][1],
+            columns[1][2] * columns[2][0] - columns[1][0] * columns[2][2],
+            columns[1][0] * columns[2][1] - columns[1][1] * columns[2][0],
+        ];
+        if dot3(&columns[0], &cross) < 0.0 {
+            for i in 0..3 {
+                scale[i] = -scale[i];
+                columns[i] = [-columns[i][0], -columns[i][1], -columns[i][2]];
+            }
+        }
+
+        // rotation matrix entry at row r, column c
+        let r = |r: usize, c: usize| columns[c][r];
+        let mut quaternion = [
+            0.5 * (1.0 + r(0, 0) - r(1, 1) - r(2, 2)).max(0.0).sqrt(),
+            0.5 * (1.0 - r(0, 0) + r(1, 1) - r(2, 2)).max(0.0).sqrt(),
+            0.5 * (1.0 - r(0, 0) - r(1, 1) + r(2, 2)).max(0.0).sqrt(),
+            0.5 * (1.0 + r(0, 0) + r(1, 1) + r(2, 2)).max(0.0).sqrt(),
+        ];
+        if r(2, 1) < r(1, 2) {
+            quaternion[0] = -quaternion[0];
+        }
+        if r(0, 2) < r(2, 0) {
+            quaternion[1] = -quaternion[1];
+        }
+        if r(1, 0) < r(0, 1) {
+            quaternion[2] = -quaternion[2];
+        }
+
+        Some(DecomposedTransform {
+            perspective,
+            translate,
+            quaternion,
+            skew,
+            scale,
+        })
+    }
+
+    fn recompose(&self) -> M44 {
+        let mut perspective = M44::new_identity();
+        for (c, value) in self.perspective.iter().enumerate() {
+            perspective.set_rc(3, c, *value);
+        }
+        let [tx, ty, tz] = self.translate;
+        let [x, y, z, w] = self.quaternion;
+        let rotation = M44::row_major(&[
+            1.0 - 2.0 * (y * y + z * z),
+            2.0 * (x * y - z * w),
+            2.0 * (x * z + y * w),
+            0.0,
+            2.0 * (x * y + z * w),
+            1.0 - 2.0 * (x * x + z * z),
+            2.0 * (y * z - x * w),
+            0.0,
+            2.0 * (x * z - y * w),
+            2.0 * (y * z + x * w),
+            1.0 - 2.0 * (x * x + y * y),
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            1.0,
+        ]);
+        let mut skew = M44::new_identity();
+        skew.set_rc(0, 1, self.skew[0]);
+        skew.set_rc(0, 2, self.skew[1]);
+        skew.set_rc(1, 2, self.skew[2]);
+        let [sx, sy, sz] = self.scale;
+
+        let matrix = M44::concat(&perspective, &M44::translate(tx, ty, tz));
+        let matrix = M44::concat(&matrix, &rotation);
+        let matrix = M44::concat(&matrix, &skew);
+        M44::concat(&matrix, &M44::scale(sx, sy, sz))
+    }
+
+    fn interpolate(&self, other: &Self, f: f32) -> Self {
+        let lerp = |a: f32, b: f32| a + (b - a) * f;
+        let lerp3 =
+            |a: &[f32; 3], b: &[f32; 3]| [lerp(a[0], b[0]), lerp(a[1], b[1]), lerp(a[2], b[2])];
+
+        // spherical interpolation of the rotation, along the shortest path
+        let from = self.quaternion;
+        let mut to = other.quaternion;
+        let mut product: f32 = (0..4).map(|i| from[i] * to[i]).sum();
+        if product < 0.0 {
+            to = to.map(|v| -v);
+            product = -product;
+        }
+        let product = product.min(1.0);
+        let quaternion = if product >= 1.0 - f32::EPSILON {
+            from
+        } else {
+            let theta = product.acos();
+            let w = (f * theta).sin() / (1.0 - product * product).sqrt();
+            let a = (f * theta).cos() - product * w;
+            [0, 1, 2, 3].map(|i| from[i] * a + to[i] * w)
+        };
+
+        DecomposedTransform {
+            perspective: [0, 1, 2, 3].map(|i| lerp(self.perspective[i], other.perspective[i])),
+            translate: lerp3(&self.translate, &other.translate),
+            quaternion,
+            skew: lerp3(&self.skew, &other.skew),
+            scale: lerp3(&self.scale, &other.scale),
+        }
+    }
+}
+
+impl Interpolate for Transform {
+    fn interpolate(&self, other: &Transform, f: f32) -> Transform {
+        if f <= 0.0 {
+            return self.clone();
+        }
+        if f >= 1.0 {
+            return other.clone();
+        }
+        // an empty transform animates from (or to) the identity of the other operations
+        let (from, to) = match (self.operations.is_empty(), other.operations.is_empty()) {
+            (true, false) => (
+                other.operations.iter().map(|op| op.identity()).collect(),
+                other.operations.clone(),
+            ),
+            (false, true) => (
+                self.operations.clone(),
+                self.operations.iter().map(|op| op.identity()).collect(),
+            ),
+            _ => (self.operations.clone(), other.operations.clone()),
+        };
+        if from.len() == to.len() {
+            let operations: Option<Vec<_>> = from
+                .iter()
+                .zip(to.iter())
+                .map(|(a, b)| interpolate_operation(a, b, f))
+                .collect();
+            if let Some(operations) = operations {
+                return Transform::new(operations);
+            }
+        }
+        let from = Transform::new(from).to_m44();
+        let to = Transform::new(to).to_m44();
+        match (
+            DecomposedTransform::decompose(&from),
+            DecomposedTransform::decompose(&to),
+        ) {
+            (Some(a), Some(b)) => Transform::from(a.interpolate(&b, f).recompose()),
+            // not invertible, the transform snaps halfway
+            _ if f < 0.5 => self.clone(),
+            _ => other.clone(),
+        }
+    }
+}
+
 impl Interpolate for Option<Picture> {
     fn interpolate(&self, other: &Option<Picture>, f: f32) -> Option<Picture> {
         if f < 0.5 {
@@ -319,6 +967,36 @@ impl Interpolate for bool {
         }
     }
 }
+
+impl Interpolate for BorderStyle {
+    fn interpolate(&self, other: &BorderStyle, f: f32) -> BorderStyle {
+        if f < 0.5 {
+            *self
+        } else {
+            *other
+        }
+    }
+}
+
+impl Interpolate for BlendMode {
+    fn interpolate(&self, other: &BlendMode, f: f32) -> BlendMode {
+        if f < 0.5 {
+            *self
+        } else {
+            *other
+        }
+    }
+}
+
+impl Interpolate for Option<BackgroundImage> {
+    fn interpolate(&self, other: &Option<BackgroundImage>, f: f32) -> Option<BackgroundImage> {
+        if f < 0.5 {
+            self.clone()
+        } else {
+            other.clone()
+        }
+    }
+}
 // easing version of the bezier 1d with p0 = 0 and p3 = 1
 fn bezier_easing_1d(p1: f64, p2: f64, f: f64) -> f64 {
     let f2 = f * f;
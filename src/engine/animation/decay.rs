@@ -0,0 +1,150 @@
+// https://developer.apple.com/documentation/uikit/uiscrollview/decelerationrate
+// https://www.ryanjuckett.com/damped-springs/
+
+use super::spring::Spring;
+
+const TOLERANCE_POSITION: f32 = 0.003;
+const TOLERANCE_VELOCITY: f32 = 0.003;
+
+/// An inertial timing: the progress starts moving with `initial_velocity`
+/// (expressed in progress units per second) and exponentially decelerates
+/// with the given `rate`, coming to rest at `initial_velocity / rate`.
+///
+/// Progress is relative to the animated change, so a progress of 1.0 maps to
+/// the target value of the change. Optional `bounds` (also in progress units)
+/// clamp the motion: when the decay crosses one of them, the remaining
+/// velocity is handed off to `spring`, which settles on the crossed bound.
+#[derive(Debug, Clone, Copy)]
+pub struct Decay {
+    pub initial_velocity: f32,
+    /// Exponential deceleration rate in 1/s
+    pub rate: f32,
+    pub bounds: Option<(f32, f32)>,
+    /// Spring used to settle on a bound once it is exceeded
+    pub spring: Spring,
+}
+
+/// The point where a bounded decay hands off to its spring
+#[derive(Debug, Clone, Copy)]
+struct Handoff {
+    time: f32,
+    position: f32,
+    velocity: f32,
+    bound: f32,
+}
+
+impl Decay {
+    /// Equivalent of UIScrollView.DecelerationRate.normal (0.998 per ms)
+    pub const RATE_NORMAL: f32 = 2.002;
+    /// Equivalent of UIScrollView.DecelerationRate.fast (0.99 per ms)
+    pub const RATE_FAST: f32 = 10.050;
+
+    pub fn new(initial_velocity: f32) -> Self {
+        Decay::with_rate(initial_velocity, Decay::RATE_NORMAL)
+    }
+
+    pub fn with_rate(initial_velocity: f32, rate: f32) -> Self {
+        Decay {
+            initial_velocity,
+            rate,
+            bounds: None,
+            spring: Spring::with_duration_and_bounce(0.4, 0.0),
+        }
+    }
+
+    pub fn with_bounds(initial_velocity: f32, min: f32, max: f32) -> Self {
+        let mut decay = Decay::new(initial_velocity);
+        decay.bounds = Some((min.min(max), max.max(min)));
+        decay
+    }
+
+    /// The progress where the unbounded decay comes to rest
+    pub fn projected_progress(&self) -> f32 {
+        self.initial_velocity / self.rate()
+    }
+
+    /// The progress where the animation comes to rest, taking the bounds into account
+    pub fn resting_progress(&self) -> f32 {
+        self.handoff()
+            .map(|handoff| handoff.bound)
+            .unwrap_or_else(|| self.projected_progress())
+    }
+
+    fn rate(&self) -> f32 {
+        self.rate.max(f32::EPSILON)
+    }
+
+    fn handoff(&self) -> Option<Handoff> {
+        let (min, max) = self.bounds?;
+        let v = self.initial_velocity;
+        let k = self.rate();
+        // already out of bounds, hand off right away
+        if 0.0 < min || 0.0 > max {
+            let bound = if 0.0 < min { min } else { max };
+            return Some(Handoff {
+                time: 0.0,
+                position: 0.0,
+                velocity: v,
+                bound,
+            });
+        }
+        let projected = self.projected_progress();
+        let bound = if projected > max {
+            max
+        } else if projected < min {
+            min
+        } else {
+            return None;
+        };
+        // solve v/k * (1 - e^(-k*t)) = bound
+        let remaining = 1.0 - bound * k / v;
+        let time = -remaining.ln() / k;
+        Some(Handoff {
+            time,
+            position: bound,
+            velocity: v * remaining,
+            bound,
+        })
+    }
+
+    fn spring_from(&self, handoff: &Handoff) -> Spring {
+        let mut spring = self.spring;
+        // the spring always targets 1.0, offset it so that it targets the bound
+        spring.initial_position = 1.0 + handoff.position - handoff.bound;
+        spring.initial_velocity = handoff.velocity;
+        spring
+    }
+
+    pub fn update_pos_vel_at(&self, t: f32) -> (f32, f32) {
+        if let Some(handoff) = self.handoff() {
+            if t >= handoff.time {
+                let (position, velocity) = self
+                    .spring_from(&handoff)
+                    .update_pos_vel_at(t - handoff.time);
+                return (handoff.bound + position - 1.0, velocity);
+            }
+        }
+        let k = self.rate();
+        let exp_decay = (-k * t).exp();
+        let velocity = self.initial_velocity * exp_decay;
+        let position = self.initial_velocity / k * (1.0 - exp_decay);
+        (position, velocity)
+    }
+
+    pub fn update_at(&mut self, elapsed: f32) -> f32 {
+        // Clamp negative elapsed (still within the transition delay) like the spring does
+        self.update_pos_vel_at(elapsed.max(0.0)).0
+    }
+
+    pub fn done(&self, elapsed: f32) -> bool {
+        if let Some(handoff) = self.handoff() {
+            if elapsed >= handoff.time {
+                return self.spring_from(&handoff).done(elapsed - handoff.time);
+            }
+            return false;
+        }
+        let (position, velocity) = self.update_pos_vel_at(elapsed);
+        (position - self.projected_progress()).abs() < TOLERANCE_POSITION
+            && velocity.abs() < TOLERANCE_VELOCITY
+    }
+}
@@ -1,6 +1,7 @@
 //! # Timing functions for animations:
 //! * `Easing`: a quadratic bezier curve timing defined by two points and a duration
 //! * `Spring`: a physics-based spring timing, emulating a dumped harmonic oscillator
+//! * `Decay`: an inertial timing, decelerating from an initial velocity
 //!
 //! # Helper functions:
 //! * `Transition::ease_in(duration: f32) -> Transition`
@@ -11,6 +12,10 @@
 //! * `Transition::ease_in_out_quad(duration: f32) -> Transition`
 //! * `Transition::spring(duration: f32, bounce: f32) -> Transition`
 //! * `Transition::spring_with_velocity(duration: f32, bounce: f32, velocity: f32) -> Transition`
+//! * `Transition::spring_critically_damped(duration: f32) -> Transition`
+//! * `Transition::spring_overdamped(duration: f32, damping_ratio: f32) -> Transition`
+//! * `Transition::decay(velocity: f32) -> Transition`
+//! * `Transition::decay_with_bounds(velocity: f32, min: f32, max: f32) -> Transition`
 //!   ...
 //!
 //! # Usage
@@ -29,6 +34,14 @@
 //!
 //! // spring helper with perceptual duration and initial velocity
 //! layer.set_position((100.0, 100.0), Transition::spring_with_velocity(0.3, 0.3, 0.3));
+//!
+//! // decay helper: velocity is expressed in progress units per second,
+//! // progress 1.0 being the target value; springs back into 0.0..=1.0
+//! layer.set_position((100.0, 100.0), Transition::decay_with_bounds(4.0, 0.0, 1.0));
+//!
+//! // 2D spring: both axes share the velocity vector, in points per second;
+//! // passing None keeps the velocity of the running 2D spring, if any
+//! layer.set_position_spring((200.0, 100.0), Spring::critically_damped(0.4), Point::new(0.0, 800.0));
 //! ```
 //!
 //! # Advanced Easing usage
@@ -58,14 +71,16 @@
 
 use core::fmt;
 
+mod decay;
 mod easing;
 mod spring;
 mod timing;
 
 pub use self::timing::{KeyframeSegment, TimingFunction};
 
+pub use decay::Decay;
 pub use easing::Easing;
-pub use spring::Spring;
+pub use spring::{Spring, Spring2d};
 
 /// Transition is a data structure that contains the information needed to
 /// create an animation that can start at a later time.
@@ -139,6 +154,30 @@ impl Transition {
             timing: TimingFunction::spring_with_initial_velocity(duration, bounce, velocity),
         }
     }
+    pub fn spring_critically_damped(duration: f32) -> Self {
+        Transition {
+            delay: 0.0,
+            timing: TimingFunction::spring_critically_damped(duration),
+        }
+    }
+    pub fn spring_overdamped(duration: f32, damping_ratio: f32) -> Self {
+        Transition {
+            delay: 0.0,
+            timing: TimingFunction::spring_overdamped(duration, damping_ratio),
+        }
+    }
+    pub fn decay(velocity: f32) -> Self {
+        Transition {
+            delay: 0.0,
+            timing: TimingFunction::decay(velocity),
+        }
+    }
+    pub fn decay_with_bounds(velocity: f32, min: f32, max: f32) -> Self {
+        Transition {
+            delay: 0.0,
+            timing: TimingFunction::decay_with_bounds(velocity, min, max),
+        }
+    }
     pub fn keyframes(segments: Vec<KeyframeSegment>) -> Self {
         Transition {
             delay: 0.0,
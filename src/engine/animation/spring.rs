@@ -1,6 +1,8 @@
 // http://hyperphysics.phy-astr.gsu.edu/hbase/oscda.html
 // https://www.ryanjuckett.com/damped-springs/
 
+use crate::types::Point;
+
 #[derive(Debug, Clone, Copy)]
 pub struct Spring {
     pub mass: f32,
@@ -9,6 +11,10 @@ pub struct Spring {
     pub initial_velocity: f32,
     pub initial_position: f32,
     pub last_update: f32,
+    /// Distance from the target under which the spring is considered at rest
+    pub rest_position: f32,
+    /// Speed under which the spring is considered at rest
+    pub rest_velocity: f32,
 }
 
 const TOLERANCE_POSITION: f32 = 0.003;
@@ -23,6 +29,8 @@ impl Spring {
             initial_velocity: 0.0,
             initial_position: 0.0,
             last_update: 0.0,
+            rest_position: TOLERANCE_POSITION,
+            rest_velocity: TOLERANCE_VELOCITY,
         }
     }
 
@@ -39,6 +47,8 @@ impl Spring {
             initial_velocity,
             initial_position: 0.0,
             last_update: 0.0,
+            rest_position: TOLERANCE_POSITION,
+            rest_velocity: TOLERANCE_VELOCITY,
         }
     }
 
@@ -66,8 +76,91 @@ impl Spring {
             initial_velocity: 0.0,
             initial_position: 0.0,
             last_update: 0.0,
+            rest_position: TOLERANCE_POSITION,
+            rest_velocity: TOLERANCE_VELOCITY,
+        }
+    }
+
+    /// A spring that reaches its target as fast as possible without overshooting
+    pub fn critically_damped(duration: f32) -> Self {
+        Spring::with_duration_and_damping_ratio(duration, 1.0)
+    }
+
+    /// A spring that slowly creeps towards its target, `damping_ratio` is
+    /// clamped to be greater than 1.0
+    pub fn overdamped(duration: f32, damping_ratio: f32) -> Self {
+        Spring::with_duration_and_damping_ratio(duration, damping_ratio.max(1.0))
+    }
+
+    pub fn with_duration_and_damping_ratio(duration: f32, damping_ratio: f32) -> Self {
+        let mass = 1.0;
+        let omega = 2.0 * std::f32::consts::PI / duration;
+        let stiffness = mass * omega.powi(2);
+        // computed from the stiffness so that the damping ratio is exact
+        let damping = damping_ratio * 2.0 * (mass * stiffness).sqrt();
+        Spring::new(mass, stiffness, damping)
+    }
+
+    /// Override the thresholds used to decide when the spring is at rest
+    pub fn with_rest_thresholds(mut self, position: f32, velocity: f32) -> Self {
+        self.rest_position = position;
+        self.rest_velocity = velocity;
+        self
+    }
+
+    pub fn natural_frequency(&self) -> f32 {
+        (self.stiffness / self.mass).sqrt()
+    }
+
+    pub fn damping_ratio(&self) -> f32 {
+        self.damping / (2.0 * (self.mass * self.stiffness).sqrt())
+    }
+
+    /// Analytic estimate of the time, in seconds, after which the spring
+    /// envelope stays within `rest_position` from the target
+    pub fn settling_time(&self) -> f32 {
+        let omega = self.natural_frequency();
+        let zeta = self.damping_ratio();
+        let delta_x = self.initial_position - 1.0;
+        let v0 = self.initial_velocity;
+        let tolerance = self.rest_position.max(f32::EPSILON);
+
+        let time = if zeta < 1.0 {
+            let omega_d = omega * (1.0 - zeta * zeta).sqrt();
+            let amplitude =
+                (delta_x.powi(2) + ((v0 + zeta * omega * delta_x) / omega_d).powi(2)).sqrt();
+            (amplitude / tolerance).ln() / (zeta * omega)
+        } else if zeta == 1.0 {
+            // the envelope is (|a| + |b|t)e^(-wt), refine the estimate a few times
+            let a = delta_x.abs();
+            let b = (v0 + omega * delta_x).abs();
+            let mut t = ((a + b / omega) / tolerance).ln() / omega;
+            for _ in 0..8 {
+                t = ((a + b * t.max(0.0)) / tolerance).ln() / omega;
+            }
+            t
+        } else {
+            // the slowest root dominates the envelope
+            let (_, r2, c1, c2) = self.overdamped_terms(omega, zeta, delta_x);
+            ((c1.abs() + c2.abs()) / tolerance).ln() / r2.abs()
+        };
+        if time.is_finite() {
+            time.max(0.0)
+        } else {
+            0.0
         }
     }
+
+    /// roots and coefficients of the overdamped solution
+    /// x(t) = c1 * e^(r1 * t) + c2 * e^(r2 * t)
+    fn overdamped_terms(&self, omega: f32, zeta: f32, delta_x: f32) -> (f32, f32, f32, f32) {
+        let r1 = -omega * (zeta + (zeta * zeta - 1.0).sqrt());
+        let r2 = -omega * (zeta - (zeta * zeta - 1.0).sqrt());
+        let c1 = (self.initial_velocity - r2 * delta_x) / (r1 - r2);
+        let c2 = delta_x - c1;
+        (r1, r2, c1, c2)
+    }
+
     pub fn with_duration_bounce_and_velocity(
         duration: f32,
         bounce: f32,
@@ -107,12 +200,11 @@ impl Spring {
             (new_position, new_velocity)
         } else {
             // Overdamped case
-            let r1 = -omega * (zeta + (zeta * zeta - 1.0).sqrt());
-            let r2 = -omega * (zeta - (zeta * zeta - 1.0).sqrt());
+            let (r1, r2, c1, c2) = self.overdamped_terms(omega, zeta, delta_x);
             let exp_r1 = (r1 * t).exp();
             let exp_r2 = (r2 * t).exp();
-            let new_position = target + delta_x * (exp_r1 + exp_r2) / 2.0;
-            let new_velocity = delta_x * (r1 * exp_r1 + r2 * exp_r2) / 2.0;
+            let new_position = target + c1 * exp_r1 + c2 * exp_r2;
+            let new_velocity = c1 * r1 * exp_r1 + c2 * r2 * exp_r2;
             (new_position, new_velocity)
         }
     }
@@ -127,6 +219,91 @@ impl Spring {
         let target = 1.0;
         let (position, velocity) = self.update_pos_vel_at(elapsed);
 
-        (position - target).abs() < TOLERANCE_POSITION && velocity.abs() < TOLERANCE_VELOCITY
+        (position - target).abs() < self.rest_position && velocity.abs() < self.rest_velocity
+    }
+}
+
+/// A spring animating a `Point` in two dimensions.
+///
+/// Both axes share a single velocity vector, expressed in value units per
+/// second, so a retargeted motion keeps its momentum and direction instead of
+/// being squeezed onto the straight line between `from` and `to`.
+/// Rest thresholds of the inner spring are relative to the travelled distance,
+/// as they are for progress based springs.
+#[derive(Debug, Clone, Copy)]
+pub struct Spring2d {
+    pub spring: Spring,
+    pub from: Point,
+    pub to: Point,
+    pub initial_velocity: Point,
+}
+
+impl Spring2d {
+    pub fn new(spring: Spring, from: Point, to: Point, initial_velocity: Point) -> Self {
+        Spring2d {
+            spring,
+            from,
+            to,
+            initial_velocity,
+        }
+    }
+
+    fn distance(&self) -> f32 {
+        let dx = self.to.x - self.from.x;
+        let dy = self.to.y - self.from.y;
+        (dx * dx + dy * dy).sqrt()
+    }
+
+    /// The motion is linear in the initial displacement and velocity, so it is
+    /// composed from the response to a unit displacement and to a unit velocity.
+    pub fn update_pos_vel_at(&self, t: f32) -> (Point, Point) {
+        let mut displacement = self.spring;
+        displacement.initial_position = 0.0;
+        displacement.initial_velocity = 0.0;
+        let (d_pos, d_vel) = displacement.update_pos_vel_at(t);
+
+        let mut impulse = self.spring;
+        impulse.initial_position = 1.0;
+        impulse.initial_velocity = 1.0;
+        let (i_pos, i_vel) = impulse.update_pos_vel_at(t);
+
+        let delta = Point {
+            x: self.from.x - self.to.x,
+            y: self.from.y - self.to.y,
+        };
+        let v = self.initial_velocity;
+        let d = 1.0 - d_pos;
+        let i = i_pos - 1.0;
+        let position = Point {
+            x: self.to.x + delta.x * d + v.x * i,
+            y: self.to.y + delta.y * d + v.y * i,
+        };
+        let velocity = Point {
+            x: -delta.x * d_vel + v.x * i_vel,
+            y: -delta.y * d_vel + v.y * i_vel,
+        };
+        (position, velocity)
+    }
+
+    /// Progress of the motion projected on the `from` -> `to` segment
+    pub fn update_at(&mut self, elapsed: f32) -> f32 {
+        let (position, _) = self.update_pos_vel_at(elapsed.max(0.0));
+        let dx = self.to.x - self.from.x;
+        let dy = self.to.y - self.from.y;
+        let length_sq = dx * dx + dy * dy;
+        if length_sq <= f32::EPSILON {
+            return 1.0;
+        }
+        ((position.x - self.from.x) * dx + (position.y - self.from.y) * dy) / length_sq
+    }
+
+    pub fn done(&self, elapsed: f32) -> bool {
+        let (position, velocity) = self.update_pos_vel_at(elapsed);
+        let scale = self.distance().max(1.0);
+        let dx = position.x - self.to.x;
+        let dy = position.y - self.to.y;
+        (dx * dx + dy * dy).sqrt() < self.spring.rest_position * scale
+            && (velocity.x * velocity.x + velocity.y * velocity.y).sqrt()
+                < self.spring.rest_velocity * scale
     }
 }
@@ -1,4 +1,8 @@
-use super::{spring::Spring, Easing};
+use super::{
+    decay::Decay,
+    spring::{Spring, Spring2d},
+    Easing,
+};
 
 /// A single segment within a keyframe animation.
 ///
@@ -22,10 +26,16 @@ pub struct KeyframeSegment {
 pub enum TimingFunction {
     Easing(Easing, f32),
     Spring(Spring),
+    /// A spring moving a `Point` with a velocity vector shared by both axes;
+    /// the progress is the motion projected on the from -> to segment.
+    Spring2d(Spring2d),
     /// Piecewise timing: a sequence of segments, each with its own
     /// duration, easing, and progress range.
     /// The second field caches the total duration to avoid recomputing per frame.
     Keyframes(Vec<KeyframeSegment>, f32),
+    /// Inertial timing: starts from an initial velocity and decelerates
+    /// exponentially, optionally settling on bounds with a spring.
+    Decay(Decay),
 }
 
 impl TimingFunction {
@@ -80,6 +90,26 @@ impl TimingFunction {
         ))
     }
 
+    /// A spring that settles as fast as possible without overshooting.
+    pub fn spring_critically_damped(duration: f32) -> Self {
+        TimingFunction::Spring(Spring::critically_damped(duration))
+    }
+
+    /// A spring that creeps towards the target, `damping_ratio` is at least 1.0.
+    pub fn spring_overdamped(duration: f32, damping_ratio: f32) -> Self {
+        TimingFunction::Spring(Spring::overdamped(duration, damping_ratio))
+    }
+
+    /// Build a decay timing function from a velocity in progress units per second.
+    pub fn decay(initial_velocity: f32) -> Self {
+        TimingFunction::Decay(Decay::new(initial_velocity))
+    }
+
+    /// Build a decay timing function that springs back when leaving `min..=max`.
+    pub fn decay_with_bounds(initial_velocity: f32, min: f32, max: f32) -> Self {
+        TimingFunction::Decay(Decay::with_bounds(initial_velocity, min, max))
+    }
+
     /// Build a keyframes timing function from a list of segments.
     pub fn keyframes(segments: Vec<KeyframeSegment>) -> Self {
         let total_duration: f32 = segments.iter().map(|s| s.duration).sum();
@@ -89,12 +119,21 @@ impl TimingFunction {
     pub fn update_at(&mut self, elapsed: f32) -> (f32, f32) {
         match self {
             TimingFunction::Easing(Easing { x1, x2, y1, y2 }, duration) => {
-                let t = elapsed / *duration;
+                // a zero duration completes as soon as the delay is over
+                let t = if *duration > 0.0 {
+                    elapsed / *duration
+                } else if elapsed >= 0.0 {
+                    1.0
+                } else {
+                    0.0
+                };
                 let t = t.clamp(0.0, 1.0);
                 let ease = bezier_easing::bezier_easing(*x1, *y1, *x2, *y2).unwrap();
                 (ease(t), t)
             }
             TimingFunction::Spring(solver) => (solver.update_at(elapsed), elapsed),
+            TimingFunction::Spring2d(solver) => (solver.update_at(elapsed), elapsed),
+            TimingFunction::Decay(solver) => (solver.update_at(elapsed), elapsed),
             TimingFunction::Keyframes(segments, total_duration) => {
                 let total_duration = *total_duration;
                 if total_duration <= 0.0 || segments.is_empty() {
@@ -140,7 +179,9 @@ impl TimingFunction {
         match self {
             TimingFunction::Easing(_, duration) => current - start >= *duration,
             TimingFunction::Spring(solver) => solver.done(current - start),
+            TimingFunction::Spring2d(solver) => solver.done(current - start),
             TimingFunction::Keyframes(_, total_duration) => current - start >= *total_duration,
+            TimingFunction::Decay(solver) => solver.done(current - start),
         }
     }
 }
@@ -7,28 +7,139 @@
 //! On every update the Engine step forward the animations and applies the changes to the Nodes.
 
 use super::{
-    animation::Transition, node::RenderableFlags, AnimationRef, Command, Engine, SyncCommand,
-    TransactionCallback, TransactionRef,
+    animation::{Spring2d, Transition},
+    node::RenderableFlags,
+    storage::FlatStorageId,
+    AnimatedNodeChange, AnimationRef, Command, Engine, SyncCommand, TransactionCallback,
+    TransactionRef,
 };
-use crate::easing::Interpolate;
-use std::sync::{
-    atomic::{AtomicUsize, Ordering},
-    Arc,
+use crate::{
+    easing::Interpolate,
+    shape::MeasuredPath,
+    types::{Point, Point3d},
+};
+use std::{
+    cell::{Cell, RefCell},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
 };
 
 static ATTRIBUTE_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
+thread_local! {
+    // depth of the nested scopes disabling implicit transitions on this thread
+    static IMPLICIT_TRANSITIONS_DISABLED: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Changes recorded by `Engine::transaction` on the current thread,
+/// waiting to be committed together
+struct RecordedTransaction {
+    engine_id: usize,
+    animation: AnimationRef,
+    changes: Vec<(FlatStorageId, AnimatedNodeChange)>,
+}
+
+thread_local! {
+    static RECORDED_TRANSACTIONS: RefCell<Vec<RecordedTransaction>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The shared animation of the innermost transaction recorded for the engine
+pub(crate) fn recording_animation(engine_id: usize) -> Option<AnimationRef> {
+    RECORDED_TRANSACTIONS.with(|recorded| {
+        recorded
+            .borrow()
+            .iter()
+            .rev()
+            .find(|transaction| transaction.engine_id == engine_id)
+            .map(|transaction| transaction.animation)
+    })
+}
+
+/// Adds a change to the innermost transaction recorded for the engine
+pub(crate) fn record_change(engine_id: usize, id: FlatStorageId, change: AnimatedNodeChange) {
+    RECORDED_TRANSACTIONS.with(|recorded| {
+        if let Some(transaction) = recorded
+            .borrow_mut()
+            .iter_mut()
+            .rev()
+            .find(|transaction| transaction.engine_id == engine_id)
+        {
+            transaction.changes.push((id, change));
+        }
+    });
+}
+
+/// Runs `f` recording the changes scheduled on the engine, returns them
+/// once `f` is done. The recorded changes are discarded if `f` panics.
+pub(crate) fn record_transaction(
+    engine_id: usize,
+    animation: AnimationRef,
+    f: impl FnOnce(),
+) -> Vec<(FlatStorageId, AnimatedNodeChange)> {
+    struct ScopeGuard;
+    impl Drop for ScopeGuard {
+        fn drop(&mut self) {
+            RECORDED_TRANSACTIONS.with(|recorded| {
+                recorded.borrow_mut().pop();
+            });
+        }
+    }
+    RECORDED_TRANSACTIONS.with(|recorded| {
+        recorded.borrow_mut().push(RecordedTransaction {
+            engine_id,
+            animation,
+            changes: Vec::new(),
+        })
+    });
+    let guard = ScopeGuard;
+    f();
+    let changes = RECORDED_TRANSACTIONS.with(|recorded| {
+        recorded
+            .borrow_mut()
+            .last_mut()
+            .map(|transaction| std::mem::take(&mut transaction.changes))
+            .unwrap_or_default()
+    });
+    drop(guard);
+    changes
+}
+
+/// Returns false while running inside `without_implicit_transitions`
+pub(crate) fn implicit_transitions_enabled() -> bool {
+    IMPLICIT_TRANSITIONS_DISABLED.with(|depth| depth.get() == 0)
+}
+
+/// Runs `f` with implicit transitions disabled on the current thread
+pub(crate) fn without_implicit_transitions<R>(f: impl FnOnce() -> R) -> R {
+    struct ScopeGuard;
+    impl Drop for ScopeGuard {
+        fn drop(&mut self) {
+            IMPLICIT_TRANSITIONS_DISABLED.with(|depth| depth.set(depth.get() - 1));
+        }
+    }
+    IMPLICIT_TRANSITIONS_DISABLED.with(|depth| depth.set(depth.get() + 1));
+    let _guard = ScopeGuard;
+    f()
+}
+
 #[derive(Debug, Clone)]
 pub struct Attribute<V: Sync + std::fmt::Debug> {
     pub id: usize,
     value: Arc<parking_lot::RwLock<V>>,
 }
 
+/// A new unique id for a value changed through the engine
+pub(crate) fn next_attribute_id() -> usize {
+    ATTRIBUTE_COUNTER.fetch_add(1, Ordering::SeqCst)
+}
+
 impl<V: Sync + Clone + std::fmt::Debug> Attribute<V> {
     pub fn new(value: V) -> Attribute<V> {
         let value = Arc::new(parking_lot::RwLock::new(value));
         Self {
-            id: ATTRIBUTE_COUNTER.fetch_add(1, Ordering::SeqCst),
+            id: next_attribute_id(),
             value,
         }
     }
@@ -60,6 +171,13 @@ impl<V: Sync + Clone + std::fmt::Debug> Attribute<V> {
     }
 }
 
+/// Two attributes are equal when they share the same value
+impl<V: Sync + std::fmt::Debug> PartialEq for Attribute<V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
 /// A representation of a change to a property, including an optional transition
 
 #[derive(Clone, Debug)]
@@ -78,6 +196,42 @@ pub struct ModelChange<T: Sync + std::fmt::Debug> {
     pub flag: RenderableFlags,
 }
 
+/// Change to a model property that is not interpolated: the new value is
+/// applied as soon as the change is committed, whatever the animation progress
+#[derive(Clone, Debug)]
+pub struct ValueChange<T: Sync + std::fmt::Debug> {
+    pub value_change: AttributeChange<T>,
+    pub flag: RenderableFlags,
+}
+
+/// Change to a `Point` model property driven by a 2D spring: the value is
+/// computed from the elapsed time rather than the progress, so that both
+/// axes follow the spring velocity vector
+#[derive(Clone, Debug)]
+pub struct SpringPointChange {
+    pub value_change: AttributeChange<Point>,
+    pub spring: Spring2d,
+    pub flag: RenderableFlags,
+}
+
+/// The model property driven by a `MotionPathChange`
+#[derive(Clone, Debug)]
+pub enum MotionPathTarget {
+    /// Moves a point along the path
+    Position(Attribute<Point>),
+    /// Rotates around the z axis to follow the path tangent
+    Rotation(Attribute<Point3d>),
+}
+
+/// Change that maps the animation progress to a distance along a path,
+/// measured once when the change is created
+#[derive(Clone, Debug)]
+pub struct MotionPathChange {
+    pub path: MeasuredPath,
+    pub target: MotionPathTarget,
+    pub flag: RenderableFlags,
+}
+
 #[derive(Clone)]
 pub struct Transaction {
     pub(crate) engine: Arc<Engine>,
@@ -149,6 +303,88 @@ impl<I: Interpolate + Sync + PartialEq + Send + Clone + std::fmt::Debug + 'stati
 {
 }
 
+impl<V: PartialEq + std::fmt::Debug + Send + Sync + Clone> Command for ValueChange<V> {
+    fn execute(&self, _progress: f32) -> RenderableFlags {
+        let value_change = &self.value_change;
+        if value_change.from != value_change.to {
+            value_change.target.set(value_change.to.clone());
+            self.flag
+        } else {
+            RenderableFlags::empty()
+        }
+    }
+    fn value_id(&self) -> usize {
+        self.value_change.target.id
+    }
+}
+impl<V: PartialEq + std::fmt::Debug + Send + Sync + Clone + 'static> SyncCommand
+    for ValueChange<V>
+{
+}
+
+impl Command for SpringPointChange {
+    fn execute(&self, progress: f32) -> RenderableFlags {
+        let value_change = &self.value_change;
+        let value_to = value_change.from.interpolate(&value_change.to, progress);
+        if value_change.target.value() != value_to {
+            value_change.target.set(value_to);
+            self.flag
+        } else {
+            RenderableFlags::empty()
+        }
+    }
+    fn execute_at(&self, _progress: f32, elapsed: f32) -> RenderableFlags {
+        let (value_to, _velocity) = self.spring.update_pos_vel_at(elapsed);
+        if self.value_change.target.value() != value_to {
+            self.value_change.target.set(value_to);
+            self.flag
+        } else {
+            RenderableFlags::empty()
+        }
+    }
+    fn value_id(&self) -> usize {
+        self.value_change.target.id
+    }
+}
+impl SyncCommand for SpringPointChange {}
+
+impl Command for MotionPathChange {
+    fn execute(&self, progress: f32) -> RenderableFlags {
+        let Some((position, tangent)) = self.path.pos_tan_at(progress) else {
+            return RenderableFlags::empty();
+        };
+        match &self.target {
+            MotionPathTarget::Position(attribute) => {
+                let position = Point {
+                    x: position.x,
+                    y: position.y,
+                };
+                if attribute.value() != position {
+                    attribute.set(position);
+                    return self.flag;
+                }
+            }
+            MotionPathTarget::Rotation(attribute) => {
+                let mut rotation = attribute.value();
+                let angle = tangent.y.atan2(tangent.x);
+                if rotation.z != angle {
+                    rotation.z = angle;
+                    attribute.set(rotation);
+                    return self.flag;
+                }
+            }
+        }
+        RenderableFlags::empty()
+    }
+    fn value_id(&self) -> usize {
+        match &self.target {
+            MotionPathTarget::Position(attribute) => attribute.id,
+            MotionPathTarget::Rotation(attribute) => attribute.id,
+        }
+    }
+}
+impl SyncCommand for MotionPathChange {}
+
 macro_rules! change_model {
     ($variable_name:ident, $variable_type:ty, $flags:expr) => {
         paste::paste! {
@@ -157,7 +393,8 @@ macro_rules! change_model {
                 value: impl Into<$variable_type>,
                 transition: impl Into<Option<Transition>>,
             )  -> TransactionRef {
-                let transition = transition.into();
+                let transition =
+                    self.transition_or_implicit(stringify!($variable_name), transition.into());
                 let value:$variable_type = value.into();
 
                 let flags = $flags;
@@ -183,6 +420,9 @@ macro_rules! change_model {
                                                 s.update_pos_vel_at(animation_state.time);
                                             current_velocity
                                         }
+                                        TimingFunction::Decay(d) => {
+                                            d.update_pos_vel_at(animation_state.time).1
+                                        }
                                         _ => 0.0,
                                     }
                                 } else {
@@ -199,7 +439,7 @@ macro_rules! change_model {
                         start: t.delay + self.engine.now(),
                     }, true)
                 });
-                if animation.is_none() {
+                if animation.is_none() && !self.engine.is_recording_transaction() {
                     // if there is no transition, apply the change immediately
                     self.model.$variable_name.set(value);
                 }
@@ -228,4 +468,68 @@ macro_rules! change_model {
     };
 }
 
+/// Generates a `set_<name>_spring` setter for `Point` properties, animating
+/// them with a 2D spring. When `velocity` is None, the velocity vector of a
+/// running 2D spring on the same property is carried over. Inside
+/// `Engine::transaction` the spring is ignored and the value follows the
+/// transaction timing, like the other setters of the batch.
+macro_rules! change_model_spring_2d {
+    ($variable_name:ident, $flags:expr) => {
+        paste::paste! {
+            pub fn [<set_ $variable_name _spring>](
+                &self,
+                value: impl Into<Point>,
+                spring: Spring,
+                velocity: impl Into<Option<Point>>,
+            ) -> TransactionRef {
+                let value: Point = value.into();
+                let value_id = self.model.$variable_name.id;
+                let now = self.engine.now();
+
+                let velocity = velocity.into().unwrap_or_else(|| {
+                    self.engine
+                        .get_transaction_for_value(value_id)
+                        .and_then(|running_transaction| running_transaction.animation_id)
+                        .and_then(|animation_id| self.engine.get_animation(animation_id))
+                        .map(|animation_state| match animation_state.animation.timing {
+                            TimingFunction::Spring2d(s) => {
+                                let elapsed = (now - animation_state.animation.start).max(0.0);
+                                s.update_pos_vel_at(elapsed).1
+                            }
+                            _ => Point::default(),
+                        })
+                        .unwrap_or_default()
+                });
+
+                if self.engine.is_recording_transaction() {
+                    // inside Engine::transaction the change follows the progress
+                    // of the shared animation, the spring is not used
+                    let change = Arc::new(ModelChange {
+                        value_change: self.model.$variable_name.to(value, None),
+                        flag: $flags,
+                    });
+                    return self.engine.schedule_change(self.id, change, None);
+                }
+
+                let from = self.model.$variable_name.value();
+                let spring = Spring2d::new(spring, from, value, velocity);
+                let change = Arc::new(SpringPointChange {
+                    value_change: self.model.$variable_name.to(value, None),
+                    spring,
+                    flag: $flags,
+                });
+                let animation = self.engine.add_animation(
+                    Animation {
+                        timing: TimingFunction::Spring2d(spring),
+                        start: now,
+                    },
+                    true,
+                );
+                self.engine.schedule_change(self.id, change, Some(animation))
+            }
+        }
+    };
+}
+
 pub(crate) use change_model;
+pub(crate) use change_model_spring_2d;
@@ -0,0 +1,119 @@
+//! Statistics of the engine updates, always collected: the time spent in each
+//! stage of [`Engine::update`](super::Engine::update), the number of nodes
+//! updated and repainted, the damaged area and the use of the caches.
+//! A rolling history of the last updates is kept, e.g. to draw a
+//! performance overlay.
+
+use std::{collections::VecDeque, time::Duration};
+
+use serde::Serialize;
+
+use crate::drawing::SurfaceCacheStats;
+
+/// The number of updates kept in the frame statistics history
+pub const FRAME_STATS_HISTORY_SIZE: usize = 120;
+
+/// The time spent in each stage of an update
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct StageTimings {
+    /// Advancing the animations
+    pub animations: Duration,
+    /// Applying the transactions to the layers
+    pub transactions: Duration,
+    /// Computing the layout tree
+    pub layout: Duration,
+    /// Updating and repainting the render nodes
+    pub nodes: Duration,
+    /// Running the transitions and animations callbacks
+    pub callbacks: Duration,
+    /// Removing the deleted nodes, finished animations and transactions
+    pub cleanup: Duration,
+}
+
+impl StageTimings {
+    /// The time spent in all the stages
+    pub fn total(&self) -> Duration {
+        self.animations
+            + self.transactions
+            + self.layout
+            + self.nodes
+            + self.callbacks
+            + self.cleanup
+    }
+}
+
+/// The statistics of an update and of the drawing that followed it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct FrameStats {
+    pub timings: StageTimings,
+    /// The nodes changed by the transactions
+    pub nodes_updated: usize,
+    /// The nodes whose content was repainted
+    pub nodes_repainted: usize,
+    /// The damaged area, in pixels
+    pub damage_area: u64,
+    /// The image-cache surfaces reused while drawing
+    pub cache_hits: usize,
+    /// The image-cache surfaces created while drawing
+    pub cache_misses: usize,
+    /// The nodes culled by the last occlusion computation
+    pub occluded_nodes: usize,
+}
+
+impl FrameStats {
+    /// The area of the damaged region
+    pub(crate) fn region_area(region: &skia_safe::Region) -> u64 {
+        skia_safe::region::Iterator::new(region)
+            .map(|rect| rect.width() as u64 * rect.height() as u64)
+            .sum()
+    }
+}
+
+/// The statistics of the last updates, the most recent last.
+/// The cache lookups are attributed to the most recent update until the
+/// next one starts.
+#[derive(Debug, Default)]
+pub(crate) struct FrameStatsHistory {
+    frames: VecDeque<FrameStats>,
+    /// The cache counters when the most recent update ended
+    cache_at_update: SurfaceCacheStats,
+}
+
+impl FrameStatsHistory {
+    pub(crate) fn push(&mut self, mut stats: FrameStats, cache: SurfaceCacheStats) {
+        if let Some(last) = self.frames.back_mut() {
+            Self::set_cache_lookups(last, &self.cache_at_update, &cache);
+            // occlusion is computed once per drawing, the last result holds
+            // until it is computed again
+            stats.occluded_nodes = last.occluded_nodes;
+        }
+        if self.frames.len() == FRAME_STATS_HISTORY_SIZE {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(stats);
+        self.cache_at_update = cache;
+    }
+    pub(crate) fn set_occluded_nodes(&mut self, occluded_nodes: usize) {
+        if let Some(last) = self.frames.back_mut() {
+            last.occluded_nodes = occluded_nodes;
+        }
+    }
+    /// The statistics of the most recent update
+    pub(crate) fn last(&self, cache: &SurfaceCacheStats) -> FrameStats {
+        let mut last = self.frames.back().copied().unwrap_or_default();
+        Self::set_cache_lookups(&mut last, &self.cache_at_update, cache);
+        last
+    }
+    /// The statistics of the last updates, the oldest first
+    pub(crate) fn history(&self, cache: &SurfaceCacheStats) -> Vec<FrameStats> {
+        let mut frames: Vec<_> = self.frames.iter().copied().collect();
+        if let Some(last) = frames.last_mut() {
+            Self::set_cache_lookups(last, &self.cache_at_update, cache);
+        }
+        frames
+    }
+    fn set_cache_lookups(stats: &mut FrameStats, from: &SurfaceCacheStats, to: &SurfaceCacheStats) {
+        stats.cache_hits = to.hits.saturating_sub(from.hits);
+        stats.cache_misses = to.misses.saturating_sub(from.misses);
+    }
+}
@@ -230,10 +230,46 @@ impl LayersEngine {
     pub fn damage(&self) -> skia_safe::Rect {
         *self.engine.damage.read().unwrap()
     }
-    /// Clear the damage rect of the scene
+    /// Get the damaged areas of the scene, the damage rect is their bounds
+    pub fn damage_region(&self) -> skia_safe::Region {
+        self.engine.damage_region()
+    }
+    /// Clear the damage rect of the scene, ending the current frame
     #[profiling::function]
     pub fn clear_damage(&self) {
-        *self.engine.damage.write().unwrap() = skia_safe::Rect::default();
+        self.engine.clear_damage();
+    }
+    /// The number of frames completed
+    pub fn frame_count(&self) -> usize {
+        self.engine.frame_count()
+    }
+    /// The area to repaint in a buffer of the given age, None for a full repaint
+    pub fn damage_since(&self, frames_ago: usize) -> Option<skia_safe::Region> {
+        self.engine.damage_since(frames_ago)
+    }
+    /// Statistics of the surfaces of the image-cached layers
+    pub fn surface_cache_stats(&self) -> crate::drawing::SurfaceCacheStats {
+        self.engine.surface_cache_stats()
+    }
+    /// Drops the cached surfaces of the image-cached layers
+    pub fn purge_caches(&self) {
+        self.engine.purge_caches();
+    }
+    /// Sets the debug overlays drawn over the scene
+    pub fn set_debug_overlays(&self, overlays: crate::drawing::DebugOverlays) {
+        self.engine.set_debug_overlays(overlays);
+    }
+    /// The statistics of the most recent update
+    pub fn frame_stats(&self) -> super::FrameStats {
+        self.engine.frame_stats()
+    }
+    /// The statistics of the last updates, the oldest first
+    pub fn frame_stats_history(&self) -> Vec<super::FrameStats> {
+        self.engine.frame_stats_history()
+    }
+    /// Enables the automatic image caching of stable subtrees
+    pub fn set_auto_image_caching(&self, enabled: bool) {
+        self.engine.set_auto_image_caching(enabled);
     }
     /// Sends a pointer move event to the engine
     ///
@@ -380,7 +416,16 @@ impl LayersEngine {
             let scene = engine_ref.scene();
             scene.with_arena(|arena| {
                 scene.with_renderable_arena(|renderable_arena| {
-                    render_node_tree(id, arena, renderable_arena, c, 1.0, None, None);
+                    render_node_tree_with_cache(
+                        id,
+                        arena,
+                        renderable_arena,
+                        c,
+                        1.0,
+                        None,
+                        None,
+                        Some(scene.surface_cache()),
+                    );
                 });
             });
             skia::Rect::from_xywh(0.0, 0.0, w, h)
@@ -22,7 +22,8 @@
 
 #![allow(unused_imports)]
 
-pub use node::SceneNode;
+pub use frame_stats::{FrameStats, StageTimings, FRAME_STATS_HISTORY_SIZE};
+pub use node::{AutoCacheDecision, SceneNode};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use tracing::error;
 
@@ -32,6 +33,7 @@ mod stages;
 
 pub(crate) mod command;
 pub(crate) mod draw_to_picture;
+mod frame_stats;
 pub mod scene;
 
 pub mod animation;
@@ -52,30 +54,37 @@ use stages::{nodes_for_layout, trigger_callbacks, update_node_single};
 use taffy::prelude::*;
 
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     hash::Hash,
     ops::Deref,
     sync::{
-        atomic::{AtomicBool, AtomicUsize, Ordering},
+        atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering},
         Arc, RwLock,
     },
+    time::Instant,
 };
 
 use once_cell::sync::Lazy;
 
 use self::{
-    animation::{Animation, Transition},
+    animation::{Animation, TimingFunction, Transition},
+    frame_stats::FrameStatsHistory,
     // command::NoopChange,
     node::RenderableFlags,
+    occlusion::OcclusionCache,
     scene::Scene,
     stages::{
         cleanup_animations, cleanup_nodes, cleanup_transactions, execute_transactions,
-        update_animations, update_layout_tree,
+        update_animations, update_auto_caching, update_layout_tree,
     },
     storage::{FlatStorage, FlatStorageId, TreeStorageId},
 };
 use crate::{
-    drawing::render_node_tree,
+    drawing::{
+        render_node_tree_with_cache,
+        surface_cache::{SurfaceCache, SurfaceCacheStats},
+        DebugOverlays,
+    },
     engine::node::SceneNodeRenderable,
     layers::layer::{model::PointerHandlerFunction, render_layer::RenderLayer, Layer},
     prelude::ContentDrawFunction,
@@ -89,6 +98,12 @@ pub struct Timestamp(f32);
 /// A trait for objects that can be exectuded by the engine.
 pub trait Command {
     fn execute(&self, progress: f32) -> RenderableFlags;
+    /// Execute an animated change, `elapsed` is the time in seconds since the
+    /// animation started. Commands that simulate their own motion can use it
+    /// instead of the progress.
+    fn execute_at(&self, progress: f32, _elapsed: f32) -> RenderableFlags {
+        self.execute(progress)
+    }
     fn value_id(&self) -> usize;
 }
 
@@ -119,6 +134,9 @@ pub struct AnimationState {
 
 static TRANSACTION_CALLBACK_ID: AtomicUsize = AtomicUsize::new(0);
 
+/// Number of past frames whose damage is kept for `Engine::damage_since`
+pub const DAMAGE_HISTORY_SIZE: usize = 8;
+
 fn transaction_callback_id() -> usize {
     TRANSACTION_CALLBACK_ID.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
 }
@@ -356,6 +374,12 @@ pub struct Engine {
 
     /// The damage rect for the current frame
     pub(crate) damage: Arc<RwLock<skia_safe::Rect>>,
+    /// The damaged areas for the current frame, `damage` is their bounds
+    pub(crate) damage_region: Arc<RwLock<skia_safe::Region>>,
+    /// The damage regions of the last frames, the most recent first
+    damage_history: RwLock<VecDeque<skia_safe::Region>>,
+    /// The number of frames completed, incremented by `clear_damage`
+    frame_count: AtomicUsize,
     /// The current pointer position
     pointer_position: RwLock<skia::Point>,
     /// The node that is currently hovered by the pointer
@@ -378,6 +402,25 @@ pub struct Engine {
     cached_depth_groups: RwLock<Vec<(usize, Vec<indextree::NodeId>)>>,
     /// Flag indicating the traversal caches need rebuild (set on tree structure changes).
     traversal_cache_dirty: AtomicBool,
+    /// Incremented on every tree structure change, the occlusion computed
+    /// for an older generation is recomputed from scratch
+    tree_generation: AtomicUsize,
+    /// The occlusion computed for each root, updated incrementally
+    occlusion_caches: RwLock<HashMap<NodeRef, OcclusionCache>>,
+
+    /// The offscreen surfaces of the image-cached layers
+    pub(crate) surface_cache: Arc<SurfaceCache>,
+    /// Whether the engine decides which subtrees are image cached
+    auto_image_caching: AtomicBool,
+    /// The updates not yet recorded by the automatic caching
+    pub(crate) auto_cache_pending_updates: AtomicU32,
+    /// The updates left before a subtree can be promoted, the automatic
+    /// caching is skipped on the updates without changes until then
+    pub(crate) auto_cache_next_promotion: AtomicU32,
+    /// The statistics of the last updates
+    frame_stats: RwLock<FrameStatsHistory>,
+    /// The debug overlays drawn over the scene
+    debug_overlays: AtomicU32,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -565,8 +608,10 @@ impl Engine {
         );
 
         let scene = Scene::create(width, height);
+        let surface_cache = scene.surface_cache.clone();
         let scene_root = RwLock::new(None);
         let damage = Arc::new(RwLock::new(skia_safe::Rect::default()));
+        let damage_region = Arc::new(RwLock::new(skia_safe::Region::new()));
 
         let layers = RwLock::new(HashMap::new());
 
@@ -585,6 +630,9 @@ impl Engine {
             layout_root,
             scene_root,
             damage,
+            damage_region,
+            damage_history: RwLock::new(VecDeque::with_capacity(DAMAGE_HISTORY_SIZE)),
+            frame_count: AtomicUsize::new(0),
             pointer_handlers: FlatStorage::new(),
             pointer_position: RwLock::new(skia::Point::default()),
             current_hover_node: RwLock::new(None),
@@ -593,6 +641,14 @@ impl Engine {
             cached_nodes_post_order: RwLock::new(Vec::new()),
             cached_depth_groups: RwLock::new(Vec::new()),
             traversal_cache_dirty: AtomicBool::new(true),
+            tree_generation: AtomicUsize::new(0),
+            occlusion_caches: RwLock::new(HashMap::new()),
+            surface_cache,
+            auto_image_caching: AtomicBool::new(false),
+            auto_cache_pending_updates: AtomicU32::new(0),
+            auto_cache_next_promotion: AtomicU32::new(0),
+            frame_stats: RwLock::new(FrameStatsHistory::default()),
+            debug_overlays: AtomicU32::new(0),
         }
     }
 
@@ -607,6 +663,11 @@ impl Engine {
 
         new_engine
     }
+    /// The engine with the given id, used by the drawing code to reach the
+    /// engine owning a scene node
+    pub(crate) fn get_by_id(id: usize) -> Option<Arc<Engine>> {
+        ENGINE_REGISTRY.get(id)
+    }
     /// set the layer as the root of the scene and root of the layout tree
     pub fn scene_set_root(&self, layer: impl Into<Layer>) -> NodeRef {
         let layer: Layer = layer.into();
@@ -640,7 +701,8 @@ impl Engine {
         let mut layout_tree = self.layout_tree.write().unwrap();
         let layout_id = layout_tree.new_leaf(Style::default()).unwrap();
 
-        let scene_node = SceneNode::new();
+        let mut scene_node = SceneNode::new();
+        scene_node.engine_id = Some(self.id);
 
         let scene_node_id = self.scene.insert_node(scene_node, None);
 
@@ -884,6 +946,18 @@ impl Engine {
         Ok(())
     }
 
+    /// Detach the layer from its parent in the scene and layout tree, without
+    /// deleting it: the layer and its sublayers can be added again later.
+    pub fn remove_layer_from_parent(&self, layer: NodeRef) {
+        let Some(layer) = self.get_layer(&layer) else {
+            return;
+        };
+        self.layout_detach_layer(&layer);
+        self.scene.detach_node(layer.id);
+        self.invalidate_hit_test_node_list();
+        self.invalidate_traversal_cache();
+    }
+
     pub fn mark_for_delete(&self, layer: NodeRef) {
         self.cleanup_pointer_handlers_for_subtree(layer);
         self.scene.with_arena_mut(|arena| {
@@ -1061,6 +1135,12 @@ impl Engine {
     pub fn now(&self) -> f32 {
         self.timestamp.read().unwrap().0
     }
+    /// Runs `f` with implicit transitions disabled on the current thread:
+    /// setters called without a transition apply their value immediately,
+    /// explicit transitions are still honored. Scopes can be nested.
+    pub fn without_implicit_transitions<R>(&self, f: impl FnOnce() -> R) -> R {
+        command::without_implicit_transitions(f)
+    }
     /// Returns the number of transactions currently scheduled to be executed.
     /// Use this to determine whether another call to `update` is needed.
     pub fn pending_transactions_count(&self) -> usize {
@@ -1128,6 +1208,26 @@ impl Engine {
     ) -> TransactionRef {
         let value_id: usize = change.value_id();
 
+        if let Some(animation_id) = command::recording_animation(self.id) {
+            // inside Engine::transaction: defer the change to the commit,
+            // sharing the transaction animation
+            let transaction_id = self.transactions.reserve_id();
+            command::record_change(
+                self.id,
+                transaction_id,
+                AnimatedNodeChange {
+                    change,
+                    animation_id: Some(animation_id),
+                    node_id: target_id,
+                },
+            );
+            return TransactionRef {
+                id: transaction_id,
+                value_id,
+                engine_id: self.id,
+            };
+        }
+
         let animated_node_change = AnimatedNodeChange {
             change,
             animation_id,
@@ -1149,6 +1249,83 @@ impl Engine {
             engine_id: self.id,
         }
     }
+    /// Records the changes scheduled by the `set_*` calls made inside `f` on
+    /// any layer of this engine, and attaches them to a single animation built
+    /// from `transition`. The changes are committed together: none of them is
+    /// applied before the next `update`. Without a transition they are all
+    /// applied at once on the next update.
+    ///
+    /// Returns the shared animation, also passed to `f`, which can be awaited.
+    /// A transaction started inside `f` joins the outer one: its changes are
+    /// committed with the outer changes and share the outer animation.
+    /// If `f` panics nothing is committed.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let animation = engine.transaction(
+    ///     |_| {
+    ///         layer.set_position((100.0, 0.0), None);
+    ///         other.set_opacity(0.0, None);
+    ///     },
+    ///     Transition::ease_out(0.3),
+    /// );
+    /// animation.await;
+    /// ```
+    pub fn transaction(
+        &self,
+        f: impl FnOnce(AnimationRef),
+        transition: impl Into<Option<Transition>>,
+    ) -> AnimationRef {
+        if let Some(animation) = command::recording_animation(self.id) {
+            f(animation);
+            return animation;
+        }
+        let transition = transition.into().unwrap_or(Transition {
+            delay: 0.0,
+            timing: TimingFunction::linear(0.0),
+        });
+        let animation = self.add_animation_from_transition(&transition, false);
+        // drops the animation if `f` panics, the recorded changes are discarded
+        struct CancelGuard<'a> {
+            engine: &'a Engine,
+            animation: AnimationRef,
+        }
+        impl Drop for CancelGuard<'_> {
+            fn drop(&mut self) {
+                self.engine.cancel_animation(self.animation);
+            }
+        }
+        let guard = CancelGuard {
+            engine: self,
+            animation,
+        };
+        let changes = command::record_transaction(self.id, animation, || f(animation));
+        std::mem::forget(guard);
+        self.commit_changes(changes);
+        self.start_animation(animation, transition.delay);
+        animation
+    }
+    /// Whether the changes scheduled on the current thread are being recorded
+    /// by `Engine::transaction`
+    pub(crate) fn is_recording_transaction(&self) -> bool {
+        command::recording_animation(self.id).is_some()
+    }
+    /// Inserts the recorded changes while holding the transactions lock, so
+    /// that an update never observes only part of them
+    fn commit_changes(&self, changes: Vec<(FlatStorageId, AnimatedNodeChange)>) {
+        self.transactions.with_data_mut(|transactions| {
+            let mut values_transactions = self.values_transactions.write().unwrap();
+            for (transaction_id, change) in changes {
+                let value_id = change.change.value_id();
+                if let Some(existing_transaction) =
+                    values_transactions.insert(value_id, transaction_id)
+                {
+                    transactions.remove(&existing_transaction);
+                }
+                transactions.insert(transaction_id, change);
+            }
+        });
+    }
     pub fn schedule_changes(
         &self,
         animated_changes: &[AnimatedNodeChange],
@@ -1210,12 +1387,18 @@ impl Engine {
             *timestamp = t.clone();
             t
         };
+        let mut stats = FrameStats::default();
 
         // 1.1 Update animations to the current timestamp
+        let stage_start = Instant::now();
         let (started_animations, finished_animations) = update_animations(self, &timestamp);
+        stats.timings.animations = stage_start.elapsed();
 
         // 1.2 Execute transactions using the updated animations
+        let stage_start = Instant::now();
         let (updated_nodes, finished_transitions, _needs_redraw) = execute_transactions(self);
+        stats.timings.transactions = stage_start.elapsed();
+        stats.nodes_updated = updated_nodes.len();
 
         let needs_draw = !updated_nodes.is_empty();
 
@@ -1229,37 +1412,70 @@ impl Engine {
             && started_animations.is_empty()
             && finished_animations.is_empty()
         {
+            let stage_start = Instant::now();
             let removed_damage = cleanup_nodes(self);
+            stats.timings.cleanup = stage_start.elapsed();
+            let stage_start = Instant::now();
+            update_auto_caching(self, false);
+            stats.timings.nodes = stage_start.elapsed();
             if !removed_damage.is_empty() {
-                let mut current_damage = self.damage.write().unwrap();
-                current_damage.join(removed_damage);
+                stats.damage_area = FrameStats::region_area(&skia_safe::Region::from_rect(
+                    occlusion::rect_to_irect(removed_damage),
+                ));
+                self.record_frame_stats(stats);
+                self.add_damage(removed_damage);
                 return true;
             }
+            self.record_frame_stats(stats);
             return false;
         }
 
         // 2.0 cleanup nodes marked for removal before layout/render
         // so traversal caches and arena access don't hit freed nodes.
+        let stage_start = Instant::now();
         let removed_damage = cleanup_nodes(self);
+        stats.timings.cleanup = stage_start.elapsed();
 
         // 3.0 update the layout tree using taffy
+        let stage_start = Instant::now();
         update_layout_tree(self);
+        stats.timings.layout = stage_start.elapsed();
 
         // 4.0 update render nodes and trigger repaint
-
-        let mut damage = self.update_nodes();
+        let stage_start = Instant::now();
+        let (mut damage, mut damage_region, repainted) = self.update_nodes_damage();
         damage.join(removed_damage);
+        if !removed_damage.is_empty() {
+            damage_region.op_rect(
+                occlusion::rect_to_irect(removed_damage),
+                skia_safe::region::RegionOp::Union,
+            );
+        }
+
+        // 4.1 promote or demote the automatically image-cached subtrees
+        update_auto_caching(self, true);
+        stats.timings.nodes = stage_start.elapsed();
+        stats.nodes_repainted = repainted;
+        stats.damage_area = FrameStats::region_area(&damage_region);
 
         // 5.0 trigger the callbacks for the listeners on the transitions
+        let stage_start = Instant::now();
         trigger_callbacks(self, &started_animations);
+        stats.timings.callbacks = stage_start.elapsed();
 
         // 6.0 cleanup the animations marked as done and
         // transactions already executed
+        let stage_start = Instant::now();
         cleanup_animations(self, finished_animations);
         cleanup_transactions(self, finished_transitions);
+        stats.timings.cleanup += stage_start.elapsed();
+        self.record_frame_stats(stats);
 
-        let mut current_damage = self.damage.write().unwrap();
-        current_damage.join(damage);
+        self.damage.write().unwrap().join(damage);
+        self.damage_region
+            .write()
+            .unwrap()
+            .op_region(&damage_region, skia_safe::region::RegionOp::Union);
 
         #[cfg(feature = "debugger")]
         {
@@ -1268,13 +1484,21 @@ impl Engine {
 
         needs_draw || !damage.is_empty()
     }
-    #[profiling::function]
+    /// Update the render nodes and returns the bounds of the damage
     pub fn update_nodes(&self) -> skia_safe::Rect {
+        self.update_nodes_damage().0
+    }
+    /// Update the render nodes, returning the bounds of the damage, the
+    /// damaged areas and the number of repainted nodes
+    #[profiling::function]
+    pub(crate) fn update_nodes_damage(&self) -> (skia_safe::Rect, skia_safe::Region, usize) {
         let layout = self.layout_tree.read().unwrap();
         let mut total_damage = skia_safe::Rect::default();
+        let mut damage_region = skia_safe::Region::new();
+        let mut repainted = 0;
         let node = self.scene_root.read().unwrap();
         let Some(root_id) = *node else {
-            return total_damage;
+            return (total_damage, damage_region, repainted);
         };
 
         // Rebuild traversal caches only when tree structure changed
@@ -1296,6 +1520,7 @@ impl Engine {
         // Parents are processed before children so cumulative transforms are correct.
         let mut parents_changed: std::collections::HashSet<indextree::NodeId> =
             std::collections::HashSet::new();
+        let mut changed_nodes: Vec<NodeRef> = Vec::new();
         for (_depth, nodes_at_depth) in depth_groups.iter() {
             let results: Vec<_> = nodes_at_depth
                 .iter()
@@ -1318,7 +1543,8 @@ impl Engine {
                         parent_render_layer.as_ref(),
                         parent_changed,
                     );
-                    if !result.damage.is_empty() {
+                    // moving a cached ancestor doesn't change its cached image
+                    if !result.damage.is_empty() && !result.moved_with_parent {
                         self.mark_image_cached_ancestors_for_repaint(*node_id);
                     }
 
@@ -1328,11 +1554,21 @@ impl Engine {
 
             // Phase 4: Accumulate child damages to parents (sequential for each depth)
             for (node_id, result) in results.iter() {
+                if result.repainted {
+                    repainted += 1;
+                }
+                if result.changed {
+                    changed_nodes.push(NodeRef(*node_id));
+                }
                 if result.propagate_to_children {
                     parents_changed.insert(*node_id);
                 }
                 if !result.damage.is_empty() {
                     per_node_damage.insert(NodeRef(*node_id), result.damage);
+                    damage_region.op_rect(
+                        occlusion::rect_to_irect(result.damage),
+                        skia_safe::region::RegionOp::Union,
+                    );
                 }
                 total_damage.join(result.damage);
             }
@@ -1341,14 +1577,35 @@ impl Engine {
         // Phase 4.5: Fold occlusion into the accumulated damage. For the
         // scene root, walk front-to-back and subtract the global bounds
         // of any opaque layer from damage contributions of layers behind
-        // it. Replaces `total_damage` with the clipped region's bounding
-        // rect — same type/shape, smaller value when occlusion applies.
+        // it. Replaces the damage region with the clipped one and
+        // `total_damage` with its bounding rect. The draw order of the
+        // root occlusion cache is updated from the changed nodes and reused.
+        let mut occlusion_caches = self.occlusion_caches.write().unwrap();
         if !per_node_damage.is_empty() {
-            let occluded_damage = self.scene.with_arena(|arena| {
-                occlusion::compute_occlusion_aware_damage(root_id, arena, &per_node_damage)
+            let tree_generation = self.tree_generation.load(Ordering::Relaxed);
+            let cache = occlusion_caches.entry(root_id).or_default();
+            cache.add_changed_nodes(changed_nodes.iter().copied());
+            damage_region = self.scene.with_arena(|arena| {
+                cache.update(root_id, arena, tree_generation);
+                cache.occlusion_aware_damage(arena, &per_node_damage)
             });
-            total_damage = occluded_damage;
+            let bounds = damage_region.bounds();
+            total_damage = if bounds.is_empty() {
+                skia_safe::Rect::default()
+            } else {
+                skia_safe::Rect::from_irect(bounds)
+            };
+        }
+
+        // Phase 4.6: Record the changed nodes, the occlusion computed for
+        // the roots is updated from them, once the bounds of their
+        // ancestors include them.
+        if !changed_nodes.is_empty() {
+            for cache in occlusion_caches.values_mut() {
+                cache.add_changed_nodes(changed_nodes.iter().copied());
+            }
         }
+        drop(occlusion_caches);
 
         // Phase 5: Bubble up bounds from children to parents
         for node_id in nodes_post_order.iter() {
@@ -1376,8 +1633,12 @@ impl Engine {
                     if let Some(backdrop_rrects) =
                         &root_node.get().render_layer.backdrop_blur_region
                     {
-                        for rrect in backdrop_rrects {
+                        for (rrect, _) in backdrop_rrects {
                             total_damage.join(rrect.rect());
+                            damage_region.op_rect(
+                                occlusion::rect_to_irect(*rrect.rect()),
+                                skia_safe::region::RegionOp::Union,
+                            );
                         }
                     }
                 }
@@ -1389,7 +1650,7 @@ impl Engine {
             self.rebuild_hit_test_node_list(root_id.0);
         }
 
-        total_damage
+        (total_damage, damage_region, repainted)
     }
 
     /// Rebuild the hit test node list by traversing from root,
@@ -1429,8 +1690,11 @@ impl Engine {
             result.push(node_id);
         }
 
-        // Recurse into children
+        // Recurse into children, the mask layer is not hit-testable
         for child_id in node_id.children(arena) {
+            if scene_node.render_layer.mask == Some(NodeRef(child_id)) {
+                continue;
+            }
             Self::collect_hit_test_nodes(child_id, arena, result);
         }
     }
@@ -1533,12 +1797,13 @@ impl Engine {
     /// Bubble up backdrop blur regions from children to parents.
     /// Transforms child's backdrop blur regions (including its own if it has BackgroundBlur)
     /// into parent's coordinate space and merges them into parent's backdrop_blur_region.
+    /// Each rrect keeps the backdrop effect of the layer it comes from.
     /// Uses Vec<RRect> for thread safety, converted to Path during rendering.
     /// Skips hidden nodes and their subtrees.
     fn bubble_up_backdrop_blur_regions(&self, node_id: indextree::NodeId) {
         self.scene.with_arena_mut(|arena| {
             // Collect child's backdrop blur rrects, blend mode, and rounded bounds
-            let (child_blend_mode, child_rbounds, child_rrects, child_transform) = {
+            let (child_blend_mode, child_effect, child_rbounds, child_rrects, child_transform) = {
                 let Some(child_node) = arena.get(node_id) else {
                     return;
                 };
@@ -1558,6 +1823,7 @@ impl Engine {
 
                 (
                     child.render_layer.blend_mode,
+                    child.render_layer.backdrop_effect,
                     child.render_layer.rbounds,
                     child.render_layer.backdrop_blur_region.clone(),
                     child.render_layer.local_transform.to_m33(),
@@ -1591,12 +1857,12 @@ impl Engine {
                     ];
                     let transformed_rrect =
                         skia_safe::RRect::new_rect_radii(transformed_rect, &radii);
-                    parent_rrects.push(transformed_rrect);
+                    parent_rrects.push((transformed_rrect, child_effect));
                 }
 
                 // Merge child's backdrop rrects (from its descendants) transformed to parent space
                 if let Some(child_rrects) = child_rrects {
-                    for rrect in child_rrects {
+                    for (rrect, effect) in child_rrects {
                         let (transformed_rect, _) = child_transform.map_rect(rrect.rect());
                         let radii = [
                             rrect.radii(skia_safe::rrect::Corner::UpperLeft),
@@ -1606,7 +1872,7 @@ impl Engine {
                         ];
                         let transformed =
                             skia_safe::RRect::new_rect_radii(transformed_rect, &radii);
-                        parent_rrects.push(transformed);
+                        parent_rrects.push((transformed, effect));
                     }
                 }
             }
@@ -1615,10 +1881,14 @@ impl Engine {
 
     fn mark_image_cached_ancestors_for_repaint(&self, node_id: indextree::NodeId) {
         self.scene.with_arena_mut(|arena| {
+            if let Some(node) = arena.get_mut(node_id) {
+                node.get_mut().auto_cache.changed = true;
+            }
             let ancestor_ids: Vec<_> = node_id.ancestors(arena).skip(1).collect();
             for ancestor_id in ancestor_ids {
                 if let Some(ancestor_node) = arena.get_mut(ancestor_id) {
                     let ancestor = ancestor_node.get_mut();
+                    ancestor.auto_cache.changed = true;
                     ancestor.set_needs_repaint(true);
                     if ancestor.is_image_cached() {
                         ancestor.increase_frame();
@@ -2105,7 +2375,16 @@ impl Engine {
 
             // Try-lock to avoid blocking while a writer holds (or waits on) the arenas.
             if let (Ok(nodes), Ok(renderables)) = (nodes.try_read(), renderables.try_read()) {
-                render_node_tree(layer_id, &nodes, &renderables, c, 1.0, None, None);
+                render_node_tree_with_cache(
+                    layer_id,
+                    &nodes,
+                    &renderables,
+                    c,
+                    1.0,
+                    None,
+                    None,
+                    Some(scene.surface_cache()),
+                );
             }
             skia::Rect::from_xywh(0.0, 0.0, w, h)
         };
@@ -2114,9 +2393,133 @@ impl Engine {
     pub fn damage(&self) -> skia_safe::Rect {
         *self.damage.read().unwrap()
     }
+    /// The damaged areas accumulated since the last `clear_damage`.
+    /// Unlike `damage()`, distant changes don't damage the area between them.
+    pub fn damage_region(&self) -> skia_safe::Region {
+        self.damage_region.read().unwrap().clone()
+    }
+    /// Statistics of the offscreen surfaces of the image-cached layers
+    pub fn surface_cache_stats(&self) -> SurfaceCacheStats {
+        self.surface_cache.stats()
+    }
+    /// Sets the memory budget, in bytes, of the image-cached layers surfaces.
+    /// The least recently used surfaces are evicted when the budget is exceeded,
+    /// they are released on the rendering thread when the scene is next drawn.
+    pub fn set_surface_cache_budget(&self, bytes: usize) {
+        self.surface_cache.set_budget(bytes);
+    }
+    /// Sets the debug overlays drawn over the scene by `draw_scene`: the damaged
+    /// areas, the occluded layers, the cached layers and the layout bounds.
+    pub fn set_debug_overlays(&self, overlays: DebugOverlays) {
+        self.debug_overlays
+            .store(overlays.bits(), Ordering::Relaxed);
+    }
+    /// The debug overlays drawn over the scene
+    pub fn debug_overlays(&self) -> DebugOverlays {
+        DebugOverlays::from_bits_truncate(self.debug_overlays.load(Ordering::Relaxed))
+    }
+    /// The statistics of the most recent update: the time spent in each stage,
+    /// the nodes updated and repainted, the damaged area, and the image-cache
+    /// lookups and occluded nodes of the drawing that followed it.
+    pub fn frame_stats(&self) -> FrameStats {
+        self.frame_stats
+            .read()
+            .unwrap()
+            .last(&self.surface_cache.stats())
+    }
+    /// The statistics of the last `FRAME_STATS_HISTORY_SIZE` updates, the oldest first
+    pub fn frame_stats_history(&self) -> Vec<FrameStats> {
+        self.frame_stats
+            .read()
+            .unwrap()
+            .history(&self.surface_cache.stats())
+    }
+    fn record_frame_stats(&self, stats: FrameStats) {
+        self.frame_stats
+            .write()
+            .unwrap()
+            .push(stats, self.surface_cache.stats());
+    }
+    /// Enables the automatic image caching: the engine tracks how often each
+    /// subtree changes and caches the stable subtrees drawing several layers,
+    /// while subtrees that change frequently are drawn directly.
+    /// Layers explicitly set as image cached are always cached.
+    pub fn set_auto_image_caching(&self, enabled: bool) {
+        self.auto_image_caching.store(enabled, Ordering::Relaxed);
+        self.auto_cache_pending_updates.store(0, Ordering::Relaxed);
+        self.auto_cache_next_promotion.store(0, Ordering::Relaxed);
+        if enabled {
+            return;
+        }
+        let promoted = self.scene.with_arena_mut(|arena| {
+            let node_ids: Vec<_> = arena
+                .iter()
+                .filter(|node| !node.is_removed())
+                .filter_map(|node| arena.get_node_id(node))
+                .collect();
+            let mut promoted = Vec::new();
+            for node_id in node_ids {
+                let scene_node = arena[node_id].get_mut();
+                if scene_node.auto_cache.is_promoted() {
+                    promoted.push(NodeRef(node_id));
+                }
+                scene_node.auto_cache.reset();
+            }
+            promoted
+        });
+        for node_ref in promoted {
+            self.surface_cache.remove(&node_ref);
+        }
+    }
+    /// Whether the automatic image caching is enabled
+    pub fn auto_image_caching(&self) -> bool {
+        self.auto_image_caching.load(Ordering::Relaxed)
+    }
+    /// Drops the cached surfaces of the image-cached layers, for example on
+    /// memory pressure. They are released on the rendering thread, and the
+    /// layers redrawn, when the scene is next drawn.
+    pub fn purge_caches(&self) {
+        self.surface_cache.purge();
+    }
+    /// Ends the current frame: the damage is moved to the damage history
+    /// and the frame counter is incremented.
     pub fn clear_damage(&self) {
         let mut damage = self.damage.write().unwrap();
         *damage = skia_safe::Rect::default();
+        let mut damage_region = self.damage_region.write().unwrap();
+        let frame_damage = std::mem::replace(&mut *damage_region, skia_safe::Region::new());
+
+        let mut history = self.damage_history.write().unwrap();
+        if history.len() == DAMAGE_HISTORY_SIZE {
+            history.pop_back();
+        }
+        history.push_front(frame_damage);
+        self.frame_count.fetch_add(1, Ordering::Relaxed);
+    }
+    /// The number of frames completed, each call to `clear_damage` ends a frame
+    pub fn frame_count(&self) -> usize {
+        self.frame_count.load(Ordering::Relaxed)
+    }
+    /// The area to repaint in a buffer last drawn `frames_ago` frames ago
+    /// (the EGL/Vulkan buffer age): the current damage together with the damage
+    /// of the frames drawn since then.
+    ///
+    /// Returns None when the whole buffer must be repainted: the buffer age is
+    /// unknown (0) or older than the damage history.
+    pub fn damage_since(&self, frames_ago: usize) -> Option<skia_safe::Region> {
+        if frames_ago == 0 {
+            return None;
+        }
+        let mut region = self.damage_region();
+        let history = self.damage_history.read().unwrap();
+        let past_frames = frames_ago - 1;
+        if past_frames > history.len() {
+            return None;
+        }
+        for frame_damage in history.iter().take(past_frames) {
+            region.op_region(frame_damage, skia_safe::region::RegionOp::Union);
+        }
+        Some(region)
     }
 
     /// Compute occlusion culling for the given root node.
@@ -2125,24 +2528,60 @@ impl Engine {
     /// hidden behind opaque layers. Results are stored on the `Scene` and
     /// used by `render_node_tree` to skip occluded nodes.
     ///
+    /// The occlusion computed for the root is kept and updated from the nodes
+    /// changed by the following updates: only a change to an occluder, to a
+    /// clip or to the tree structure traverses the whole subtree again.
+    ///
     /// Call this after `update()` for each root node you intend to draw.
     pub fn compute_occlusion(&self, root: NodeRef) {
-        let occluded = self
-            .scene
-            .with_arena(|arena| occlusion::compute_occlusion(root, arena));
+        let tree_generation = self.tree_generation.load(Ordering::Relaxed);
+        let occluded = {
+            let mut caches = self.occlusion_caches.write().unwrap();
+            let cache = caches.entry(root).or_default();
+            // the set is shared with the scene
+            self.scene
+                .with_arena(|arena| cache.update(root, arena, tree_generation).clone())
+        };
+        self.frame_stats
+            .write()
+            .unwrap()
+            .set_occluded_nodes(occluded.len());
         self.scene.add_occlusion(root, occluded);
     }
 
+    /// The number of times the occlusion of `root` was computed from scratch,
+    /// by `compute_occlusion` or by the damage of the updates; the other
+    /// times it is updated from the changed nodes.
+    pub fn occlusion_rebuilds(&self, root: NodeRef) -> usize {
+        self.occlusion_caches
+            .read()
+            .unwrap()
+            .get(&root)
+            .map_or(0, |cache| cache.rebuilds())
+    }
+
+    /// Forget the occlusion computed for a removed root
+    pub(crate) fn remove_occlusion_cache(&self, root: NodeRef) {
+        self.occlusion_caches.write().unwrap().remove(&root);
+    }
+
     /// Clear all cached occlusion data.
     ///
     /// Call this before recomputing occlusion for a new frame to avoid
-    /// stale entries from previous roots.
+    /// stale entries from previous roots. The occlusion computed for the roots
+    /// is still updated incrementally when they are computed again.
     pub fn clear_occlusion(&self) {
         self.scene.clear_occlusion();
     }
     pub fn add_damage(&self, rect: skia_safe::Rect) {
         let mut damage = self.damage.write().unwrap();
         damage.join(rect);
+        if !rect.is_empty() {
+            self.damage_region.write().unwrap().op_rect(
+                occlusion::rect_to_irect(rect),
+                skia_safe::region::RegionOp::Union,
+            );
+        }
     }
 
     /// Mark the hit test node list as dirty, requiring rebuild on next update.
@@ -2155,6 +2594,7 @@ impl Engine {
     /// `update_nodes()`.  Called when the tree structure changes (add/remove/reparent).
     pub fn invalidate_traversal_cache(&self) {
         self.traversal_cache_dirty.store(true, Ordering::Relaxed);
+        self.tree_generation.fetch_add(1, Ordering::Relaxed);
     }
 
     /// Rebuild the cached post-order and depth-grouped traversal lists from the
@@ -0,0 +1,167 @@
+use serde::Serialize;
+
+/// The number of consecutive updates without changes in a subtree before it
+/// is promoted to an image cache
+pub(crate) const AUTO_CACHE_PROMOTE_FRAMES: u32 = 16;
+/// The number of changes in the last 16 updates that demote an automatically
+/// cached subtree
+pub(crate) const AUTO_CACHE_DEMOTE_REPAINTS: u32 = 4;
+/// The minimum number of drawn layers in a subtree for it to be worth caching
+pub(crate) const AUTO_CACHE_MIN_COST: usize = 4;
+/// The cached pixels each drawn layer of a subtree is worth: sparse subtrees
+/// covering a large area are not cached
+pub(crate) const AUTO_CACHE_PIXELS_PER_LAYER: f32 = 512.0 * 512.0;
+/// The automatically cached surfaces use at most this fraction of the surface
+/// cache budget
+pub(crate) const AUTO_CACHE_BUDGET_FRACTION: usize = 2;
+
+/// Whether caching a subtree drawing `cost` layers into a surface of `pixels`
+/// is worth it, with `available_bytes` of the budget left to the automatic caching
+pub(crate) fn worth_caching(cost: usize, pixels: f32, available_bytes: usize) -> bool {
+    cost >= AUTO_CACHE_MIN_COST
+        && cost as f32 * AUTO_CACHE_PIXELS_PER_LAYER >= pixels
+        && (pixels * 4.0) as usize <= available_bytes
+}
+
+/// The last decision of the automatic caching for a node
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum AutoCacheDecision {
+    /// The subtree is stable and expensive, it is drawn from an image cache
+    Promoted,
+    /// The subtree changes too often, it is drawn directly
+    Demoted,
+}
+
+/// Tracks how often the subtree of a node changes, to decide if it should
+/// be image cached when the engine automatic caching is enabled.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct AutoCache {
+    /// One bit per engine update, the most recent in the lowest bit,
+    /// set when the subtree of the node was damaged
+    history: u32,
+    /// The number of updates recorded, up to the history length
+    updates: u32,
+    /// The subtree was damaged during the current update
+    pub(crate) changed: bool,
+    pub(crate) decision: Option<AutoCacheDecision>,
+}
+
+impl AutoCache {
+    pub(crate) fn is_promoted(&self) -> bool {
+        self.decision == Some(AutoCacheDecision::Promoted)
+    }
+    /// Ends an update, recording if the subtree changed during it
+    pub(crate) fn record_update(&mut self) {
+        self.record_updates(1);
+    }
+    /// Ends `count` updates at once, the subtree could only change during
+    /// the last one
+    pub(crate) fn record_updates(&mut self, count: u32) {
+        if count == 0 {
+            return;
+        }
+        self.history = self.history.checked_shl(count).unwrap_or(0) | self.changed as u32;
+        self.updates = self.updates.saturating_add(count).min(u32::BITS);
+        self.changed = false;
+    }
+    /// The number of updates left before the subtree can be promoted
+    pub(crate) fn updates_before_promotion(&self) -> u32 {
+        AUTO_CACHE_PROMOTE_FRAMES.saturating_sub(self.stable_updates())
+    }
+    /// The number of updates since the subtree last changed
+    pub(crate) fn stable_updates(&self) -> u32 {
+        self.history.trailing_zeros().min(self.updates)
+    }
+    /// The number of changes in the last 16 updates
+    pub(crate) fn recent_changes(&self) -> u32 {
+        (self.history & 0xffff).count_ones()
+    }
+    /// Updates the decision given whether the subtree is worth caching, see
+    /// [`worth_caching`], and whether an ancestor is already cached.
+    /// Returns true if the decision changed.
+    pub(crate) fn decide(&mut self, worth_caching: bool, ancestor_cached: bool) -> bool {
+        let decision = if self.is_promoted() {
+            if ancestor_cached || !worth_caching {
+                None
+            } else if self.recent_changes() >= AUTO_CACHE_DEMOTE_REPAINTS {
+                Some(AutoCacheDecision::Demoted)
+            } else {
+                self.decision
+            }
+        } else if !ancestor_cached
+            && worth_caching
+            && self.stable_updates() >= AUTO_CACHE_PROMOTE_FRAMES
+        {
+            Some(AutoCacheDecision::Promoted)
+        } else {
+            self.decision
+        };
+        let changed = decision != self.decision;
+        self.decision = decision;
+        changed
+    }
+    /// Forgets the decision, used when the automatic caching is disabled
+    pub(crate) fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stable_expensive_subtrees_are_promoted() {
+        let mut auto_cache = AutoCache::default();
+        for _ in 0..AUTO_CACHE_PROMOTE_FRAMES - 1 {
+            auto_cache.record_update();
+            assert!(!auto_cache.decide(true, false));
+        }
+        auto_cache.record_update();
+        // a subtree not worth caching is not promoted
+        assert!(!auto_cache.decide(false, false));
+        // nor a subtree already in a cache
+        assert!(!auto_cache.decide(true, true));
+        assert!(auto_cache.decide(true, false));
+        assert!(auto_cache.is_promoted());
+    }
+
+    #[test]
+    fn frequently_changing_subtrees_are_demoted() {
+        let mut auto_cache = AutoCache::default();
+        auto_cache.decision = Some(AutoCacheDecision::Promoted);
+        for _ in 0..AUTO_CACHE_DEMOTE_REPAINTS - 1 {
+            auto_cache.changed = true;
+            auto_cache.record_update();
+            auto_cache.record_update();
+            assert!(!auto_cache.decide(true, false));
+        }
+        auto_cache.changed = true;
+        auto_cache.record_update();
+        assert!(auto_cache.decide(true, false));
+        assert_eq!(auto_cache.decision, Some(AutoCacheDecision::Demoted));
+
+        // the subtree is promoted again once it is stable
+        assert_eq!(
+            auto_cache.updates_before_promotion(),
+            AUTO_CACHE_PROMOTE_FRAMES
+        );
+        auto_cache.record_updates(AUTO_CACHE_PROMOTE_FRAMES);
+        assert!(auto_cache.decide(true, false));
+        assert!(auto_cache.is_promoted());
+    }
+
+    #[test]
+    fn large_sparse_subtrees_are_not_worth_caching() {
+        let budget = 256 * 1024 * 1024;
+        let pixels = 400.0 * 400.0;
+        assert!(worth_caching(AUTO_CACHE_MIN_COST, pixels, budget));
+        // a cheap subtree
+        assert!(!worth_caching(AUTO_CACHE_MIN_COST - 1, pixels, budget));
+        // a few layers covering a large area
+        assert!(!worth_caching(AUTO_CACHE_MIN_COST, 3000.0 * 3000.0, budget));
+        assert!(worth_caching(40, 3000.0 * 3000.0, budget));
+        // a surface that doesn't fit in the budget left
+        assert!(!worth_caching(AUTO_CACHE_MIN_COST, pixels, 400 * 400));
+    }
+}
@@ -17,8 +17,11 @@ use crate::{
 
 use super::{draw_to_picture::DrawDebugInfo, NodeRef};
 
+pub(crate) mod auto_cache;
 pub(crate) mod contains_point;
 
+pub use auto_cache::AutoCacheDecision;
+pub(crate) use auto_cache::{worth_caching, AutoCache, AUTO_CACHE_BUDGET_FRACTION};
 pub use contains_point::ContainsPoint;
 
 /// SceneNode is the main data structure for the engine. It contains a model
@@ -88,12 +91,20 @@ pub struct SceneNode {
     pub(crate) following: Option<NodeRef>,
     pub(crate) _debug_info: Option<DrawDebugInfo>,
     pub(crate) frame_number: usize,
+    /// Incremented with `frame_number` when the drawing of the node, in layer
+    /// coordinates, changes; not when the node is only moved. The cached
+    /// images of the node are drawn at a content frame.
+    pub(crate) content_frame: usize,
     /// Externally reported content damage, in layer-local coordinates.
     /// Populated by `Layer::add_damage` / `Layer::set_damage` for content
     /// whose damage source is outside the draw closure (e.g. Wayland
     /// surface buffer damage). Consumed and cleared by `do_repaint`,
     /// where it is unioned with the closure's returned rect.
     pub(crate) pending_damage: Option<skia_safe::Rect>,
+    /// The engine owning the node, where its cached surface is stored
+    pub(crate) engine_id: Option<usize>,
+    /// The state of the automatic image caching of the node
+    pub(crate) auto_cache: AutoCache,
 }
 
 impl Default for SceneNode {
@@ -107,9 +118,12 @@ impl Default for SceneNode {
             is_deleted: false,
             _debug_info: None,
             frame_number: 0,
+            content_frame: 0,
             followers: HashSet::new(),
             following: None,
             pending_damage: None,
+            engine_id: None,
+            auto_cache: AutoCache::default(),
         }
     }
 }
@@ -186,8 +200,13 @@ impl SceneNode {
     pub fn set_image_cached(&mut self, value: bool) {
         self.image_cached = value;
     }
+    /// True if the node is image cached, explicitly or by the engine automatic caching
     pub fn is_image_cached(&self) -> bool {
-        self.image_cached
+        self.image_cached || self.auto_cache.is_promoted()
+    }
+    /// The last decision of the engine automatic caching for the node
+    pub fn auto_cache_decision(&self) -> Option<AutoCacheDecision> {
+        self.auto_cache.decision
     }
     pub fn set_picture_cached(&mut self, value: bool) {
         self.picture_cached = value;
@@ -220,6 +239,14 @@ impl SceneNode {
             self.frame_number = 1;
         }
         // }
+        self.content_frame = self.frame_number;
+    }
+    /// Increases the frame number of a node that was only moved, its cached
+    /// images are still up to date
+    pub(crate) fn increase_frame_moved(&mut self) {
+        let content_frame = self.content_frame;
+        self.increase_frame();
+        self.content_frame = content_frame;
     }
     /// update the renderlayer based on model and layout
     #[profiling::function]
@@ -228,6 +255,7 @@ impl SceneNode {
         layout: &Layout,
         model: Arc<ModelLayer>,
         matrix: Option<&M44>,
+        sublayer_transform: Option<&M44>,
         context_opacity: f32,
         local_children_bounds: skia_safe::Rect,
         force_update: bool,
@@ -249,8 +277,13 @@ impl SceneNode {
             || self.rendering_flags.contains(RenderableFlags::NEEDS_LAYOUT)
             || self.rendering_flags.contains(RenderableFlags::NEEDS_PAINT)
         {
-            self.render_layer
-                .update_with_model_and_layout(&model, layout, matrix, context_opacity);
+            self.render_layer.update_with_model_and_layout(
+                &model,
+                layout,
+                matrix,
+                sublayer_transform,
+                context_opacity,
+            );
             // bounds_with_children: union in this node's local space
             self.render_layer.bounds_with_children = self.render_layer.bounds;
             self.render_layer
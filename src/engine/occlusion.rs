@@ -10,10 +10,24 @@
 //!
 //! Clip-awareness: when a parent has `clip_children = true`, child bounds are
 //! intersected with the parent clip. Children fully outside the clip are marked
-//! occluded. Opaque layers only contribute the intersection of their bounds
-//! with the active clip to the occlusion mask.
+//! occluded. Opaque layers only contribute the intersection of their opaque
+//! bounds with the active clip to the occlusion mask.
+//!
+//! The opaque bounds of a layer are a conservative rect inside its shape:
+//! the inner rect of rounded corners and of convex custom shapes, mapped
+//! through transforms that keep the layer axis-aligned (scales and rotations
+//! by multiples of 90 degrees). Layers with other transforms never occlude.
+//!
+//! The engine keeps the occlusion computed for each root in an
+//! `OcclusionCache` and updates it from the nodes changed by the following
+//! updates: the changes that can't affect the other nodes, like a layer that
+//! is not an occluder moving or a layer repainting in place, only update the
+//! changed nodes. The changes to the occluders, to the clips and to the tree
+//! structure recompute the occlusion of the whole root. The damage of the
+//! updates is folded with the draw order kept by the cache of the scene root.
 
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 use indextree::Arena;
 use skia_safe::Contains;
@@ -22,14 +36,15 @@ use crate::engine::node::SceneNode;
 use crate::engine::storage::TreeStorageId;
 use crate::engine::NodeRef;
 
-/// Per-root occlusion data: the set of node ids that are fully occluded.
-pub type OcclusionMap = HashMap<NodeRef, HashSet<NodeRef>>;
+/// Per-root occlusion data: the set of node ids that are fully occluded,
+/// shared with the engine occlusion caches.
+pub type OcclusionMap = HashMap<NodeRef, Arc<HashSet<NodeRef>>>;
 
 /// Compute the set of occluded nodes for a given root.
 ///
 /// The algorithm flattens the subtree rooted at `root` into draw order
 /// (back-to-front via pre-order traversal), then iterates **front-to-back**
-/// (reversed). Fully-opaque rectangular layers contribute their global bounds
+/// (reversed). Opaque layers contribute their global opaque bounds
 /// (clipped to any ancestor clip region) to an occlusion list. Any subsequent
 /// (i.e. behind) node whose visible bounds are fully contained by an opaque
 /// rect is marked occluded.
@@ -38,22 +53,113 @@ pub type OcclusionMap = HashMap<NodeRef, HashSet<NodeRef>>;
 /// region are also marked occluded since they produce no visible pixels.
 #[profiling::function]
 pub fn compute_occlusion(root: NodeRef, arena: &Arena<SceneNode>) -> HashSet<NodeRef> {
-    let root_id: TreeStorageId = root.into();
+    let mut cache = OcclusionCache::default();
+    cache.rebuild(root, arena);
+    Arc::unwrap_or_clone(cache.occluded)
+}
 
-    // Collect nodes in draw order (pre-order = back-to-front),
-    // along with context opacity and the active clip rect.
-    let mut draw_order: Vec<NodeOcclusionInfo> = Vec::new();
-    collect_draw_order(root_id, arena, 1.0, None, &mut draw_order);
+/// The occlusion computed for a root, kept by the engine to update it
+/// incrementally from the nodes changed since it was computed.
+#[derive(Default)]
+pub(crate) struct OcclusionCache {
+    /// The nodes of the root in draw order
+    draw_order: Vec<NodeOcclusionInfo>,
+    /// The index of the nodes in the draw order
+    indices: HashMap<NodeRef, usize>,
+    /// The opaque bounds of the occluders, with their index in the draw order.
+    /// The occluders hidden by other occluders are left out.
+    occluders: Vec<(usize, skia_safe::Rect)>,
+    /// Shared with the scene, copied on write when a change updates it
+    occluded: Arc<HashSet<NodeRef>>,
+    /// The nodes changed by the updates since the occlusion was computed
+    changed_nodes: HashSet<NodeRef>,
+    /// The generation of the tree structure the occlusion was computed for,
+    /// `None` before it is computed
+    tree_generation: Option<usize>,
+    /// The number of times the occlusion was computed from scratch
+    rebuilds: usize,
+}
 
-    // Front-to-back iteration (reverse of draw order).
-    let mut opaque_rects: Vec<skia_safe::Rect> = Vec::new();
-    let mut occluded = HashSet::new();
+impl OcclusionCache {
+    pub(crate) fn add_changed_nodes(&mut self, nodes: impl IntoIterator<Item = NodeRef>) {
+        self.changed_nodes.extend(nodes);
+    }
 
-    for info in draw_order.iter().rev() {
+    /// Brings the occlusion of `root` up to date with the changed nodes,
+    /// recomputing it when the tree structure changed, and returns the
+    /// occluded nodes.
+    pub(crate) fn update(
+        &mut self,
+        root: NodeRef,
+        arena: &Arena<SceneNode>,
+        tree_generation: usize,
+    ) -> &Arc<HashSet<NodeRef>> {
+        let up_to_date =
+            self.tree_generation == Some(tree_generation) && self.update_changed_nodes(root, arena);
+        if !up_to_date {
+            self.rebuild(root, arena);
+            self.rebuilds += 1;
+            self.tree_generation = Some(tree_generation);
+        }
+        self.changed_nodes.clear();
+        &self.occluded
+    }
+
+    /// The number of times the occlusion was computed from scratch instead
+    /// of being updated from the changed nodes
+    pub(crate) fn rebuilds(&self) -> usize {
+        self.rebuilds
+    }
+
+    /// Folds the occlusion into the per-node damage, see
+    /// [`compute_occlusion_aware_damage_region`], with the draw order of the last
+    /// update.
+    pub(crate) fn occlusion_aware_damage(
+        &self,
+        arena: &Arena<SceneNode>,
+        per_node_damage: &HashMap<NodeRef, skia_safe::Rect>,
+    ) -> skia_safe::Region {
+        fold_occlusion_into_damage(&self.draw_order, arena, per_node_damage)
+    }
+
+    /// Computes the occlusion of all the nodes of `root`, see [`compute_occlusion`].
+    fn rebuild(&mut self, root: NodeRef, arena: &Arena<SceneNode>) {
+        let root_id: TreeStorageId = root.into();
+
+        // Collect nodes in draw order (pre-order = back-to-front),
+        // along with context opacity and the active clip rect.
+        self.draw_order.clear();
+        collect_draw_order(root_id, arena, DrawContext::root(), &mut self.draw_order);
+        self.indices = self
+            .draw_order
+            .iter()
+            .enumerate()
+            .map(|(index, info)| (info.node_ref, index))
+            .collect();
+        self.occluders.clear();
+        let mut occluded = HashSet::new();
+
+        // Front-to-back iteration (reverse of draw order).
+        for index in (0..self.draw_order.len()).rev() {
+            if self.is_occluded(index) {
+                occluded.insert(self.draw_order[index].node_ref);
+                continue;
+            }
+            // If this layer is opaque, add its *clipped* opaque bounds to the mask
+            if let Some(opaque_bounds) = self.draw_order[index].opaque_bounds {
+                self.occluders.push((index, opaque_bounds));
+            }
+        }
+        self.occluded = Arc::new(occluded);
+    }
+
+    /// Whether the node at `index` in the draw order is hidden by the
+    /// occluders in front of it or by an ancestor clip.
+    fn is_occluded(&self, index: usize) -> bool {
+        let info = &self.draw_order[index];
         // Entire subtree is outside an ancestor clip — always skip
         if info.clipped_out {
-            occluded.insert(info.node_ref);
-            continue;
+            return true;
         }
 
         let visible_bounds = info.visible_bounds;
@@ -61,26 +167,95 @@ pub fn compute_occlusion(root: NodeRef, arena: &Arena<SceneNode>) -> HashSet<Nod
         // Zero-size nodes produce no pixels on their own but may have visible
         // children. Never mark them as occluded and never use them as occluders.
         if visible_bounds.width() <= 0.0 || visible_bounds.height() <= 0.0 {
-            continue;
+            return false;
         }
 
-        // Check if this node is fully covered by any existing opaque rect
-        let is_occluded = opaque_rects
+        // Check if this node is fully covered by any occluder in front of it
+        self.occluders
             .iter()
-            .any(|rect| rect.contains(visible_bounds));
+            .any(|(occluder, rect)| *occluder > index && rect.contains(visible_bounds))
+    }
 
-        if is_occluded {
-            occluded.insert(info.node_ref);
-            continue;
+    /// Updates the occlusion of the changed nodes and of their ancestors,
+    /// whose bounds with children follow them. Returns false, leaving the
+    /// cache inconsistent, when the changes can affect the other nodes:
+    /// an occluder changed, a node passes a different clip, opacity or
+    /// blending to its children, or a node started to be drawn.
+    fn update_changed_nodes(&mut self, root: NodeRef, arena: &Arena<SceneNode>) -> bool {
+        let mut indices = HashSet::new();
+        for node in self.changed_nodes.iter() {
+            if !self.indices.contains_key(node) {
+                if self.is_newly_drawn(*node, root, arena) {
+                    return false;
+                }
+                continue;
+            }
+            for ancestor in node.0.ancestors(arena) {
+                // the ancestors of a node in the draw order are in it too,
+                // up to the root
+                match self.indices.get(&NodeRef(ancestor)) {
+                    Some(index) if indices.insert(*index) => {}
+                    _ => break,
+                }
+            }
+        }
+
+        let mut moved = Vec::new();
+        for index in indices {
+            let previous = &self.draw_order[index];
+            let node_id: TreeStorageId = previous.node_ref.into();
+            let Some(info) = node_occlusion_info(node_id, arena, previous.context) else {
+                return false;
+            };
+            let has_children = arena
+                .get(node_id)
+                .is_some_and(|node| node.first_child().is_some());
+            if info.opaque_bounds != previous.opaque_bounds
+                || (has_children && info.child_context != previous.child_context)
+            {
+                return false;
+            }
+            if info.visible_bounds != previous.visible_bounds
+                || info.clipped_out != previous.clipped_out
+            {
+                moved.push(index);
+            }
+            self.draw_order[index] = info;
         }
 
-        // If this layer is fully opaque, add its *clipped* bounds to the mask
-        if info.is_opaque {
-            opaque_rects.push(visible_bounds);
+        for index in moved {
+            let node_ref = self.draw_order[index].node_ref;
+            if self.is_occluded(index) != self.occluded.contains(&node_ref) {
+                let occluded = Arc::make_mut(&mut self.occluded);
+                if !occluded.remove(&node_ref) {
+                    occluded.insert(node_ref);
+                }
+            }
         }
+        true
     }
 
-    occluded
+    /// Whether a node missing from the draw order is drawn now: it was
+    /// hidden, fully transparent or outside of the root when the occlusion
+    /// was computed.
+    fn is_newly_drawn(&self, node: NodeRef, root: NodeRef, arena: &Arena<SceneNode>) -> bool {
+        let mut child: TreeStorageId = node.into();
+        for ancestor in node.0.ancestors(arena).skip(1) {
+            if let Some(index) = self.indices.get(&NodeRef(ancestor)) {
+                // the subtree of the nearest drawn ancestor was skipped at this child
+                let parent = &self.draw_order[*index];
+                let is_mask = arena[ancestor].get().render_layer.mask == Some(NodeRef(child));
+                return !is_mask
+                    && node_occlusion_info(child, arena, parent.child_context).is_some();
+            }
+            child = ancestor;
+        }
+        // No ancestor is drawn: the node is outside of the root, or the
+        // root itself was not drawn
+        node.0
+            .ancestors(arena)
+            .any(|ancestor| NodeRef(ancestor) == root)
+    }
 }
 
 /// Fold occlusion into per-node damage to produce an occlusion-aware
@@ -102,18 +277,40 @@ pub fn compute_occlusion(root: NodeRef, arena: &Arena<SceneNode>) -> HashSet<Nod
 /// change. The full pixel set is visible to the caller only via this
 /// bounding box — sufficient for the use case where downstream
 /// consumers clip/redraw a rectangular area.
-#[profiling::function]
 pub fn compute_occlusion_aware_damage(
     root: NodeRef,
     arena: &Arena<SceneNode>,
     per_node_damage: &HashMap<NodeRef, skia_safe::Rect>,
 ) -> skia_safe::Rect {
+    let bounds = compute_occlusion_aware_damage_region(root, arena, per_node_damage).bounds();
+    if bounds.is_empty() {
+        skia_safe::Rect::default()
+    } else {
+        skia_safe::Rect::from_irect(bounds)
+    }
+}
+
+/// Same as [`compute_occlusion_aware_damage`], but returns the damage
+/// region itself: disjoint damaged areas stay separate.
+#[profiling::function]
+pub fn compute_occlusion_aware_damage_region(
+    root: NodeRef,
+    arena: &Arena<SceneNode>,
+    per_node_damage: &HashMap<NodeRef, skia_safe::Rect>,
+) -> skia_safe::Region {
     let root_id: TreeStorageId = root.into();
 
     // Collect nodes in draw order (pre-order = back-to-front).
     let mut draw_order: Vec<NodeOcclusionInfo> = Vec::new();
-    collect_draw_order(root_id, arena, 1.0, None, &mut draw_order);
+    collect_draw_order(root_id, arena, DrawContext::root(), &mut draw_order);
+    fold_occlusion_into_damage(&draw_order, arena, per_node_damage)
+}
 
+fn fold_occlusion_into_damage(
+    draw_order: &[NodeOcclusionInfo],
+    arena: &Arena<SceneNode>,
+    per_node_damage: &HashMap<NodeRef, skia_safe::Rect>,
+) -> skia_safe::Region {
     // Opaque shapes accumulated in front-to-back order, tagged with the
     // node that contributed each one. The tag lets us filter descendants
     // out when subtracting occlusion from an ancestor's damage — a
@@ -152,12 +349,9 @@ pub fn compute_occlusion_aware_damage(
         }
 
         // After considering this node's damage, if the node itself is a
-        // valid opaque occluder, record its visible bounds.
-        if info.is_opaque {
-            let vb = info.visible_bounds;
-            if vb.width() > 0.0 && vb.height() > 0.0 {
-                occluder_shapes.push((info.node_ref, rect_to_irect(vb)));
-            }
+        // valid opaque occluder, record its opaque bounds.
+        if let Some(opaque_bounds) = info.opaque_bounds {
+            occluder_shapes.push((info.node_ref, rect_to_inner_irect(opaque_bounds)));
         }
     }
 
@@ -174,18 +368,13 @@ pub fn compute_occlusion_aware_damage(
         damage.op_rect(rect_to_irect(*rect), skia_safe::region::RegionOp::Union);
     }
 
-    let bounds = damage.bounds();
-    if bounds.is_empty() {
-        skia_safe::Rect::default()
-    } else {
-        skia_safe::Rect::from_irect(bounds)
-    }
+    damage
 }
 
 /// Convert a floating-point rect to the integer rect that `skia::Region`
 /// requires. Rounds outward so the integer rect covers every pixel the
 /// float rect touches — never under-reports damage.
-fn rect_to_irect(r: skia_safe::Rect) -> skia_safe::IRect {
+pub(crate) fn rect_to_irect(r: skia_safe::Rect) -> skia_safe::IRect {
     skia_safe::IRect::from_ltrb(
         r.left.floor() as i32,
         r.top.floor() as i32,
@@ -194,6 +383,17 @@ fn rect_to_irect(r: skia_safe::Rect) -> skia_safe::IRect {
     )
 }
 
+/// Convert an occluder rect to the integer rect of the pixels it fully
+/// covers. Rounds inward so partially covered pixels stay damaged.
+fn rect_to_inner_irect(r: skia_safe::Rect) -> skia_safe::IRect {
+    skia_safe::IRect::from_ltrb(
+        r.left.ceil() as i32,
+        r.top.ceil() as i32,
+        r.right.floor() as i32,
+        r.bottom.floor() as i32,
+    )
+}
+
 /// Returns `true` if `maybe_descendant` is a strict descendant of
 /// `ancestor` in the scene tree (i.e. lives in the subtree rooted at
 /// `ancestor` but is not `ancestor` itself). Walks `maybe_descendant`'s
@@ -218,45 +418,93 @@ fn is_descendant(maybe_descendant: NodeRef, ancestor: NodeRef, arena: &Arena<Sce
     false
 }
 
+/// What an ancestor passes down to the occlusion of its subtree
+#[derive(Clone, Copy, PartialEq)]
+struct DrawContext {
+    /// The opacity of the ancestors
+    opacity: f32,
+    /// The intersection of all ancestor `clip_children` bounds in global
+    /// coordinates. `None` means no clipping is active.
+    clip_rect: Option<skia_safe::Rect>,
+    /// Set when an ancestor composites its subtree with a blend mode,
+    /// through a mask or through filters: the backdrop stays visible through
+    /// the whole group, so nothing in it occludes.
+    blended: bool,
+}
+
+impl DrawContext {
+    fn root() -> Self {
+        Self {
+            opacity: 1.0,
+            clip_rect: None,
+            blended: false,
+        }
+    }
+}
+
 struct NodeOcclusionInfo {
     node_ref: NodeRef,
     /// The node bounds intersected with the active clip (what is actually visible).
     visible_bounds: skia_safe::Rect,
-    /// Whether the node is fully opaque and can act as an occluder.
-    is_opaque: bool,
+    /// The area the node hides, intersected with the active clip, when it
+    /// can act as an occluder.
+    opaque_bounds: Option<skia_safe::Rect>,
     /// Whether the node (including its children) is entirely outside its ancestor clip region.
     clipped_out: bool,
+    /// The context the node is drawn in
+    context: DrawContext,
+    /// The context the node draws its children in
+    child_context: DrawContext,
 }
 
 /// Pre-order traversal collecting occlusion info in draw order.
-///
-/// `clip_rect` is the intersection of all ancestor `clip_children` bounds
-/// in global coordinates. `None` means no clipping is active.
+/// Mask layers are not drawn and are skipped.
 fn collect_draw_order(
     node_id: TreeStorageId,
     arena: &Arena<SceneNode>,
-    context_opacity: f32,
-    clip_rect: Option<skia_safe::Rect>,
+    context: DrawContext,
     out: &mut Vec<NodeOcclusionInfo>,
 ) {
-    let Some(node) = arena.get(node_id) else {
+    let Some(info) = node_occlusion_info(node_id, arena, context) else {
         return;
     };
+    let child_context = info.child_context;
+    out.push(info);
+
+    let mask = arena[node_id].get().render_layer.mask;
+    for child_id in node_id.children(arena).collect::<Vec<_>>() {
+        if mask == Some(NodeRef(child_id)) {
+            continue;
+        }
+        collect_draw_order(child_id, arena, child_context, out);
+    }
+}
+
+/// The occlusion info of a node drawn in `context`. Returns `None` when
+/// the node and its subtree draw nothing.
+fn node_occlusion_info(
+    node_id: TreeStorageId,
+    arena: &Arena<SceneNode>,
+    context: DrawContext,
+) -> Option<NodeOcclusionInfo> {
+    let node = arena.get(node_id)?;
     if node.is_removed() {
-        return;
+        return None;
     }
     let scene_node = node.get();
 
     // Hidden nodes and their entire subtree are invisible — skip them.
     if scene_node.hidden() {
-        return;
+        return None;
     }
 
+    let context_opacity = context.opacity;
+    let clip_rect = context.clip_rect;
     let opacity = scene_node.render_layer.opacity * context_opacity;
 
     // Fully transparent subtrees produce no pixels — skip entirely.
     if opacity <= 0.0 {
-        return;
+        return None;
     }
 
     let render_layer = &scene_node.render_layer;
@@ -282,14 +530,17 @@ fn collect_draw_order(
     // A node is only an occluder when it AND all its ancestors are fully opaque.
     // Layers with opacity < 1.0, hidden parents, or semi-transparent subtrees
     // never contribute to the occlusion mask (but can still be occluded).
-    let is_opaque = !clipped_out && render_layer.is_fully_opaque() && context_opacity >= 1.0;
-
-    out.push(NodeOcclusionInfo {
-        node_ref: NodeRef(node_id),
-        visible_bounds,
-        is_opaque,
-        clipped_out,
-    });
+    let opaque_bounds = if !clipped_out && !context.blended && context_opacity >= 1.0 {
+        render_layer
+            .global_opaque_bounds()
+            .map(|opaque_bounds| match clip_rect {
+                Some(clip) => intersect_rects(opaque_bounds, clip),
+                None => opaque_bounds,
+            })
+            .filter(|opaque_bounds| opaque_bounds.width() > 0.0 && opaque_bounds.height() > 0.0)
+    } else {
+        None
+    };
 
     // Compute the clip rect for children
     let child_clip = if render_layer.clip_children {
@@ -303,9 +554,24 @@ fn collect_draw_order(
         clip_rect
     };
 
-    for child_id in node_id.children(arena).collect::<Vec<_>>() {
-        collect_draw_order(child_id, arena, opacity, child_clip, out);
-    }
+    let blended = context.blended
+        || render_layer.blend_mode.to_skia().is_some()
+        || render_layer.mask.is_some()
+        || !render_layer.filters.is_empty()
+        || render_layer.preserve_3d;
+
+    Some(NodeOcclusionInfo {
+        node_ref: NodeRef(node_id),
+        visible_bounds,
+        opaque_bounds,
+        clipped_out,
+        context,
+        child_context: DrawContext {
+            opacity,
+            clip_rect: child_clip,
+            blended,
+        },
+    })
 }
 
 /// Intersect two rects, returning an empty rect if they don't overlap.
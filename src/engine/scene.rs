@@ -5,6 +5,7 @@
 //! The tree is stored in a memory arena using IndexTree, which allow fast read/write and thread safe parallel iterations.
 
 use crate::{
+    drawing::{surface_cache::SurfaceCache, DEFAULT_SURFACE_CACHE_BUDGET},
     engine::occlusion::OcclusionMap,
     engine::storage::{FlatStorage, FlatStorageData},
     layers::layer::render_layer::RenderLayer,
@@ -16,7 +17,7 @@ use std::sync::{Arc, RwLock};
 use tracing::error;
 
 use super::{
-    node::{RenderableFlags, SceneNode, SceneNodeRenderable},
+    node::{AutoCacheDecision, RenderableFlags, SceneNode, SceneNodeRenderable},
     storage::{TreeStorage, TreeStorageId},
     Engine, NodeRef,
 };
@@ -45,6 +46,9 @@ pub struct Scene {
     /// Per-root occlusion data: maps a root NodeRef to the set of nodes
     /// that are fully occluded when drawing from that root.
     occlusion_map: RwLock<OcclusionMap>,
+
+    /// The offscreen surfaces of the image-cached nodes
+    pub(crate) surface_cache: Arc<SurfaceCache>,
 }
 
 impl Scene {
@@ -59,6 +63,7 @@ impl Scene {
                 y: height,
             }),
             occlusion_map: RwLock::new(std::collections::HashMap::new()),
+            surface_cache: Arc::new(SurfaceCache::new(DEFAULT_SURFACE_CACHE_BUDGET)),
         }
     }
     pub fn set_size(&self, width: f32, height: f32) {
@@ -69,17 +74,23 @@ impl Scene {
         };
     }
 
+    /// The cache of the offscreen surfaces of the image-cached nodes, to pass
+    /// to `render_node_tree_with_cache`
+    pub fn surface_cache(&self) -> &SurfaceCache {
+        &self.surface_cache
+    }
+
     /// Returns a snapshot of the occlusion map (root -> occluded node set).
     pub fn occlusion_map(&self) -> Option<OcclusionMap> {
         self.occlusion_map.read().ok().map(|m| m.clone())
     }
 
-    /// Returns a clone of the occluded-node set for the given root, without
-    /// cloning the entire map.
+    /// Returns the occluded-node set for the given root, shared with the
+    /// engine, without cloning the entire map.
     pub fn occluded_set(
         &self,
         root: super::NodeRef,
-    ) -> Option<std::collections::HashSet<super::NodeRef>> {
+    ) -> Option<Arc<std::collections::HashSet<super::NodeRef>>> {
         self.occlusion_map
             .read()
             .ok()
@@ -90,7 +101,7 @@ impl Scene {
     pub(crate) fn add_occlusion(
         &self,
         root: super::NodeRef,
-        occluded: std::collections::HashSet<super::NodeRef>,
+        occluded: Arc<std::collections::HashSet<super::NodeRef>>,
     ) {
         if let Ok(mut map) = self.occlusion_map.write() {
             map.insert(root, occluded);
@@ -153,6 +164,24 @@ impl Scene {
             }
         });
     }
+    /// Detach the node from its parent, keeping it and its subtree alive.
+    /// The parent node is marked as needing layout (NEEDS_LAYOUT).
+    pub(crate) fn detach_node(&self, node: NodeRef) {
+        self.with_arena_mut(|nodes| {
+            let node = *node;
+            let Some(parent) = nodes
+                .get(node)
+                .filter(|node| !node.is_removed())
+                .map(|node| node.parent())
+            else {
+                return;
+            };
+            node.detach(nodes);
+            if let Some(parent_node) = parent.and_then(|parent| nodes.get_mut(parent)) {
+                parent_node.get_mut().set_needs_layout(true);
+            }
+        });
+    }
     /// Add a new node to the scene
     pub(crate) fn insert_node(&self, node: SceneNode, parent: Option<NodeRef>) -> NodeRef {
         let id = self.nodes.insert_sync(node);
@@ -347,6 +376,9 @@ pub struct SceneNodeSnapshot {
     pub hidden: bool,
     pub pointer_events: bool,
     pub image_cached: bool,
+    /// The last decision of the engine automatic image caching
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_cache: Option<AutoCacheDecision>,
     pub picture_cached: bool,
     pub needs_layout: bool,
     pub needs_repaint: bool,
@@ -434,6 +466,7 @@ impl SceneNodeSnapshot {
             hidden: scene_node.hidden(),
             pointer_events: scene_node.pointer_events(),
             image_cached: scene_node.is_image_cached(),
+            auto_cache: scene_node.auto_cache_decision(),
             picture_cached: scene_node.is_picture_cached(),
             needs_layout: scene_node.needs_layout(),
             needs_repaint: scene_node.needs_repaint(),
@@ -1,8 +1,10 @@
 use std::{
-    collections::HashSet,
-    sync::{Arc, RwLock},
+    collections::{HashMap, HashSet},
+    sync::{atomic::Ordering, Arc, RwLock},
 };
 
+use indextree::NodeId;
+
 use rayon::{
     iter::IntoParallelRefIterator,
     prelude::{IntoParallelRefMutIterator, ParallelIterator},
@@ -12,7 +14,10 @@ use taffy::{prelude::Size, style_helpers::length, Dimension};
 #[cfg(feature = "debugger")]
 use layers_debug_server::send_debugger_message;
 
-use crate::{engine::node::RenderableFlags, prelude::Layer};
+use crate::{
+    engine::node::{worth_caching, RenderableFlags, AUTO_CACHE_BUDGET_FRACTION},
+    prelude::Layer,
+};
 
 use super::{
     storage::FlatStorageId, AnimationState, Engine, NodeRef, Timestamp, TransactionCallback,
@@ -87,6 +92,7 @@ pub(crate) fn execute_transactions(engine: &Engine) -> (Vec<NodeRef>, Vec<FlatSt
             let animations = engine.animations.data();
             let animations = &*animations.read().unwrap();
             let scene = engine.scene();
+            let now = engine.now();
 
             // iterate in parallel over all the changes to be applied
             transactions.par_iter().for_each_with(
@@ -112,7 +118,12 @@ pub(crate) fn execute_transactions(engine: &Engine) -> (Vec<NodeRef>, Vec<FlatSt
                             is_started: false,
                         });
                     // apply the changes
-                    let flags = command.change.execute(animation_state.progress);
+                    let flags = if command.animation_id.is_some() {
+                        let elapsed = (now - animation_state.animation.start).max(0.0);
+                        command.change.execute_at(animation_state.progress, elapsed)
+                    } else {
+                        command.change.execute(animation_state.progress)
+                    };
 
                     let node_id = command.node_id;
                     updated_nodes.write().unwrap().push(node_id);
@@ -597,11 +608,117 @@ pub(crate) fn cleanup_nodes(engine: &Engine) -> skia_safe::Rect {
         })
     };
     for id in deleted {
+        // the cached surfaces of the removed subtree are dropped
+        let subtree: Vec<_> = engine
+            .scene
+            .with_arena(|arena| id.descendants(arena).collect());
+        for node_id in subtree {
+            engine.surface_cache.remove(&NodeRef(node_id));
+            engine.remove_occlusion_cache(NodeRef(node_id));
+        }
         engine.scene_remove_layer(&NodeRef(id));
     }
     damage
 }
 
+/// Updates the automatic image caching of the scene: subtrees that are stable
+/// and draw enough layers for the area they cover are promoted to image caches,
+/// within a share of the surface cache budget; subtrees that change too often
+/// are demoted. The root is never cached.
+///
+/// Without `changes` in the scene no subtree can be demoted, so the walk is
+/// skipped until a subtree can be promoted; the skipped updates are recorded
+/// on the next walk.
+#[profiling::function]
+pub(crate) fn update_auto_caching(engine: &Engine, changes: bool) {
+    if !engine.auto_image_caching() {
+        return;
+    }
+    let updates = engine
+        .auto_cache_pending_updates
+        .fetch_add(1, Ordering::Relaxed)
+        + 1;
+    if !changes && updates < engine.auto_cache_next_promotion.load(Ordering::Relaxed) {
+        return;
+    }
+    engine
+        .auto_cache_pending_updates
+        .store(0, Ordering::Relaxed);
+    let Some(root) = engine.scene_root() else {
+        return;
+    };
+    let mut available_bytes = engine.surface_cache.stats().budget / AUTO_CACHE_BUDGET_FRACTION;
+    let mut next_promotion = u32::MAX;
+    let demoted = engine.scene.with_arena_mut(|arena| {
+        // parents before children
+        let nodes: Vec<_> = root
+            .0
+            .descendants(arena)
+            .filter(|node_id| !node_id.is_removed(arena))
+            .collect();
+
+        // the cost of a subtree is the number of visible layers it draws
+        let mut costs: HashMap<NodeId, usize> = HashMap::with_capacity(nodes.len());
+        for node_id in nodes.iter().rev() {
+            let scene_node = arena[*node_id].get();
+            let cost = if scene_node.hidden() {
+                0
+            } else {
+                scene_node.render_layer.visible as usize
+                    + node_id
+                        .children(arena)
+                        .filter_map(|child| costs.get(&child))
+                        .sum::<usize>()
+            };
+            costs.insert(*node_id, cost);
+        }
+
+        // nodes that are image cached or drawn into an ancestor cache
+        let mut in_cache: HashSet<NodeId> = HashSet::new();
+        let mut demoted = Vec::new();
+        for node_id in nodes {
+            let ancestor_cached = arena[node_id]
+                .parent()
+                .is_some_and(|parent| in_cache.contains(&parent));
+            let scene_node = arena[node_id].get_mut();
+            scene_node.auto_cache.record_updates(updates);
+            let was_promoted = scene_node.auto_cache.is_promoted();
+            let cost = costs.get(&node_id).copied().unwrap_or_default();
+            let bounds = scene_node.render_layer.bounds_with_children.with_outset((
+                scene_node.render_layer.border_width,
+                scene_node.render_layer.border_width,
+            ));
+            let pixels = bounds.width() * bounds.height();
+            let worth = node_id != root.0 && worth_caching(cost, pixels, available_bytes);
+            if scene_node.auto_cache.decide(worth, ancestor_cached) {
+                if scene_node.auto_cache.is_promoted() {
+                    // the cached surface has to be drawn
+                    scene_node.increase_frame();
+                } else if was_promoted {
+                    demoted.push(NodeRef(node_id));
+                }
+            }
+            if scene_node.auto_cache.is_promoted() {
+                available_bytes = available_bytes.saturating_sub((pixels * 4.0) as usize);
+            } else if worth && !ancestor_cached {
+                next_promotion =
+                    next_promotion.min(scene_node.auto_cache.updates_before_promotion());
+            }
+            if ancestor_cached || scene_node.is_image_cached() {
+                in_cache.insert(node_id);
+            }
+        }
+        demoted
+    });
+    engine
+        .auto_cache_next_promotion
+        .store(next_promotion, Ordering::Relaxed);
+    // the surfaces of the demoted subtrees are no longer used
+    for node_ref in demoted {
+        engine.surface_cache.remove(&node_ref);
+    }
+}
+
 #[cfg(feature = "debugger")]
 pub fn send_debugger(engine: &super::Engine) {
     use indextree::NodeId;
@@ -650,6 +767,10 @@ pub fn send_debugger(engine: &super::Engine) {
                             format!("easing ({:.2}s)", duration)
                         }
                         crate::engine::animation::TimingFunction::Spring(_) => "spring".to_string(),
+                        crate::engine::animation::TimingFunction::Spring2d(_) => {
+                            "spring 2d".to_string()
+                        }
+                        crate::engine::animation::TimingFunction::Decay(_) => "decay".to_string(),
                         crate::engine::animation::TimingFunction::Keyframes(segments, _) => {
                             let total: f32 = segments.iter().map(|s| s.duration).sum();
                             format!("keyframes ({}seg, {:.2}s)", segments.len(), total)
@@ -691,7 +812,7 @@ pub fn send_debugger(engine: &super::Engine) {
                     hidden: scene_node.hidden(),
                     frame_number: scene_node.frame_number,
                     picture_cached: scene_node.picture_cached,
-                    image_cached: scene_node.image_cached,
+                    image_cached: scene_node.is_image_cached(),
                 };
                 let id: usize = node_id.into();
                 Some((id, (id, render_layer, children, node_id)))
@@ -11,6 +11,13 @@ pub(crate) struct NodeUpdateResult {
     pub damage: skia::Rect,
     /// Whether this node's transform/opacity change should force children to recompute.
     pub propagate_to_children: bool,
+    /// Whether the content of the node was repainted
+    pub repainted: bool,
+    /// Whether the node had changes to apply, false when it was skipped as clean
+    pub changed: bool,
+    /// Whether the node was only moved along with an ancestor, its drawing in
+    /// the coordinates of the ancestors is unchanged
+    pub moved_with_parent: bool,
 }
 
 fn subtree_has_visible_drawables(arena: &Arena<SceneNode>, node_id: NodeId) -> bool {
@@ -58,6 +65,9 @@ pub(crate) fn update_node_single(
             return NodeUpdateResult {
                 damage: skia::Rect::default(),
                 propagate_to_children: false,
+                repainted: false,
+                changed: false,
+                moved_with_parent: false,
             };
         }
     }
@@ -66,6 +76,9 @@ pub(crate) fn update_node_single(
         return NodeUpdateResult {
             damage: skia::Rect::default(),
             propagate_to_children: false,
+            repainted: false,
+            changed: false,
+            moved_with_parent: false,
         };
     };
     let node_layout = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
@@ -83,6 +96,9 @@ pub(crate) fn update_node_single(
             return NodeUpdateResult {
                 damage: skia::Rect::default(),
                 propagate_to_children: false,
+                repainted: false,
+                changed: false,
+                moved_with_parent: false,
             };
         }
     };
@@ -95,6 +111,10 @@ pub(crate) fn update_node_single(
         prev_visible,
         prev_needs_paint,
         prev_has_filters,
+        prev_mask,
+        prev_filters,
+        prev_sublayer_transform,
+        prev_preserve_3d,
         prev_is_layout_only_passthrough,
         // render_layer.visible accounts for the hidden flag (set by update_render_layer_if_needed);
         // this lets us detect hidden-state changes separately from drawable-content changes.
@@ -111,6 +131,10 @@ pub(crate) fn update_node_single(
                 scene_node.render_layer.has_visible_drawables(),
                 scene_node.needs_repaint(),
                 scene_node.render_layer.has_filters(),
+                scene_node.render_layer.mask,
+                scene_node.render_layer.filters.clone(),
+                scene_node.render_layer.sublayer_transform,
+                scene_node.render_layer.preserve_3d,
                 scene_node.render_layer.is_layout_only_passthrough(),
                 scene_node.render_layer.visible,
             )
@@ -122,6 +146,10 @@ pub(crate) fn update_node_single(
                 false,
                 false,
                 false,
+                None,
+                Vec::new(),
+                skia_safe::M44::new_identity(),
+                false,
                 false,
                 false,
             )
@@ -152,6 +180,7 @@ pub(crate) fn update_node_single(
 
     // Account for parent transform/opacity
     let cumulative_transform = parent.map(|p| &p.transform);
+    let sublayer_transform = parent.map(|p| &p.sublayer_transform);
     let context_opacity = parent.map(|p| p.premultiplied_opacity).unwrap_or(1.0);
 
     // Ensure nodes refresh when only the parent opacity changes.
@@ -176,6 +205,7 @@ pub(crate) fn update_node_single(
                     &node_layout,
                     layer.model.clone(),
                     cumulative_transform,
+                    sublayer_transform,
                     context_opacity,
                     local_children_bounds,
                     parent_changed,
@@ -198,6 +228,10 @@ pub(crate) fn update_node_single(
         new_visible,
         current_needs_paint,
         new_has_filters,
+        new_mask,
+        new_filters,
+        new_sublayer_transform,
+        new_preserve_3d,
         new_is_layout_only_passthrough,
         is_now_hidden,
     ) = engine.scene.with_arena(|arena| {
@@ -212,6 +246,10 @@ pub(crate) fn update_node_single(
             scene_node.render_layer.has_visible_drawables(),
             scene_node.needs_repaint(),
             scene_node.render_layer.has_filters(),
+            scene_node.render_layer.mask,
+            scene_node.render_layer.filters.clone(),
+            scene_node.render_layer.sublayer_transform,
+            scene_node.render_layer.preserve_3d,
             scene_node.render_layer.is_layout_only_passthrough(),
             scene_node.hidden(),
         )
@@ -256,9 +294,17 @@ pub(crate) fn update_node_single(
         return NodeUpdateResult {
             damage: skia_safe::Rect::default(),
             propagate_to_children: parent_changed,
+            repainted: false,
+            changed: true,
+            moved_with_parent: false,
         };
     }
     let changed_filters = prev_has_filters != new_has_filters;
+    let changed_mask = prev_mask != new_mask;
+    let changed_filter_list = prev_filters != new_filters;
+    // The sublayer transform and preserve-3d change how the children are drawn
+    let changed_sublayers =
+        prev_sublayer_transform != new_sublayer_transform || prev_preserve_3d != new_preserve_3d;
 
     // A node is passthrough-only when both before and after the update it has no own
     // visible drawables, no filters, no clipping, and uses Normal blend mode.
@@ -273,6 +319,18 @@ pub(crate) fn update_node_single(
             .get_mut(node_id)
             .and_then(|node| node.get_mut().pending_damage.take())
     });
+    // Only the position of the node changed: its content, in layer
+    // coordinates, is the same and the cached images of the node are reused
+    let moved_only = position_changed
+        && !layout_changed
+        && !prev_needs_paint
+        && pending_damage.is_none()
+        && !opacity_changed
+        && !visibility_changed
+        && !changed_filters
+        && !changed_mask
+        && !changed_filter_list
+        && !changed_sublayers;
 
     let mut updated_renderable = None;
     let content_damage = engine.scene.with_arena(|arena| {
@@ -298,6 +356,7 @@ pub(crate) fn update_node_single(
         }
     });
 
+    let repainted = updated_renderable.is_some();
     if let Some(renderable) = updated_renderable {
         engine
             .scene
@@ -374,6 +433,36 @@ pub(crate) fn update_node_single(
         total_damage.join(prev_transformed_bounds);
         total_damage.join(new_transformed_bounds);
     }
+    // Setting or removing a mask changes what is visible of the whole subtree.
+    if changed_mask {
+        total_damage.join(prev_transformed_bounds);
+        total_damage.join(new_transformed_bounds);
+    }
+    // The filters of the node and of its ancestors draw the damaged content
+    // beyond its bounds (blur, drop shadow).
+    let filters_outset = engine.scene.with_arena(|arena| {
+        std::iter::successors(Some(node_id), |id| {
+            arena.get(*id).and_then(|node| node.parent())
+        })
+        .filter_map(|id| arena.get(id))
+        .map(|node| RenderLayer::filters_outset(&node.get().render_layer.filters))
+        .sum::<f32>()
+    });
+    if filters_outset > 0.0 && !total_damage.is_empty() {
+        total_damage = total_damage.with_outset((filters_outset, filters_outset));
+    }
+    // Filters apply to the whole subtree and can draw outside of it (blur, drop shadow).
+    if changed_filter_list {
+        let prev_outset = RenderLayer::filters_outset(&prev_filters);
+        let new_outset = RenderLayer::filters_outset(&new_filters);
+        total_damage.join(prev_transformed_bounds.with_outset((prev_outset, prev_outset)));
+        total_damage.join(new_transformed_bounds.with_outset((new_outset, new_outset)));
+    }
+    // Projecting or reordering the sublayers redraws the whole subtree.
+    if changed_sublayers {
+        total_damage.join(prev_transformed_bounds);
+        total_damage.join(new_transformed_bounds);
+    }
     let content_repainted = !content_damage.is_empty();
     let damaged = content_repainted
         || (geometry_changed_self && (has_visible_drawables || is_debug))
@@ -382,17 +471,25 @@ pub(crate) fn update_node_single(
         || parent_changed
         || visibility_changed
         || changed_render_layer
-        || changed_filters;
+        || changed_filters
+        || changed_mask
+        || changed_filter_list
+        || changed_sublayers;
 
     if damaged {
         engine.scene.with_arena_mut(|arena| {
             if let Some(node) = arena.get_mut(node_id) {
-                node.get_mut().increase_frame();
+                if moved_only {
+                    node.get_mut().increase_frame_moved();
+                } else {
+                    node.get_mut().increase_frame();
+                }
             }
         });
     }
 
-    let propagate_to_children = parent_changed || geometry_changed_self || opacity_changed;
+    let propagate_to_children =
+        parent_changed || geometry_changed_self || opacity_changed || changed_sublayers;
     let nid: usize = node_id.into();
     if nid == 8 && total_damage.width() == 1000.0 {
         println!("update_node_single: node_id={:?}, damage={:?}, layout_changed={}, position_changed={}, opacity_changed={}, visibility_changed={}, changed_render_layer={}, prev_has_filters={}, new_has_filters={}, propagate_to_children={}", nid, total_damage, layout_changed, position_changed, opacity_changed, visibility_changed, changed_render_layer, prev_has_filters, new_has_filters, propagate_to_children);
@@ -400,6 +497,10 @@ pub(crate) fn update_node_single(
     NodeUpdateResult {
         damage: total_damage,
         propagate_to_children,
+        repainted,
+        changed: true,
+        // the position relative to the parent is unchanged
+        moved_with_parent: moved_only && !changed_render_layer,
     }
 }
 
@@ -80,6 +80,10 @@ impl<V: Clone + Send + Sync> FlatStorage<V> {
         self.insert_with_id(value, id);
         id
     }
+    /// Reserves an id for a value that will be inserted later with `insert_with_id`
+    pub fn reserve_id(&self) -> FlatStorageId {
+        self.index.fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+    }
     pub fn insert_with_id(&self, value: V, id: FlatStorageId) -> FlatStorageId {
         let mut data = self.data.write().unwrap();
         data.insert(id, value);
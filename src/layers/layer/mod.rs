@@ -18,13 +18,14 @@ use taffy::style::Style;
 
 use self::model::{ContentDrawFunction, PointerHandlerFunction};
 
+use crate::easing::Interpolate;
 use crate::engine::{command::*, PointerEventType};
 use crate::engine::{node::RenderableFlags, TransactionCallback};
-use crate::engine::{Engine, NodeRef, TransactionRef};
-use crate::shape::Shape;
+use crate::engine::{Command, Engine, NodeRef, TransactionRef};
+use crate::shape::{MeasuredPath, Shape};
 use crate::types::*;
 use crate::{
-    drawing::render_node_tree,
+    drawing::render_node_tree_with_cache,
     engine::{animation::*, storage::TreeStorageId, AnimatedNodeChange},
 };
 
@@ -37,6 +38,37 @@ thread_local! {
     static RENDERING_LAYERS: RefCell<HashSet<NodeRef>> = RefCell::new(HashSet::new());
 }
 
+/// The attributes accepted by `Layer::set_implicit_transition`
+pub const IMPLICIT_TRANSITION_ATTRIBUTES: &[&str] = &[
+    "position",
+    "scale",
+    "rotation",
+    "anchor_point",
+    "transform",
+    "sublayer_transform",
+    "perspective",
+    "perspective_origin",
+    "opacity",
+    "size",
+    "background_color",
+    "border_corner_radius",
+    "border_color",
+    "border_width",
+    "border_dash_length",
+    "border_dash_gap",
+    "border_dash_phase",
+    "shadow_offset",
+    "shadow_radius",
+    "shadow_spread",
+    "shadow_color",
+    "shadows",
+    "backdrop_effect",
+    "image_filter_progress",
+    "filters",
+    "clip_content",
+    "clip_children",
+];
+
 #[allow(private_interfaces)]
 #[repr(C)]
 #[derive(Clone)]
@@ -147,10 +179,102 @@ impl Layer {
             .load(std::sync::atomic::Ordering::Relaxed)
     }
 
+    /// Sets the transition used to animate `attribute` (e.g. `"position"`) when
+    /// its setter is called without a transition, like Core Animation actions.
+    /// Passing None removes the implicit transition. The attribute is one of
+    /// [`IMPLICIT_TRANSITION_ATTRIBUTES`], debug builds panic on any other name.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// layer.set_implicit_transition("position", Transition::spring(0.3, 0.1));
+    /// // animates with the spring
+    /// layer.set_position((100.0, 100.0), None);
+    /// // applied immediately
+    /// engine.without_implicit_transitions(|| {
+    ///     layer.set_position((0.0, 0.0), None);
+    /// });
+    /// ```
+    pub fn set_implicit_transition(
+        &self,
+        attribute: impl Into<String>,
+        transition: impl Into<Option<Transition>>,
+    ) {
+        let attribute = attribute.into();
+        debug_assert!(
+            IMPLICIT_TRANSITION_ATTRIBUTES.contains(&attribute.as_str()),
+            "unknown attribute {attribute:?} for implicit transitions"
+        );
+        let mut transitions = self.model.implicit_transitions.write().unwrap();
+        match transition.into() {
+            Some(transition) => {
+                transitions.insert(attribute, transition);
+            }
+            None => {
+                transitions.remove(&attribute);
+            }
+        }
+    }
+    pub fn implicit_transition(&self, attribute: &str) -> Option<Transition> {
+        self.model
+            .implicit_transitions
+            .read()
+            .unwrap()
+            .get(attribute)
+            .cloned()
+    }
+    /// The explicit transition if any, otherwise the implicit one unless
+    /// implicit transitions are disabled in the current scope.
+    /// Inside an `Engine::transaction` no transition is used, the changes share
+    /// the transaction animation.
+    pub(crate) fn transition_or_implicit(
+        &self,
+        attribute: &str,
+        transition: Option<Transition>,
+    ) -> Option<Transition> {
+        debug_assert!(
+            IMPLICIT_TRANSITION_ATTRIBUTES.contains(&attribute),
+            "{attribute:?} is missing from IMPLICIT_TRANSITION_ATTRIBUTES"
+        );
+        if self.engine.is_recording_transaction() {
+            return None;
+        }
+        transition.or_else(|| {
+            if implicit_transitions_enabled() {
+                self.implicit_transition(attribute)
+            } else {
+                None
+            }
+        })
+    }
+    /// Schedules a change to an attribute that is not animated: it is applied
+    /// immediately, or together with the other changes inside an `Engine::transaction`.
+    fn schedule_discrete_change<T>(
+        &self,
+        attribute: &Attribute<T>,
+        value: T,
+        flag: RenderableFlags,
+    ) -> TransactionRef
+    where
+        T: Interpolate + PartialEq + fmt::Debug + Send + Sync + Clone + 'static,
+    {
+        let change = Arc::new(ModelChange {
+            value_change: attribute.to(value.clone(), None),
+            flag,
+        });
+        if !self.engine.is_recording_transaction() {
+            attribute.set(value);
+        }
+        self.engine.schedule_change(self.id, change, None)
+    }
+
     change_model!(position, Point, RenderableFlags::NEEDS_LAYOUT);
     change_model!(scale, Point, RenderableFlags::NEEDS_LAYOUT);
     change_model!(rotation, Point3d, RenderableFlags::NEEDS_LAYOUT);
     change_model!(anchor_point, Point, RenderableFlags::NEEDS_LAYOUT);
+    change_model!(transform, Transform, RenderableFlags::NEEDS_LAYOUT);
+    change_model!(sublayer_transform, Transform, RenderableFlags::NEEDS_LAYOUT);
+    change_model!(perspective, f32, RenderableFlags::NEEDS_LAYOUT);
+    change_model!(perspective_origin, Point, RenderableFlags::NEEDS_LAYOUT);
     change_model!(opacity, f32, RenderableFlags::NEEDS_LAYOUT);
 
     change_model!(background_color, PaintColor, RenderableFlags::NEEDS_PAINT);
@@ -162,14 +286,117 @@ impl Layer {
 
     change_model!(border_color, PaintColor, RenderableFlags::NEEDS_PAINT);
     change_model!(border_width, f32, RenderableFlags::NEEDS_PAINT);
+    change_model!(border_dash_length, f32, RenderableFlags::NEEDS_PAINT);
+    change_model!(border_dash_gap, f32, RenderableFlags::NEEDS_PAINT);
+    change_model!(border_dash_phase, f32, RenderableFlags::NEEDS_PAINT);
     change_model!(shadow_offset, Point, RenderableFlags::NEEDS_PAINT);
     change_model!(shadow_radius, f32, RenderableFlags::NEEDS_PAINT);
     change_model!(shadow_spread, f32, RenderableFlags::NEEDS_PAINT);
     change_model!(shadow_color, Color, RenderableFlags::NEEDS_PAINT);
+    change_model!(
+        backdrop_effect,
+        BackdropEffect,
+        RenderableFlags::NEEDS_PAINT
+    );
     change_model!(image_filter_progress, f32, RenderableFlags::NEEDS_PAINT);
+    change_model!(filters, Vec<Filter>, RenderableFlags::NEEDS_PAINT);
     change_model!(clip_content, bool, RenderableFlags::NEEDS_PAINT);
     change_model!(clip_children, bool, RenderableFlags::NEEDS_PAINT);
 
+    change_model_spring_2d!(position, RenderableFlags::NEEDS_LAYOUT);
+    change_model_spring_2d!(scale, RenderableFlags::NEEDS_LAYOUT);
+    change_model_spring_2d!(anchor_point, RenderableFlags::NEEDS_LAYOUT);
+    change_model_spring_2d!(shadow_offset, RenderableFlags::NEEDS_PAINT);
+
+    /// Sets the list of additional shadows, drawn following the layer shape with the
+    /// first shadow on top.
+    /// Shadows already in the list animate to their new value, new ones fade in from
+    /// transparent; when the list gets shorter the extra shadows are removed immediately.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// layer.set_shadows(
+    ///     vec![
+    ///         Shadow::new((0.0, 4.0), 12.0, 0.0, Color::new_rgba(0.0, 0.0, 0.0, 0.3)),
+    ///         Shadow::inset((0.0, 1.0), 2.0, 0.0, Color::new_rgba(1.0, 1.0, 1.0, 0.5)),
+    ///     ],
+    ///     Transition::ease_out(0.3),
+    /// );
+    /// ```
+    pub fn set_shadows(
+        &self,
+        shadows: Vec<Shadow>,
+        transition: impl Into<Option<Transition>>,
+    ) -> Vec<TransactionRef> {
+        let transition = self.transition_or_implicit("shadows", transition.into());
+        let mut attributes = self.model.shadows.value();
+        attributes.truncate(shadows.len());
+        for shadow in shadows.iter().skip(attributes.len()) {
+            attributes.push(Attribute::new(shadow.transparent()));
+        }
+        // the list itself may have changed, inside a transaction it is
+        // replaced together with the other changes
+        let list_change = Arc::new(ValueChange {
+            value_change: self.model.shadows.to(attributes.clone(), None),
+            flag: RenderableFlags::NEEDS_PAINT,
+        });
+        let recording = self.engine.is_recording_transaction();
+        if !recording {
+            self.model.shadows.set(attributes.clone());
+            // always repaint
+            self.engine
+                .set_node_flags(self.id, RenderableFlags::NEEDS_PAINT);
+        }
+        self.engine.schedule_change(self.id, list_change, None);
+
+        let animation = transition.map(|t| self.engine.add_animation_from_transition(&t, true));
+        let changes: Vec<AnimatedNodeChange> = attributes
+            .iter()
+            .zip(shadows)
+            .map(|(attribute, shadow)| {
+                if animation.is_none() && !recording {
+                    // if there is no transition, apply the change immediately
+                    attribute.set(shadow);
+                }
+                AnimatedNodeChange {
+                    change: Arc::new(ModelChange {
+                        value_change: attribute.to(shadow, transition.clone()),
+                        flag: RenderableFlags::NEEDS_PAINT,
+                    }),
+                    animation_id: None,
+                    node_id: self.id,
+                }
+            })
+            .collect();
+        self.engine.schedule_changes(&changes, animation)
+    }
+
+    /// Animates the shadow at `index` independently from the others.
+    /// Returns None if there is no shadow at `index`.
+    pub fn set_shadow(
+        &self,
+        index: usize,
+        shadow: Shadow,
+        transition: impl Into<Option<Transition>>,
+    ) -> Option<TransactionRef> {
+        let attribute = self.model.shadows.value().get(index).cloned()?;
+        let transition = self.transition_or_implicit("shadows", transition.into());
+
+        let change = Arc::new(ModelChange {
+            value_change: attribute.to(shadow, transition.clone()),
+            flag: RenderableFlags::NEEDS_PAINT,
+        });
+        let animation = transition.map(|t| self.engine.add_animation_from_transition(&t, true));
+        if animation.is_none() && !self.engine.is_recording_transaction() {
+            // if there is no transition, apply the change immediately
+            attribute.set(shadow);
+        }
+        Some(self.engine.schedule_change(self.id, change, animation))
+    }
+    pub fn shadows(&self) -> Vec<Shadow> {
+        self.model.shadows()
+    }
+
     /// Set a custom shape for this layer.
     ///
     /// The shape affects the layer's visual boundary for rendering, hit-testing, and clipping.
@@ -195,6 +422,62 @@ impl Layer {
             .set_node_flags(self.id, RenderableFlags::NEEDS_PAINT);
     }
 
+    /// Animates `position` along `path`, mapping the animation progress to the
+    /// distance travelled on the path. The path is in the same coordinate space
+    /// as `position`. When `follow_tangent` is set, the z rotation follows the
+    /// direction of the path as well.
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let mut path = skia_safe::Path::new();
+    /// path.move_to((0.0, 0.0));
+    /// path.quad_to((200.0, 0.0), (200.0, 400.0));
+    ///
+    /// layer.set_position_along_path(&path, Transition::ease_in_out(0.6), true);
+    /// ```
+    pub fn set_position_along_path(
+        &self,
+        path: &skia::Path,
+        transition: impl Into<Option<Transition>>,
+        follow_tangent: bool,
+    ) -> TransactionRef {
+        let transition = transition.into();
+        let path = MeasuredPath::new(path);
+
+        let mut changes: Vec<Arc<MotionPathChange>> = vec![Arc::new(MotionPathChange {
+            path: path.clone(),
+            target: MotionPathTarget::Position(self.model.position.clone()),
+            flag: RenderableFlags::NEEDS_LAYOUT,
+        })];
+        if follow_tangent {
+            changes.push(Arc::new(MotionPathChange {
+                path,
+                target: MotionPathTarget::Rotation(self.model.rotation.clone()),
+                flag: RenderableFlags::NEEDS_LAYOUT,
+            }));
+        }
+
+        let recording = self.engine.is_recording_transaction();
+        let animation = transition
+            .filter(|_| !recording)
+            .map(|t| self.engine.add_animation_from_transition(&t, true));
+        if animation.is_none() && !recording {
+            // if there is no transition, apply the change immediately
+            for change in changes.iter() {
+                change.execute(1.0);
+            }
+        }
+        let changes: Vec<AnimatedNodeChange> = changes
+            .into_iter()
+            .map(|change| AnimatedNodeChange {
+                change,
+                animation_id: None,
+                node_id: self.id,
+            })
+            .collect();
+        self.engine.schedule_changes(&changes, animation)[0]
+    }
+
     /// Sets the anchor point while compensating the `position` so the layer stays in the
     /// same place on screen. Returns the newly applied position.
     pub fn set_anchor_point_preserving_position(&self, anchor_point: impl Into<Point>) -> Point {
@@ -286,7 +569,7 @@ impl Layer {
         value: impl Into<Size>,
         transition: impl Into<Option<Transition>>,
     ) -> TransactionRef {
-        let transition = transition.into();
+        let transition = self.transition_or_implicit("size", transition.into());
         let value: Size = value.into();
 
         let flags = RenderableFlags::NEEDS_LAYOUT;
@@ -314,6 +597,9 @@ impl Layer {
                                         s.update_pos_vel_at(animation_state.time);
                                     current_velocity
                                 }
+                                TimingFunction::Decay(d) => {
+                                    d.update_pos_vel_at(animation_state.time).1
+                                }
                                 _ => 0.0,
                             }
                         } else {
@@ -334,7 +620,7 @@ impl Layer {
                 true,
             )
         });
-        if animation.is_none() {
+        if animation.is_none() && !self.engine.is_recording_transaction() {
             self.model.size.set(value);
         }
         self.engine.schedule_change(self.id, change, animation)
@@ -362,7 +648,7 @@ impl Layer {
         let draw: ContentDrawFunction = content_handler.into();
         *model_content = Some(draw.into());
 
-        let attribute_id = self.model.blend_mode.id;
+        let attribute_id = self.model.redraw_id;
         self.engine
             .schedule_change(self.id, Arc::new(NoopChange::paint(attribute_id)), None);
     }
@@ -394,11 +680,103 @@ impl Layer {
         self.engine.prepend_layer(layer, self.id)
     }
 
+    /// Masks the layer and its sublayers with the alpha of another layer.
+    ///
+    /// The mask layer is attached to this layer, absolutely positioned in its
+    /// coordinate space, and rendered like any other layer (a gradient background
+    /// gives soft edges) but it is not drawn as a sublayer: only its alpha is used.
+    /// Passing `None` detaches the current mask layer, which can be reused.
+    ///
+    /// ```ignore
+    /// let fade = engine.new_layer();
+    /// fade.set_size(Size::percent(1.0, 1.0), None);
+    /// fade.set_background_color(vertical_fade_gradient, None);
+    /// scroll_view.set_mask(&fade)?;
+    /// ```
+    pub fn set_mask<'a>(
+        &self,
+        mask: impl Into<Option<&'a Layer>>,
+    ) -> Result<(), crate::layers::error::LayerError> {
+        let mask = mask.into();
+        let previous = self.model.mask.value();
+        if previous == mask.map(|mask| mask.id) {
+            return Ok(());
+        }
+        if let Some(mask) = mask {
+            let mut style = mask.node_layout_style();
+            style.position = taffy::style::Position::Absolute;
+            mask.set_layout_style(style);
+            self.add_sublayer(&mask.id)?;
+        }
+        if let Some(previous) = previous {
+            self.engine.remove_layer_from_parent(previous);
+        }
+        let mask = mask.map(|mask| mask.id);
+        let change = Arc::new(ValueChange {
+            value_change: self.model.mask.to(mask, None),
+            flag: RenderableFlags::NEEDS_PAINT,
+        });
+        if !self.engine.is_recording_transaction() {
+            self.model.mask.set(mask);
+        }
+        self.engine.invalidate_hit_test_node_list();
+        self.engine.schedule_change(self.id, change, None);
+        Ok(())
+    }
+    /// The layer masking this layer, if any
+    pub fn mask(&self) -> Option<Layer> {
+        let mask = self.model.mask.value()?;
+        self.engine.get_layer(&mask)
+    }
+
+    /// Sets an image drawn over the background color, clipped to the layer shape.
+    /// The image can be stretched, tiled or nine-sliced to fill the layer bounds.
+    pub fn set_background_image(&self, background_image: impl Into<Option<BackgroundImage>>) {
+        self.schedule_discrete_change(
+            &self.model.background_image,
+            background_image.into(),
+            RenderableFlags::NEEDS_PAINT,
+        );
+    }
+    pub fn background_image(&self) -> Option<BackgroundImage> {
+        self.model.background_image.value()
+    }
+
     pub fn set_blend_mode(&self, blend_mode: BlendMode) {
-        self.model.blend_mode.set(blend_mode);
-        let attribute_id = self.model.blend_mode.id;
-        self.engine
-            .schedule_change(self.id, Arc::new(NoopChange::paint(attribute_id)), None);
+        self.schedule_discrete_change(
+            &self.model.blend_mode,
+            blend_mode,
+            RenderableFlags::NEEDS_PAINT,
+        );
+    }
+    pub fn blend_mode(&self) -> BlendMode {
+        self.model.blend_mode.value()
+    }
+    /// Sets the border style. Dashed and dotted borders follow the layer shape,
+    /// their pattern is controlled by `border_dash_length` and `border_dash_gap`
+    /// (derived from the border width when 0.0) and shifted by the animatable
+    /// `border_dash_phase`.
+    pub fn set_border_style(&self, border_style: BorderStyle) {
+        self.schedule_discrete_change(
+            &self.model.border_style,
+            border_style,
+            RenderableFlags::NEEDS_PAINT,
+        );
+    }
+    pub fn border_style(&self) -> BorderStyle {
+        self.model.border_style.value()
+    }
+    /// When set, the sublayers are not flattened into the layer plane: they keep
+    /// their 3D transforms and are drawn back to front by depth.
+    pub fn set_preserve_3d(&self, preserve_3d: bool) {
+        self.schedule_discrete_change(
+            &self.model.preserve_3d,
+            preserve_3d,
+            RenderableFlags::NEEDS_LAYOUT | RenderableFlags::NEEDS_PAINT,
+        );
+    }
+    pub fn preserve_3d(&self) -> bool {
+        self.model.preserve_3d.value()
     }
     pub fn set_display(&self, display: Display) {
         self.model.display.set(display);
@@ -625,6 +1003,10 @@ impl Layer {
     pub fn remove(&self) {
         self.engine.mark_for_delete(self.id);
     }
+    /// Detaches the layer from its parent without deleting it
+    pub fn remove_from_parent(&self) {
+        self.engine.remove_layer_from_parent(self.id);
+    }
     pub fn set_effect(&self, effect: impl Effect + 'static) {
         let effect = Arc::new(effect);
         effect.init(self);
@@ -686,7 +1068,12 @@ impl Layer {
     pub fn set_content_opaque(&self, content_opaque: bool) {
         self.engine.scene.with_arena_mut(|arena| {
             if let Some(node) = arena.get_mut(self.id.0).filter(|n| !n.is_removed()) {
-                node.get_mut().set_content_opaque(content_opaque);
+                let node = node.get_mut();
+                if node.is_content_opaque() != content_opaque {
+                    node.set_content_opaque(content_opaque);
+                    // the node is updated so that the occlusion is updated with it
+                    node.insert_flags(RenderableFlags::NEEDS_PAINT);
+                }
             }
         });
     }
@@ -733,7 +1120,7 @@ impl Layer {
             scene_node.followers.iter().copied().collect()
         });
         self.mark_followers_needs_paint(&follower_ids);
-        let attribute_id = self.model.blend_mode.id;
+        let attribute_id = self.model.redraw_id;
         self.engine
             .schedule_change(self.id, Arc::new(NoopChange::paint(attribute_id)), None);
     }
@@ -753,7 +1140,7 @@ impl Layer {
             scene_node.followers.iter().copied().collect()
         });
         self.mark_followers_needs_paint(&follower_ids);
-        let attribute_id = self.model.blend_mode.id;
+        let attribute_id = self.model.redraw_id;
         self.engine
             .schedule_change(self.id, Arc::new(NoopChange::paint(attribute_id)), None);
     }
@@ -778,7 +1165,7 @@ impl Layer {
                 node.get_mut().followers.insert(follower);
             }
         });
-        let attribute_id = self.model.blend_mode.id;
+        let attribute_id = self.model.redraw_id;
         self.engine
             .schedule_change(self.id, Arc::new(NoopChange::layout(attribute_id)), None);
     }
@@ -811,7 +1198,16 @@ impl Layer {
             let damage = scene
                 .try_with_arena(|arena| {
                     scene.with_renderable_arena(|renderable_arena| {
-                        render_node_tree(layer_id, arena, renderable_arena, c, 1.0, None, None);
+                        render_node_tree_with_cache(
+                            layer_id,
+                            arena,
+                            renderable_arena,
+                            c,
+                            1.0,
+                            None,
+                            None,
+                            Some(scene.surface_cache()),
+                        );
                     });
                     // the damage of a mirrored layer is the bounds with children
                     if let Some(scene_node) = arena.get(layer_id.0) {
@@ -838,7 +1234,7 @@ impl Layer {
     pub fn redraw(&self) {
         self.engine
             .set_node_flags(self.id, RenderableFlags::NEEDS_PAINT);
-        let attribute_id = self.model.blend_mode.id;
+        let attribute_id = self.model.redraw_id;
         self.engine
             .schedule_change(self.id, Arc::new(NoopChange::paint(attribute_id)), None);
     }
@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     error::Error,
     hash::{Hash, Hasher},
     sync::{atomic::AtomicBool, Arc, RwLock},
@@ -8,7 +9,11 @@ use skia::{ColorFilter, ImageFilter};
 use taffy::style::Display;
 
 use crate::{
-    engine::command::Attribute,
+    engine::{
+        animation::Transition,
+        command::{next_attribute_id, Attribute},
+        NodeRef,
+    },
     shape::Shape,
     types::{BlendMode, Color, Point, *},
 };
@@ -108,25 +113,62 @@ pub(crate) struct ModelLayer {
     pub position: Attribute<Point>,
     pub scale: Attribute<Point>,
     pub rotation: Attribute<Point3d>,
+    /// Applied after scale and rotation, around the anchor point
+    pub transform: Attribute<Transform>,
+    /// Applied to the sublayers, around the perspective origin
+    pub sublayer_transform: Attribute<Transform>,
+    /// Distance of the viewer from the z=0 plane of the sublayers, 0.0 for no perspective
+    pub perspective: Attribute<f32>,
+    /// The vanishing point of the perspective and origin of the sublayer transform,
+    /// relative to the layer size
+    pub perspective_origin: Attribute<Point>,
+    /// When set the sublayers share the layer 3D space instead of being flattened into it
+    pub preserve_3d: Attribute<bool>,
     pub size: Attribute<Size>,
     pub background_color: Attribute<PaintColor>,
+    pub background_image: Attribute<Option<BackgroundImage>>,
     pub border_corner_radius: Attribute<BorderRadius>,
     pub border_color: Attribute<PaintColor>,
     pub border_width: Attribute<f32>,
+    pub border_style: Attribute<BorderStyle>,
+    pub border_dash_length: Attribute<f32>,
+    pub border_dash_gap: Attribute<f32>,
+    pub border_dash_phase: Attribute<f32>,
     pub shadow_offset: Attribute<Point>,
     pub shadow_radius: Attribute<f32>,
     pub shadow_spread: Attribute<f32>,
     pub shadow_color: Attribute<Color>,
+    /// Additional shadows, each one animated through its own attribute
+    pub shadows: Attribute<Vec<Attribute<Shadow>>>,
     pub draw_content: Arc<RwLock<Option<ContentDrawFunctionInternal>>>,
     pub blend_mode: Attribute<BlendMode>,
+    pub backdrop_effect: Attribute<BackdropEffect>,
     pub opacity: Attribute<f32>,
     pub image_filter: Attribute<Option<ImageFilter>>,
     pub color_filter: Attribute<Option<ColorFilter>>,
     pub filter_bounds: Arc<RwLock<Option<skia::Rect>>>,
     pub image_filter_progress: Attribute<f32>,
+    /// Declarative filters applied to the layer and its sublayers
+    pub filters: Attribute<Vec<Filter>>,
     pub clip_content: Attribute<bool>,
     pub clip_children: Attribute<bool>,
     pub shape: Arc<RwLock<Shape>>,
+    /// The layer whose alpha masks this layer and its sublayers
+    pub mask: Attribute<Option<NodeRef>>,
+    /// Value id of the changes that only request a repaint or a relayout of the layer
+    pub(crate) redraw_id: usize,
+    /// Transitions applied when an attribute is set without one, keyed by attribute name
+    pub(crate) implicit_transitions: RwLock<HashMap<String, Transition>>,
+}
+
+impl ModelLayer {
+    pub(crate) fn shadows(&self) -> Vec<Shadow> {
+        self.shadows
+            .value()
+            .iter()
+            .map(|shadow| shadow.value())
+            .collect()
+    }
 }
 
 impl Default for ModelLayer {
@@ -151,12 +193,17 @@ impl Default for ModelLayer {
             color: Color::new_rgba(0.0, 0.0, 0.0, 1.0),
         });
         let border_width = Attribute::new(0.0);
+        let border_style = Attribute::new(BorderStyle::Solid);
+        let border_dash_length = Attribute::new(0.0);
+        let border_dash_gap = Attribute::new(0.0);
+        let border_dash_phase = Attribute::new(0.0);
         let shadow_offset = Attribute::new(Point { x: 0.0, y: 0.0 });
         let shadow_radius = Attribute::new(0.0);
         let shadow_spread = Attribute::new(0.0);
         let shadow_color = Attribute::new(Color::new_rgba(0.0, 0.0, 0.0, 0.0));
         let content = Arc::new(RwLock::new(None));
         let blend_mode = Attribute::new(BlendMode::Normal);
+        let backdrop_effect = Attribute::new(BackdropEffect::default());
         let opacity = Attribute::new(1.0);
         let display = Attribute::new(Display::None);
         let image_filter = Attribute::new(None);
@@ -178,25 +225,41 @@ impl Default for ModelLayer {
             position,
             scale,
             rotation,
+            transform: Attribute::new(Transform::default()),
+            sublayer_transform: Attribute::new(Transform::default()),
+            perspective: Attribute::new(0.0),
+            perspective_origin: Attribute::new(Point { x: 0.5, y: 0.5 }),
+            preserve_3d: Attribute::new(false),
             size,
             background_color,
+            background_image: Attribute::new(None),
             border_corner_radius,
             border_color,
             border_width,
+            border_style,
+            border_dash_length,
+            border_dash_gap,
+            border_dash_phase,
             shadow_offset,
             shadow_radius,
             shadow_spread,
             shadow_color,
+            shadows: Attribute::new(Vec::new()),
             draw_content: content,
             blend_mode,
+            backdrop_effect,
             opacity,
             image_filter,
             color_filter,
             image_filter_progress: filter_progress,
             filter_bounds,
+            filters: Attribute::new(Vec::new()),
             clip_content,
             clip_children,
             shape,
+            mask: Attribute::new(None),
+            redraw_id: next_attribute_id(),
+            implicit_transitions: RwLock::new(HashMap::new()),
         }
     }
 }
@@ -1,5 +1,6 @@
 use super::model::{ContentDrawFunctionInternal, ModelLayer};
 use crate::{
+    engine::NodeRef,
     shape::Shape,
     types::{BlendMode, Color, Point, *},
 };
@@ -8,6 +9,12 @@ use skia_safe::Contains;
 use serde::{ser::SerializeStruct, Serialize};
 use skia::{ColorFilter, ImageFilter};
 
+/// The relative size under which a transform coefficient is considered zero
+/// when checking that a layer stays axis-aligned
+const AXIS_ALIGNED_TOLERANCE: f32 = 1e-5;
+/// The bisection steps searching a rect inside a custom shape
+const PATH_INNER_RECT_STEPS: usize = 6;
+
 #[derive(Clone, Debug)]
 #[repr(C)]
 pub struct RenderLayer {
@@ -35,12 +42,20 @@ pub struct RenderLayer {
     pub global_transformed_bounds_with_children: skia_safe::Rect,
     /// The background color of the layer
     pub background_color: PaintColor,
+    /// The image drawn over the background color
+    pub background_image: Option<BackgroundImage>,
     /// The border color of the layer
     pub border_color: PaintColor,
     /// The border width of the layer
     pub border_width: f32,
     /// The border style of the layer
     pub border_style: BorderStyle,
+    /// The length of the border dashes, 0.0 to derive it from the border width
+    pub border_dash_length: f32,
+    /// The gap between the border dashes, 0.0 to derive it from the border width
+    pub border_dash_gap: f32,
+    /// The offset of the border dash pattern along the shape
+    pub border_dash_phase: f32,
     /// The border corner radius of the layer
     pub border_corner_radius: BorderRadius,
     /// The size of the layer
@@ -53,14 +68,24 @@ pub struct RenderLayer {
     pub shadow_color: Color,
     /// The shadow spread of the layer
     pub shadow_spread: f32,
+    /// Additional outer and inset shadows, the first one is drawn on top
+    pub shadows: Vec<Shadow>,
     /// The transform of the layer relative to the root (4x4)
     pub transform: M44,
     /// The transform of the layer relative to the root (3x3)
     pub transform_33: Matrix,
     /// The transform of the layer relative to the parent (4x4)
     pub local_transform: M44,
+    /// The transform applied to the sublayers, in the layer coordinates (the perspective)
+    pub sublayer_transform: M44,
+    /// The sublayers keep their 3D transforms and are sorted by depth
+    pub preserve_3d: bool,
+    /// The point the layer is positioned and transformed around, relative to its size
+    pub anchor_point: Point,
     /// The blend mode of the layer
     pub blend_mode: BlendMode,
+    /// The material drawn behind the layer when the blend mode is BackgroundBlur
+    pub backdrop_effect: BackdropEffect,
     /// The opacity of the layer, 0.0 is transparent, 1.0 is opaque
     pub opacity: f32,
     /// The premultiplied opacity of the layer, 0.0 is transparent, 1.0 is opaque
@@ -78,12 +103,17 @@ pub struct RenderLayer {
     pub image_filter: Option<ImageFilter>,
     pub image_filter_bounds: Option<skia::Rect>,
     pub color_filter: Option<ColorFilter>,
+    /// Declarative filters applied to the layer and its sublayers, compiled at draw time
+    pub filters: Vec<Filter>,
     /// Rounded rectangles of descendants with BackgroundBlur blend mode (in local coordinates)
+    /// and their backdrop effect.
     /// Used by image-cached layers to apply backdrop blur to specific regions
     /// Stored as Vec<RRect> for thread safety, converted to Path during rendering
-    pub backdrop_blur_region: Option<Vec<skia_safe::RRect>>,
+    pub backdrop_blur_region: Option<Vec<(skia_safe::RRect, BackdropEffect)>>,
     /// The shape definition for this layer
     pub shape: Shape,
+    /// The layer whose alpha masks this layer and its sublayers
+    pub mask: Option<NodeRef>,
     /// Hint from the user that the custom draw content fills the entire bounds
     /// with opaque pixels. When true, the layer can act as an occluder even if
     /// its background color is transparent.
@@ -96,6 +126,7 @@ impl RenderLayer {
         model: &ModelLayer,
         layout: &taffy::tree::Layout,
         context_transform: Option<&M44>,
+        context_sublayer_transform: Option<&M44>,
         context_opacity: f32,
     ) {
         let key = model.key.read().unwrap().clone();
@@ -161,7 +192,12 @@ impl RenderLayer {
         let transform = M44::concat(&local_transform, &rotate_x);
         let transform = M44::concat(&transform, &rotate_y);
         let transform = M44::concat(&transform, &rotate_z);
+        let transform = M44::concat(&transform, &model.transform.value().to_m44());
         local_transform = M44::concat(&transform, &anchor_translate);
+        // the parent sublayer transform applies in the parent coordinates
+        if let Some(sublayer_transform) = context_sublayer_transform {
+            local_transform = M44::concat(sublayer_transform, &local_transform);
+        }
 
         let global_transform = M44::concat(matrix, &local_transform);
         let (transformed_bounds, _) = global_transform.to_m33().map_rect(bounds);
@@ -206,18 +242,34 @@ impl RenderLayer {
         self.key = key;
         self.size = size;
         self.background_color = background_color;
+        self.background_image = model.background_image.value();
         self.border_color = border_color;
         self.border_width = border_width;
+        self.border_style = model.border_style.value();
+        self.border_dash_length = model.border_dash_length.value();
+        self.border_dash_gap = model.border_dash_gap.value();
+        self.border_dash_phase = model.border_dash_phase.value();
 
         self.border_corner_radius = border_corner_radius;
         self.shadow_offset = shadow_offset;
         self.shadow_radius = shadow_radius;
         self.shadow_color = shadow_color;
         self.shadow_spread = shadow_spread;
+        self.shadows = model.shadows();
         self.transform = global_transform;
         self.transform_33 = self.transform.to_m33();
         self.local_transform = local_transform;
+        self.sublayer_transform = Self::compose_sublayer_transform(
+            model.perspective.value(),
+            model.perspective_origin.value(),
+            &model.sublayer_transform.value(),
+            size,
+        );
+        self.preserve_3d = model.preserve_3d.value();
+        self.anchor_point = anchor_point;
         self.blend_mode = blend_mode;
+        self.mask = model.mask.value();
+        self.backdrop_effect = model.backdrop_effect.value();
         self.opacity = opacity;
         self.premultiplied_opacity = opacity * context_opacity;
         self.bounds = bounds;
@@ -248,6 +300,7 @@ impl RenderLayer {
             .load(std::sync::atomic::Ordering::Relaxed);
 
         self.image_filter = model.image_filter.value();
+        self.filters = model.filters.value();
         self.image_filter_bounds = *model.filter_bounds.read().unwrap();
         self.color_filter = model.color_filter.value();
     }
@@ -257,57 +310,38 @@ impl RenderLayer {
             return false;
         }
 
-        let draws_background = Self::paint_color_max_alpha(&self.background_color) > 0.0;
+        let draws_background = Self::paint_color_max_alpha(&self.background_color) > 0.0
+            || self.background_image.is_some();
         let draws_border =
             self.border_width > 0.0 && Self::paint_color_max_alpha(&self.border_color) > 0.0;
-        let draws_shadow = self.shadow_color.alpha > 0.0;
+        let draws_shadow = self.shadow_color.alpha > 0.0
+            || self.shadows.iter().any(|shadow| shadow.color.alpha > 0.0);
         let draws_content = self.content.is_some() || self.content_draw_func.is_some();
 
         draws_background || draws_border || draws_shadow || draws_content
     }
 
     fn paint_color_max_alpha(color: &PaintColor) -> f32 {
-        match color {
-            PaintColor::Solid { color } => color.alpha,
-            PaintColor::GradientLinear(gradient) => {
-                gradient.colors.iter().fold(0.0, |acc, c| acc.max(c.alpha))
-            }
-            PaintColor::GradientRadial(gradient) => {
-                gradient.colors.iter().fold(0.0, |acc, c| acc.max(c.alpha))
-            }
-        }
+        color.colors().iter().fold(0.0, |acc, c| acc.max(c.alpha))
     }
 
     fn paint_color_min_alpha(color: &PaintColor) -> f32 {
         match color {
-            PaintColor::Solid { color } => color.alpha,
-            PaintColor::GradientLinear(gradient) => {
-                gradient.colors.iter().fold(1.0, |acc, c| acc.min(c.alpha))
-            }
-            PaintColor::GradientRadial(gradient) => {
-                gradient.colors.iter().fold(1.0, |acc, c| acc.min(c.alpha))
-            }
+            // decal gradients are transparent outside of their geometry
+            PaintColor::GradientLinear(g) if g.tile_mode == TileMode::Decal => 0.0,
+            PaintColor::GradientRadial(g) if g.tile_mode == TileMode::Decal => 0.0,
+            PaintColor::GradientConic(g) if g.tile_mode == TileMode::Decal => 0.0,
+            // outside of the cones nothing is drawn
+            PaintColor::GradientTwoPointConical(_) => 0.0,
+            _ if color.colors().is_empty() => 0.0,
+            _ => color.colors().iter().fold(1.0, |acc, c| acc.min(c.alpha)),
         }
     }
 
     /// Returns true if this layer is fully opaque and can act as an occluder,
     /// meaning it completely hides any content behind its bounds.
     pub fn is_fully_opaque(&self) -> bool {
-        // Must be fully opaque (including parent opacity chain)
-        if self.premultiplied_opacity < 1.0 {
-            return false;
-        }
-
-        // BackgroundBlur reads from the backdrop, so it can't occlude
-        if self.blend_mode != BlendMode::Normal {
-            return false;
-        }
-
-        // Either the background is fully opaque, or the user declared the
-        // custom draw content fills the bounds with opaque pixels.
-        let has_opaque_fill =
-            self.content_opaque || Self::paint_color_min_alpha(&self.background_color) >= 1.0;
-        if !has_opaque_fill {
+        if !self.has_opaque_fill() {
             return false;
         }
 
@@ -328,6 +362,122 @@ impl RenderLayer {
         true
     }
 
+    /// Returns the rect, in global coordinates, that this layer completely
+    /// hides when it is drawn. It is a conservative rect inside the layer shape:
+    /// the inner rect of the rounded corners or of a convex custom shape.
+    /// Returns None when the layer can't act as an occluder, or when its
+    /// transform doesn't map it to an axis-aligned rect.
+    pub fn global_opaque_bounds(&self) -> Option<skia_safe::Rect> {
+        if !self.has_opaque_fill() || !Self::is_axis_aligned(&self.transform_33) {
+            return None;
+        }
+        let mut local_bounds = match &self.shape {
+            Shape::RoundRect => Self::rrect_inner_rect(&self.rbounds),
+            Shape::Path(_) => Self::path_inner_rect(&self.shape_path())?,
+        };
+        // the content declared opaque only fills the bounds
+        if !local_bounds.intersect(self.bounds) {
+            return None;
+        }
+        let (bounds, _) = self.transform_33.map_rect(local_bounds);
+        Some(bounds)
+    }
+
+    /// Whether the layer paints opaque pixels over its whole shape
+    fn has_opaque_fill(&self) -> bool {
+        // Must be fully opaque (including parent opacity chain)
+        if self.premultiplied_opacity < 1.0 {
+            return false;
+        }
+
+        // BackgroundBlur and the blend modes read from the backdrop, so they can't occlude
+        if self.blend_mode != BlendMode::Normal {
+            return false;
+        }
+
+        // A projected layer doesn't fill its bounding rect
+        if self.transform_33.has_perspective() {
+            return false;
+        }
+
+        // The mask and the filters can make any part of the layer transparent
+        if self.mask.is_some() || !self.filters.is_empty() {
+            return false;
+        }
+
+        // Either the background is fully opaque, or the user declared the
+        // custom draw content fills the bounds with opaque pixels.
+        self.content_opaque || Self::paint_color_min_alpha(&self.background_color) >= 1.0
+    }
+
+    /// Whether the matrix maps rects to rects: translations, scales and
+    /// rotations by multiples of 90 degrees. Skia requires the other
+    /// coefficients to be exactly zero, while a rotation by 90 degrees
+    /// leaves a rounding error in them.
+    fn is_axis_aligned(matrix: &skia_safe::Matrix) -> bool {
+        if matrix.has_perspective() {
+            return false;
+        }
+        let (scale_x, scale_y) = (matrix.scale_x(), matrix.scale_y());
+        let (skew_x, skew_y) = (matrix.skew_x(), matrix.skew_y());
+        let tolerance = scale_x
+            .abs()
+            .max(scale_y.abs())
+            .max(skew_x.abs())
+            .max(skew_y.abs())
+            * AXIS_ALIGNED_TOLERANCE;
+        let is_zero = |v: f32| v.abs() <= tolerance;
+        let scaled = is_zero(skew_x) && is_zero(skew_y) && !is_zero(scale_x) && !is_zero(scale_y);
+        let rotated = is_zero(scale_x) && is_zero(scale_y) && !is_zero(skew_x) && !is_zero(skew_y);
+        scaled || rotated
+    }
+
+    /// The rect inside a rounded rect that excludes its corners: every side
+    /// is inset to the point of its corner arcs at 45 degrees.
+    fn rrect_inner_rect(rrect: &skia_safe::RRect) -> skia_safe::Rect {
+        use skia_safe::rrect::Corner;
+        let inset = 1.0 - std::f32::consts::FRAC_1_SQRT_2;
+        let upper_left = rrect.radii(Corner::UpperLeft);
+        let upper_right = rrect.radii(Corner::UpperRight);
+        let lower_right = rrect.radii(Corner::LowerRight);
+        let lower_left = rrect.radii(Corner::LowerLeft);
+        let rect = rrect.rect();
+        skia_safe::Rect::from_ltrb(
+            rect.left + upper_left.x.max(lower_left.x) * inset,
+            rect.top + upper_left.y.max(upper_right.y) * inset,
+            rect.right - upper_right.x.max(lower_right.x) * inset,
+            rect.bottom - lower_left.y.max(lower_right.y) * inset,
+        )
+    }
+
+    /// A rect inside a custom shape, found by shrinking the shape bounds
+    /// around their center until the path contains them. Skia only
+    /// confirms the containment for convex paths, for the others there
+    /// is no rect.
+    fn path_inner_rect(path: &skia_safe::Path) -> Option<skia_safe::Rect> {
+        let bounds = *path.bounds();
+        if path.conservatively_contains_rect(bounds) {
+            return Some(bounds);
+        }
+        let inset = |fraction: f32| {
+            bounds.with_inset((bounds.width() * fraction, bounds.height() * fraction))
+        };
+        // bisect the inset, as a fraction of the bounds size: half of it is an empty rect
+        let (mut outside, mut inside) = (0.0, 0.5);
+        let mut inner_rect = None;
+        for _ in 0..PATH_INNER_RECT_STEPS {
+            let fraction = (outside + inside) / 2.0;
+            let rect = inset(fraction);
+            if path.conservatively_contains_rect(rect) {
+                inside = fraction;
+                inner_rect = Some(rect);
+            } else {
+                outside = fraction;
+            }
+        }
+        inner_rect
+    }
+
     /// Generate the shape path in local coordinates on-demand.
     /// Called during drawing to avoid caching non-Sync Skia Path.
     pub fn shape_path(&self) -> skia_safe::Path {
@@ -459,6 +609,7 @@ impl RenderLayer {
         let transform = M44::concat(&transform, &rotate_x);
         let transform = M44::concat(&transform, &rotate_y);
         let transform = M44::concat(&transform, &rotate_z);
+        let transform = M44::concat(&transform, &model.transform.value().to_m44());
         let transform = M44::concat(&transform, &anchor_translate);
 
         let transform_33 = transform.to_m33();
@@ -501,19 +652,34 @@ impl RenderLayer {
             key,
             size,
             background_color,
+            background_image: model.background_image.value(),
             border_color,
             border_width,
-            border_style: BorderStyle::Solid,
+            border_style: model.border_style.value(),
+            border_dash_length: model.border_dash_length.value(),
+            border_dash_gap: model.border_dash_gap.value(),
+            border_dash_phase: model.border_dash_phase.value(),
             border_corner_radius,
             shadow_offset,
             shadow_radius,
             shadow_color,
             shadow_spread,
+            shadows: model.shadows(),
             local_transform,
+            sublayer_transform: Self::compose_sublayer_transform(
+                model.perspective.value(),
+                model.perspective_origin.value(),
+                &model.sublayer_transform.value(),
+                size,
+            ),
+            preserve_3d: model.preserve_3d.value(),
+            anchor_point,
             transform,
             transform_33,
             content,
             blend_mode,
+            backdrop_effect: model.backdrop_effect.value(),
+            mask: model.mask.value(),
             opacity,
             premultiplied_opacity,
             bounds,
@@ -531,6 +697,7 @@ impl RenderLayer {
             clip_content,
             clip_children,
             image_filter: model.image_filter.value(),
+            filters: model.filters.value(),
             image_filter_bounds: *model.filter_bounds.read().unwrap(),
             color_filter: model.color_filter.value(),
             pointer_events: model
@@ -544,8 +711,65 @@ impl RenderLayer {
         render_layer.visible = render_layer.has_visible_drawables();
         render_layer
     }
+    /// The dash intervals used to stroke the border, None for solid borders.
+    /// Dotted borders use zero length dashes drawn with round caps.
+    pub fn border_dash_intervals(&self) -> Option<[f32; 2]> {
+        let width = self.border_width.max(1.0);
+        match self.border_style {
+            BorderStyle::Solid => None,
+            BorderStyle::Dashed => {
+                let length = if self.border_dash_length > 0.0 {
+                    self.border_dash_length
+                } else {
+                    width * 3.0
+                };
+                let gap = if self.border_dash_gap > 0.0 {
+                    self.border_dash_gap
+                } else {
+                    width * 2.0
+                };
+                Some([length, gap])
+            }
+            BorderStyle::Dotted => {
+                let gap = if self.border_dash_gap > 0.0 {
+                    self.border_dash_gap
+                } else {
+                    width * 2.0
+                };
+                Some([self.border_dash_length.max(0.0), gap])
+            }
+        }
+    }
     pub fn has_filters(&self) -> bool {
-        self.image_filter.is_some() || self.color_filter.is_some()
+        self.image_filter.is_some() || self.color_filter.is_some() || !self.filters.is_empty()
+    }
+
+    /// The transform applied to the sublayers: a perspective at `distance` from the
+    /// z=0 plane (none when 0.0) followed by the layer `sublayer_transform`,
+    /// both around `origin` (relative to `size`).
+    pub(crate) fn compose_sublayer_transform(
+        distance: f32,
+        origin: Point,
+        sublayer_transform: &Transform,
+        size: skia_safe::Size,
+    ) -> M44 {
+        if distance <= 0.0 && sublayer_transform.operations.is_empty() {
+            return M44::new_identity();
+        }
+        let origin_x = origin.x * size.width;
+        let origin_y = origin.y * size.height;
+        let mut perspective = M44::new_identity();
+        if distance > 0.0 {
+            perspective.set_rc(3, 2, -1.0 / distance);
+        }
+        let transform = M44::concat(&M44::translate(origin_x, origin_y, 0.0), &perspective);
+        let transform = M44::concat(&transform, &sublayer_transform.to_m44());
+        M44::concat(&transform, &M44::translate(-origin_x, -origin_y, 0.0))
+    }
+
+    /// How far the filters draw outside of the layer subtree
+    pub(crate) fn filters_outset(filters: &[Filter]) -> f32 {
+        filters.iter().map(Filter::outset).sum()
     }
 
     pub(crate) fn is_layout_only_passthrough(&self) -> bool {
@@ -554,6 +778,7 @@ impl RenderLayer {
             && !self.clip_content
             && !self.clip_children
             && self.blend_mode == BlendMode::Normal
+            && self.mask.is_none()
     }
 }
 
@@ -564,22 +789,32 @@ impl Default for RenderLayer {
             background_color: PaintColor::Solid {
                 color: Color::new_rgba(0.0, 0.0, 0.0, 0.0),
             },
+            background_image: None,
             border_color: PaintColor::Solid {
                 color: Color::new_rgba(0.0, 0.0, 0.0, 0.0),
             },
             border_width: 0.0,
             border_style: BorderStyle::Solid,
+            border_dash_length: 0.0,
+            border_dash_gap: 0.0,
+            border_dash_phase: 0.0,
             border_corner_radius: BorderRadius::new_single(0.0),
             size: skia_safe::Size::default(),
             shadow_offset: Point { x: 0.0, y: 0.0 },
             shadow_radius: 0.0,
             shadow_color: Color::new_rgba(0.0, 0.0, 0.0, 0.0),
             shadow_spread: 0.0,
+            shadows: Vec::new(),
             transform: M44::new_identity(),
             transform_33: Matrix::default(),
             local_transform: M44::new_identity(),
+            sublayer_transform: M44::new_identity(),
+            preserve_3d: false,
+            anchor_point: Point { x: 0.0, y: 0.0 },
             content: None,
             blend_mode: BlendMode::Normal,
+            backdrop_effect: BackdropEffect::default(),
+            mask: None,
             opacity: 1.0,
             premultiplied_opacity: 1.0,
             bounds: skia_safe::Rect::default(),
@@ -597,6 +832,7 @@ impl Default for RenderLayer {
             clip_content: false,
             clip_children: false,
             image_filter: None,
+            filters: Vec::new(),
             image_filter_bounds: None,
             color_filter: None,
             pointer_events: false,
@@ -612,7 +848,7 @@ impl Serialize for RenderLayer {
     where
         S: serde::Serializer,
     {
-        let mut seq = serializer.serialize_struct("RenderLayer", 22)?;
+        let mut seq = serializer.serialize_struct("RenderLayer", 28)?;
         // let mut seq = serializer.serialize_seq(Some(15))?;
         // seq.serialize_element(&Rectangle::from(self.rbounds))?;
         // seq.serialize_element(&self.transformed_rbounds.into())?;
@@ -627,6 +863,10 @@ impl Serialize for RenderLayer {
             &Rectangle::from(self.global_transformed_bounds_with_children),
         )?;
         seq.serialize_field("background_color", &self.background_color)?;
+        seq.serialize_field(
+            "background_image",
+            &self.background_image.as_ref().map(|image| image.mode),
+        )?;
         seq.serialize_field("border_color", &self.border_color)?;
         seq.serialize_field("border_width", &self.border_width)?;
         seq.serialize_field("border_style", &self.border_style)?;
@@ -636,8 +876,11 @@ impl Serialize for RenderLayer {
         seq.serialize_field("shadow_radius", &self.shadow_radius)?;
         seq.serialize_field("shadow_color", &self.shadow_color)?;
         seq.serialize_field("shadow_spread", &self.shadow_spread)?;
+        seq.serialize_field("shadows", &self.shadows)?;
         seq.serialize_field("blend_mode", &self.blend_mode)?;
+        seq.serialize_field("backdrop_effect", &self.backdrop_effect)?;
         seq.serialize_field("opacity", &self.opacity)?;
+        seq.serialize_field("preserve_3d", &self.preserve_3d)?;
         seq.serialize_field("visible", &self.visible)?;
         seq.serialize_field("shape", &self.shape)?;
         seq.serialize_field("image_filter", &self.image_filter.is_some())?;
@@ -646,6 +889,8 @@ impl Serialize for RenderLayer {
             &self.image_filter_bounds.map(Rectangle::from),
         )?;
         seq.serialize_field("color_filter", &self.color_filter.is_some())?;
+        seq.serialize_field("filters", &self.filters)?;
+        seq.serialize_field("mask", &self.mask.is_some())?;
         seq.serialize_field(
             "backdrop_blur_regions",
             &self.backdrop_blur_region.as_ref().map(|v| v.len()),
@@ -1,6 +1,6 @@
-pub use super::drawing::scene::{draw_scene, render_node_tree};
+pub use super::drawing::scene::{draw_scene, render_node_tree, render_node_tree_with_cache};
 pub use super::{
-    drawing::scene::DrawScene,
+    drawing::{scene::DrawScene, DebugOverlays},
     easing::Interpolate,
     engine::{
         animation::*,
@@ -13,10 +13,13 @@ pub use super::{
         layer::model::{ContentDrawError, ContentDrawFunction, PointerHandlerFunction},
         layer::Effect,
         layer::Layer,
+        layer::IMPLICIT_TRANSITION_ATTRIBUTES,
     },
     shape::Shape,
     types::{
-        BlendMode, BorderRadius, BorderStyle, Color, Image, Matrix, PaintColor, Point, Rectangle,
+        BackdropEffect, BackgroundImage, BackgroundImageMode, BlendMode, BorderRadius, BorderStyle,
+        Color, Filter, Image, Matrix, PaintColor, Point, Rectangle, Shadow, Transform,
+        TransformOperation,
     },
     view::{BuildLayerTree, LayerTree, LayerTreeBuilder, RenderLayerTree, View},
 };
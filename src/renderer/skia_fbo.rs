@@ -12,10 +12,12 @@ use skia_safe::{
 use std::{cell::Cell, io::Write};
 
 use crate::{
-    drawing::scene::set_node_transform,
-    engine::{node::SceneNode, scene::Scene, NodeRef},
+    drawing::scene::DrawScene, layers::layer::render_layer, prelude::render_node_tree_with_cache,
+};
+use crate::{
+    drawing::{debug_overlays::draw_debug_overlays, scene::set_node_transform},
+    engine::{node::SceneNode, scene::Scene, Engine, NodeRef},
 };
-use crate::{drawing::scene::DrawScene, layers::layer::render_layer, prelude::render_node_tree};
 use std::collections::HashSet;
 
 #[derive(Clone)]
@@ -112,6 +114,7 @@ impl DrawScene for SkiaFboRenderer {
         root_id: NodeRef,
         damage: Option<skia_safe::Rect>,
     ) {
+        scene.surface_cache().release_removed();
         let mut surface = self.surface();
         let mut canvas = surface.canvas();
         let save_point = canvas.save();
@@ -124,19 +127,31 @@ impl DrawScene for SkiaFboRenderer {
                     let root = root.get();
                     set_node_transform(root, canvas);
                     let occluded = scene.occluded_set(root_id);
-                    render_node_tree(
+                    render_node_tree_with_cache(
                         root_id,
                         arena,
                         renderable_arena,
                         &mut canvas,
                         1.0,
-                        occluded.as_ref(),
+                        occluded.as_deref(),
                         None,
+                        Some(scene.surface_cache()),
                     );
                 }
             });
         });
         canvas.restore_to_count(save_point);
+        scene.with_arena(|arena| {
+            scene.with_renderable_arena(|renderable_arena| {
+                let engine = arena
+                    .get(root_id.into())
+                    .and_then(|root| root.get().engine_id)
+                    .and_then(Engine::get_by_id);
+                if let Some(engine) = engine {
+                    draw_debug_overlays(&engine, root_id, arena, renderable_arena, canvas);
+                }
+            });
+        });
         // surface.flush_and_submit();
 
         // Save the scene to a Skia debug file
@@ -1,5 +1,5 @@
 use serde::Serialize;
-use skia_safe::{Path, RRect, Rect};
+use skia_safe::{ContourMeasure, ContourMeasureIter, Path, Point, RRect, Rect, Vector};
 
 use crate::types::BorderRadius;
 
@@ -54,6 +54,40 @@ impl PathData {
     }
 }
 
+/// The first contour of a path, measured once so that it can be sampled
+/// every frame without rebuilding the measure.
+///
+/// The underlying `ContourMeasure` is reference counted and immutable, so
+/// clones are cheap and can be shared across threads.
+#[derive(Debug, Clone)]
+pub struct MeasuredPath {
+    measure: Option<ContourMeasure>,
+    length: f32,
+}
+
+impl MeasuredPath {
+    /// Measure the first contour of `path`
+    pub fn new(path: &Path) -> Self {
+        let measure = ContourMeasureIter::new(path, false, None).next();
+        let length = measure.as_ref().map(|m| m.length()).unwrap_or(0.0);
+        Self { measure, length }
+    }
+
+    /// Length of the measured contour
+    pub fn length(&self) -> f32 {
+        self.length
+    }
+
+    /// Position and unit tangent at `progress` of the length of the path.
+    ///
+    /// Progress outside `0.0..=1.0` (e.g. a bouncing spring) is clamped to
+    /// the path ends.
+    pub fn pos_tan_at(&self, progress: f32) -> Option<(Point, Vector)> {
+        let measure = self.measure.as_ref().filter(|_| self.length > 0.0)?;
+        measure.pos_tan(progress.clamp(0.0, 1.0) * self.length)
+    }
+}
+
 impl Shape {
     /// Generate a Skia path from this shape definition.
     ///
@@ -94,7 +94,7 @@ impl Sub for &Point {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Default)]
 #[repr(C)]
 pub struct Point3d {
     pub x: f32,
@@ -121,17 +121,102 @@ impl From<skia_safe::Rect> for Rectangle {
     }
 }
 
-#[derive(Clone, Serialize, Debug)]
+/// How a gradient is extended outside of its start and end
+#[derive(Clone, Copy, Default, Serialize, Debug, PartialEq)]
+#[repr(u32)]
+pub enum TileMode {
+    /// Repeat the edge colors
+    #[default]
+    Clamp,
+    /// Repeat the gradient
+    Repeat,
+    /// Repeat the gradient, mirroring it every other time
+    Mirror,
+    /// Transparent outside of the gradient
+    Decal,
+}
+
+/// The direction of a linear gradient
+#[derive(Clone, Copy, Serialize, Debug)]
+pub enum GradientDirection {
+    /// From `start` to `end`, in layer coordinates
+    Points { start: Point, end: Point },
+    /// An angle in degrees like css linear-gradient: 0.0 goes bottom to top,
+    /// 90.0 left to right. The gradient line spans the layer bounds.
+    Angle(f32),
+}
+
+impl Default for GradientDirection {
+    fn default() -> Self {
+        // top to bottom
+        GradientDirection::Angle(180.0)
+    }
+}
+
+/// Colors and stop positions in `points` (0.0 to 1.0) are shared by all the gradients.
+/// When `points` doesn't match the number of colors, the colors are evenly spaced.
+#[derive(Clone, Serialize, Debug, Default)]
 pub struct GradientLinear {
     pub colors: Vec<Color>,
     pub points: Vec<f32>,
+    pub direction: GradientDirection,
+    pub tile_mode: TileMode,
+}
+impl GradientLinear {
+    /// A top to bottom gradient, clamped at the edges
+    pub fn new(colors: Vec<Color>, points: Vec<f32>) -> Self {
+        GradientLinear {
+            colors,
+            points,
+            ..Default::default()
+        }
+    }
 }
-#[derive(Clone, Serialize, Debug)]
+#[derive(Clone, Serialize, Debug, Default)]
 pub struct GradientRadial {
     pub center: Point,
     pub radius: f32,
     pub colors: Vec<Color>,
-    pub points: Vec<Point>,
+    pub points: Vec<f32>,
+    pub tile_mode: TileMode,
+}
+impl GradientRadial {
+    /// A gradient from `center` to `radius`, clamped at the edges
+    pub fn new(
+        center: impl Into<Point>,
+        radius: f32,
+        colors: Vec<Color>,
+        points: Vec<f32>,
+    ) -> Self {
+        GradientRadial {
+            center: center.into(),
+            radius,
+            colors,
+            points,
+            tile_mode: TileMode::Clamp,
+        }
+    }
+}
+/// A sweep gradient around `center`, starting at `start_angle` degrees
+/// (0.0 is the positive x axis, clockwise)
+#[derive(Clone, Serialize, Debug, Default)]
+pub struct GradientConic {
+    pub center: Point,
+    pub start_angle: f32,
+    pub colors: Vec<Color>,
+    pub points: Vec<f32>,
+    pub tile_mode: TileMode,
+}
+/// A gradient between two circles
+#[derive(Clone, Serialize, Debug, Default)]
+pub struct GradientTwoPointConical {
+    pub start: Point,
+    pub start_radius: f32,
+    pub end: Point,
+    pub end_radius: f32,
+    pub colors: Vec<Color>,
+    pub points: Vec<f32>,
+    pub tile_mode: TileMode,
 }
 
 #[allow(dead_code)]
@@ -141,6 +226,20 @@ pub enum PaintColor {
     Solid { color: Color },
     GradientLinear(Box<GradientLinear>),
     GradientRadial(Box<GradientRadial>),
+    GradientConic(Box<GradientConic>),
+    GradientTwoPointConical(Box<GradientTwoPointConical>),
+}
+impl PaintColor {
+    /// The colors of the gradient stops, or the solid color
+    pub fn colors(&self) -> &[Color] {
+        match self {
+            PaintColor::Solid { color } => std::slice::from_ref(color),
+            PaintColor::GradientLinear(gradient) => &gradient.colors,
+            PaintColor::GradientRadial(gradient) => &gradient.colors,
+            PaintColor::GradientConic(gradient) => &gradient.colors,
+            PaintColor::GradientTwoPointConical(gradient) => &gradient.colors,
+        }
+    }
 }
 impl Default for PaintColor {
     fn default() -> Self {
@@ -150,7 +249,7 @@ impl Default for PaintColor {
     }
 }
 #[allow(dead_code)]
-#[derive(Clone, Copy, Default, Serialize, Debug)]
+#[derive(Clone, Copy, Default, Serialize, Debug, PartialEq)]
 #[repr(u32)]
 pub enum BorderStyle {
     #[default]
@@ -159,6 +258,57 @@ pub enum BorderStyle {
     Dashed,
 }
 
+/// A shadow drawn following the layer shape, either outside of it or,
+/// when `inset` is set, inside of it.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[repr(C)]
+pub struct Shadow {
+    pub offset: Point,
+    /// The blur radius of the shadow
+    pub radius: f32,
+    /// Grows (or shrinks when negative) the shadow shape
+    pub spread: f32,
+    pub color: Color,
+    pub inset: bool,
+}
+
+impl Shadow {
+    pub fn new(offset: impl Into<Point>, radius: f32, spread: f32, color: Color) -> Self {
+        Shadow {
+            offset: offset.into(),
+            radius,
+            spread,
+            color,
+            inset: false,
+        }
+    }
+    pub fn inset(offset: impl Into<Point>, radius: f32, spread: f32, color: Color) -> Self {
+        Shadow {
+            inset: true,
+            ..Shadow::new(offset, radius, spread, color)
+        }
+    }
+    /// The same shadow with a transparent color, used to fade shadows in
+    pub fn transparent(&self) -> Self {
+        Shadow {
+            color: self.color.opacity(0.0),
+            ..*self
+        }
+    }
+}
+
+impl Default for Shadow {
+    fn default() -> Self {
+        Shadow {
+            offset: Point::default(),
+            radius: 0.0,
+            spread: 0.0,
+            color: Color::new_rgba(0.0, 0.0, 0.0, 0.0),
+            inset: false,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, diff::Diff, Serialize)]
 #[repr(C)]
 pub struct BorderRadius {
@@ -260,12 +410,446 @@ impl Default for Point {
     }
 }
 
+/// A declarative filter applied to a layer and its sublayers.
+///
+/// Layers take a list of filters, applied in order, that can be animated like
+/// any other attribute: filters of the same kind interpolate their parameters.
+/// The amounts follow the CSS filter functions: 1.0 leaves the content unchanged
+/// for `Brightness`, `Contrast` and `Saturate`, 0.0 for `Grayscale`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum Filter {
+    /// Gaussian blur with the given sigma
+    Blur(f32),
+    /// A blurred copy of the content alpha drawn behind it
+    DropShadow {
+        offset: Point,
+        radius: f32,
+        color: Color,
+    },
+    /// A 4x5 row-major color matrix, as `skia_safe::ColorMatrix`
+    ColorMatrix([f32; 20]),
+    Brightness(f32),
+    Contrast(f32),
+    Saturate(f32),
+    /// Rotates the hue by the given angle in degrees
+    HueRotate(f32),
+    Grayscale(f32),
+}
+
+impl Filter {
+    /// The filter of the same kind that leaves the content unchanged,
+    /// used to animate filters in and out
+    pub fn identity(&self) -> Filter {
+        match self {
+            Filter::Blur(_) => Filter::Blur(0.0),
+            Filter::DropShadow {
+                offset,
+                radius,
+                color,
+            } => Filter::DropShadow {
+                offset: *offset,
+                radius: *radius,
+                color: color.opacity(0.0),
+            },
+            Filter::ColorMatrix(_) => Filter::ColorMatrix([
+                1.0, 0.0, 0.0, 0.0, 0.0, //
+                0.0, 1.0, 0.0, 0.0, 0.0, //
+                0.0, 0.0, 1.0, 0.0, 0.0, //
+                0.0, 0.0, 0.0, 1.0, 0.0,
+            ]),
+            Filter::Brightness(_) => Filter::Brightness(1.0),
+            Filter::Contrast(_) => Filter::Contrast(1.0),
+            Filter::Saturate(_) => Filter::Saturate(1.0),
+            Filter::HueRotate(_) => Filter::HueRotate(0.0),
+            Filter::Grayscale(_) => Filter::Grayscale(0.0),
+        }
+    }
+
+    /// The 4x5 color matrix of the color filters, None for the other kinds
+    pub fn color_matrix(&self) -> Option<[f32; 20]> {
+        // luminance coefficients of the CSS filter effects matrices
+        const R: f32 = 0.213;
+        const G: f32 = 0.715;
+        const B: f32 = 0.072;
+        let saturate = |s: f32| {
+            [
+                R + (1.0 - R) * s,
+                G - G * s,
+                B - B * s,
+                0.0,
+                0.0,
+                R - R * s,
+                G + (1.0 - G) * s,
+                B - B * s,
+                0.0,
+                0.0,
+                R - R * s,
+                G - G * s,
+                B + (1.0 - B) * s,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                0.0,
+                1.0,
+                0.0,
+            ]
+        };
+        match *self {
+            Filter::ColorMatrix(matrix) => Some(matrix),
+            Filter::Brightness(b) => Some([
+                b, 0.0, 0.0, 0.0, 0.0, //
+                0.0, b, 0.0, 0.0, 0.0, //
+                0.0, 0.0, b, 0.0, 0.0, //
+                0.0, 0.0, 0.0, 1.0, 0.0,
+            ]),
+            Filter::Contrast(c) => {
+                // the translation is in normalized units
+                let t = 0.5 - 0.5 * c;
+                Some([
+                    c, 0.0, 0.0, 0.0, t, //
+                    0.0, c, 0.0, 0.0, t, //
+                    0.0, 0.0, c, 0.0, t, //
+                    0.0, 0.0, 0.0, 1.0, 0.0,
+                ])
+            }
+            Filter::Saturate(s) => Some(saturate(s)),
+            Filter::Grayscale(g) => Some(saturate(1.0 - g.clamp(0.0, 1.0))),
+            Filter::HueRotate(degrees) => {
+                let (sin, cos) = degrees.to_radians().sin_cos();
+                Some([
+                    R + cos * (1.0 - R) - sin * R,
+                    G - cos * G - sin * G,
+                    B - cos * B + sin * (1.0 - B),
+                    0.0,
+                    0.0,
+                    R - cos * R + sin * 0.143,
+                    G + cos * (1.0 - G) + sin * 0.140,
+                    B - cos * B - sin * 0.283,
+                    0.0,
+                    0.0,
+                    R - cos * R - sin * (1.0 - R),
+                    G - cos * G + sin * G,
+                    B + cos * (1.0 - B) + sin * B,
+                    0.0,
+                    0.0,
+                    0.0,
+                    0.0,
+                    0.0,
+                    1.0,
+                    0.0,
+                ])
+            }
+            Filter::Blur(_) | Filter::DropShadow { .. } => None,
+        }
+    }
+
+    /// How far the filter draws outside of the content bounds
+    pub fn outset(&self) -> f32 {
+        match self {
+            Filter::Blur(sigma) => sigma.max(0.0) * 3.0,
+            Filter::DropShadow { offset, radius, .. } => {
+                radius.max(0.0) * 3.0 + offset.x.abs().max(offset.y.abs())
+            }
+            _ => 0.0,
+        }
+    }
+}
+
+/// The material drawn behind layers with `BlendMode::BackgroundBlur`:
+/// the backdrop is blurred, color adjusted, tinted and covered with noise.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[repr(C)]
+pub struct BackdropEffect {
+    /// The blur sigma applied to the backdrop, 0.0 to disable the blur
+    pub blur_sigma: f32,
+    /// 1.0 keeps the backdrop saturation, 0.0 is grayscale
+    pub saturation: f32,
+    /// Multiplies the backdrop colors, 1.0 keeps them unchanged
+    pub brightness: f32,
+    /// Drawn over the blurred backdrop
+    pub tint: Color,
+    /// Opacity of the noise texture drawn over the layer, 0.0 to disable it
+    pub noise: f32,
+    /// Scale of the backdrop snapshot, smaller values are cheaper to blur.
+    /// Clamped to `(0.0, 1.0]` when drawing.
+    pub downsample: f32,
+}
+
+impl Default for BackdropEffect {
+    fn default() -> Self {
+        BackdropEffect {
+            blur_sigma: 40.0,
+            saturation: 1.10,
+            brightness: 1.06,
+            tint: Color::new_rgba(0.0, 0.0, 0.0, 0.0),
+            noise: 50.0 / 255.0,
+            downsample: 0.1,
+        }
+    }
+}
+
+/// How a background image fills the layer bounds
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub enum BackgroundImageMode {
+    /// The image is scaled to the layer size
+    #[default]
+    Stretch,
+    /// The image is repeated at its own size from the layer origin
+    Tile,
+    /// The corners keep their size, the edges stretch along their side and the
+    /// center stretches in both directions. The insets are in image pixels.
+    NineSlice {
+        top: f32,
+        right: f32,
+        bottom: f32,
+        left: f32,
+    },
+}
+
+/// An image drawn over the background color, inside the layer shape
+#[derive(Clone, Debug)]
+pub struct BackgroundImage {
+    pub image: Image,
+    pub mode: BackgroundImageMode,
+}
+
+impl BackgroundImage {
+    pub fn stretch(image: Image) -> Self {
+        BackgroundImage {
+            image,
+            mode: BackgroundImageMode::Stretch,
+        }
+    }
+    pub fn tile(image: Image) -> Self {
+        BackgroundImage {
+            image,
+            mode: BackgroundImageMode::Tile,
+        }
+    }
+    pub fn nine_slice(image: Image, top: f32, right: f32, bottom: f32, left: f32) -> Self {
+        BackgroundImage {
+            image,
+            mode: BackgroundImageMode::NineSlice {
+                top,
+                right,
+                bottom,
+                left,
+            },
+        }
+    }
+}
+
+impl PartialEq for BackgroundImage {
+    fn eq(&self, other: &Self) -> bool {
+        self.image.unique_id() == other.image.unique_id() && self.mode == other.mode
+    }
+}
+
+impl From<Image> for BackgroundImage {
+    fn from(image: Image) -> Self {
+        BackgroundImage::stretch(image)
+    }
+}
+
+/// A single operation of a `Transform`. Angles are in radians, like the layer rotation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TransformOperation {
+    Translate(Point3d),
+    Scale(Point3d),
+    /// Rotation around an arbitrary axis
+    Rotate {
+        axis: Point3d,
+        angle: f32,
+    },
+    /// Skew angles along the x and y axes
+    Skew(Point),
+    /// Perspective at the given distance from the z=0 plane, 0.0 for none
+    Perspective(f32),
+    Matrix(M44),
+}
+
+impl TransformOperation {
+    /// The operation of the same kind that leaves the layer unchanged,
+    /// used to animate operations in and out
+    pub fn identity(&self) -> TransformOperation {
+        match self {
+            TransformOperation::Translate(_) => TransformOperation::Translate(Point3d::default()),
+            TransformOperation::Scale(_) => TransformOperation::Scale(Point3d {
+                x: 1.0,
+                y: 1.0,
+                z: 1.0,
+            }),
+            TransformOperation::Rotate { axis, .. } => TransformOperation::Rotate {
+                axis: *axis,
+                angle: 0.0,
+            },
+            TransformOperation::Skew(_) => TransformOperation::Skew(Point::default()),
+            TransformOperation::Perspective(_) => TransformOperation::Perspective(0.0),
+            TransformOperation::Matrix(_) => TransformOperation::Matrix(M44::new_identity()),
+        }
+    }
+
+    pub fn to_m44(&self) -> M44 {
+        match self {
+            TransformOperation::Translate(t) => M44::translate(t.x, t.y, t.z),
+            TransformOperation::Scale(s) => M44::scale(s.x, s.y, s.z),
+            TransformOperation::Rotate { axis, angle } => {
+                if axis.x == 0.0 && axis.y == 0.0 && axis.z == 0.0 {
+                    return M44::new_identity();
+                }
+                M44::rotate(
+                    V3 {
+                        x: axis.x,
+                        y: axis.y,
+                        z: axis.z,
+                    },
+                    *angle,
+                )
+            }
+            TransformOperation::Skew(skew) => {
+                let mut m = M44::new_identity();
+                m.set_rc(0, 1, skew.x.tan());
+                m.set_rc(1, 0, skew.y.tan());
+                m
+            }
+            TransformOperation::Perspective(distance) => {
+                let mut m = M44::new_identity();
+                if *distance > 0.0 {
+                    m.set_rc(3, 2, -1.0 / distance);
+                }
+                m
+            }
+            TransformOperation::Matrix(m) => *m,
+        }
+    }
+}
+
+/// An arbitrary 3D transform, described as a list of operations applied in order
+/// like the CSS `transform` property.
+///
+/// Transforms whose operations match kind by kind animate each operation, any
+/// other pair animates by decomposing the two matrices.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Transform {
+    pub operations: Vec<TransformOperation>,
+}
+
+impl Transform {
+    pub fn new(operations: Vec<TransformOperation>) -> Self {
+        Transform { operations }
+    }
+    pub fn is_identity(&self) -> bool {
+        self.to_m44() == M44::new_identity()
+    }
+    /// The transform matrix, composing the operations left to right
+    pub fn to_m44(&self) -> M44 {
+        self.operations
+            .iter()
+            .fold(M44::new_identity(), |m, op| M44::concat(&m, &op.to_m44()))
+    }
+}
+
+impl From<M44> for Transform {
+    fn from(matrix: M44) -> Self {
+        Transform::new(vec![TransformOperation::Matrix(matrix)])
+    }
+}
+
+impl From<Vec<TransformOperation>> for Transform {
+    fn from(operations: Vec<TransformOperation>) -> Self {
+        Transform::new(operations)
+    }
+}
+
+impl From<TransformOperation> for Transform {
+    fn from(operation: TransformOperation) -> Self {
+        Transform::new(vec![operation])
+    }
+}
+
+/// How a layer, together with its sublayers, is composited onto what is behind it.
+///
+/// Apart from `Normal` and `BackgroundBlur`, the modes are the standard Porter-Duff and
+/// separable/non-separable blend modes: the subtree is drawn into an offscreen layer which
+/// is then blended onto the backdrop.
 #[derive(Clone, Copy, Default, Serialize, Debug)]
 #[repr(u32)]
 pub enum BlendMode {
     #[default]
     Normal,
     BackgroundBlur,
+    // Porter-Duff modes
+    Clear,
+    Src,
+    Dst,
+    DstOver,
+    SrcIn,
+    DstIn,
+    SrcOut,
+    DstOut,
+    SrcATop,
+    DstATop,
+    Xor,
+    Plus,
+    Modulate,
+    // separable blend modes
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+    Multiply,
+    // non-separable blend modes
+    Hue,
+    Saturation,
+    Color,
+    Luminosity,
+}
+
+impl BlendMode {
+    /// The skia blend mode used to composite the layer subtree onto its backdrop.
+    /// None for `Normal` and `BackgroundBlur`, which are drawn without an offscreen layer.
+    pub fn to_skia(&self) -> Option<skia_safe::BlendMode> {
+        use skia_safe::BlendMode as Skia;
+        let mode = match self {
+            BlendMode::Normal | BlendMode::BackgroundBlur => return None,
+            BlendMode::Clear => Skia::Clear,
+            BlendMode::Src => Skia::Src,
+            BlendMode::Dst => Skia::Dst,
+            BlendMode::DstOver => Skia::DstOver,
+            BlendMode::SrcIn => Skia::SrcIn,
+            BlendMode::DstIn => Skia::DstIn,
+            BlendMode::SrcOut => Skia::SrcOut,
+            BlendMode::DstOut => Skia::DstOut,
+            BlendMode::SrcATop => Skia::SrcATop,
+            BlendMode::DstATop => Skia::DstATop,
+            BlendMode::Xor => Skia::Xor,
+            BlendMode::Plus => Skia::Plus,
+            BlendMode::Modulate => Skia::Modulate,
+            BlendMode::Screen => Skia::Screen,
+            BlendMode::Overlay => Skia::Overlay,
+            BlendMode::Darken => Skia::Darken,
+            BlendMode::Lighten => Skia::Lighten,
+            BlendMode::ColorDodge => Skia::ColorDodge,
+            BlendMode::ColorBurn => Skia::ColorBurn,
+            BlendMode::HardLight => Skia::HardLight,
+            BlendMode::SoftLight => Skia::SoftLight,
+            BlendMode::Difference => Skia::Difference,
+            BlendMode::Exclusion => Skia::Exclusion,
+            BlendMode::Multiply => Skia::Multiply,
+            BlendMode::Hue => Skia::Hue,
+            BlendMode::Saturation => Skia::Saturation,
+            BlendMode::Color => Skia::Color,
+            BlendMode::Luminosity => Skia::Luminosity,
+        };
+        Some(mode)
+    }
 }
 
 impl PartialEq for BlendMode {
@@ -346,6 +930,41 @@ impl From<Color> for PaintColor {
     }
 }
 
+impl From<GradientLinear> for PaintColor {
+    fn from(gradient: GradientLinear) -> Self {
+        PaintColor::GradientLinear(Box::new(gradient))
+    }
+}
+
+impl From<GradientRadial> for PaintColor {
+    fn from(gradient: GradientRadial) -> Self {
+        PaintColor::GradientRadial(Box::new(gradient))
+    }
+}
+
+impl From<GradientConic> for PaintColor {
+    fn from(gradient: GradientConic) -> Self {
+        PaintColor::GradientConic(Box::new(gradient))
+    }
+}
+
+impl From<GradientTwoPointConical> for PaintColor {
+    fn from(gradient: GradientTwoPointConical) -> Self {
+        PaintColor::GradientTwoPointConical(Box::new(gradient))
+    }
+}
+
+impl From<TileMode> for skia_safe::TileMode {
+    fn from(mode: TileMode) -> Self {
+        match mode {
+            TileMode::Clamp => skia_safe::TileMode::Clamp,
+            TileMode::Repeat => skia_safe::TileMode::Repeat,
+            TileMode::Mirror => skia_safe::TileMode::Mirror,
+            TileMode::Decal => skia_safe::TileMode::Decal,
+        }
+    }
+}
+
 impl From<f32> for BorderRadius {
     fn from(radius: f32) -> Self {
         BorderRadius::new_single(radius)
@@ -84,12 +84,18 @@ impl BuildLayerTree for Layer {
         if let Some((ref background_color, ref transition)) = viewlayer_tree.background_color {
             scene_layer.set_background_color(background_color.clone(), transition.clone());
         }
+        if let Some(ref background_image) = viewlayer_tree.background_image {
+            scene_layer.set_background_image(background_image.clone());
+        }
         if let Some((border_color, ref transition)) = viewlayer_tree.border_color {
             scene_layer.set_border_color(border_color, transition.clone());
         }
         if let Some((border_width, ref transition)) = viewlayer_tree.border_width {
             scene_layer.set_border_width(border_width, transition.clone());
         }
+        if let Some(border_style) = viewlayer_tree.border_style {
+            scene_layer.set_border_style(border_style);
+        }
         if let Some((border_corner_radius, ref transition)) = viewlayer_tree.border_corner_radius {
             scene_layer.set_border_corner_radius(border_corner_radius, transition.clone());
         }
@@ -108,6 +114,9 @@ impl BuildLayerTree for Layer {
         if let Some((shadow_spread, ref transition)) = viewlayer_tree.shadow_spread {
             scene_layer.set_shadow_spread(shadow_spread, transition.clone());
         }
+        if let Some((ref shadows, ref transition)) = viewlayer_tree.shadows {
+            scene_layer.set_shadows(shadows.clone(), transition.clone());
+        }
         if let Some(ref layout_style) = viewlayer_tree.layout_style {
             scene_layer.set_layout_style(layout_style.clone());
         }
@@ -117,6 +126,27 @@ impl BuildLayerTree for Layer {
         if let Some(blend_mode) = viewlayer_tree.blend_mode {
             scene_layer.set_blend_mode(blend_mode);
         }
+        if let Some((backdrop_effect, ref transition)) = viewlayer_tree.backdrop_effect {
+            scene_layer.set_backdrop_effect(backdrop_effect, transition.clone());
+        }
+        if let Some((ref filters, ref transition)) = viewlayer_tree.filters {
+            scene_layer.set_filters(filters.clone(), transition.clone());
+        }
+        if let Some((ref transform, ref transition)) = viewlayer_tree.transform {
+            scene_layer.set_transform(transform.clone(), transition.clone());
+        }
+        if let Some((ref sublayer_transform, ref transition)) = viewlayer_tree.sublayer_transform {
+            scene_layer.set_sublayer_transform(sublayer_transform.clone(), transition.clone());
+        }
+        if let Some((perspective, ref transition)) = viewlayer_tree.perspective {
+            scene_layer.set_perspective(perspective, transition.clone());
+        }
+        if let Some((perspective_origin, ref transition)) = viewlayer_tree.perspective_origin {
+            scene_layer.set_perspective_origin(perspective_origin, transition.clone());
+        }
+        if let Some(preserve_3d) = viewlayer_tree.preserve_3d {
+            scene_layer.set_preserve_3d(preserve_3d);
+        }
 
         if let Some(content) = viewlayer_tree.content.clone() {
             scene_layer.set_draw_content(content);
@@ -50,6 +50,8 @@ pub struct LayerTree {
     #[builder(setter(into, strip_option), default)]
     pub background_color: Option<(PaintColor, Option<Transition>)>,
     #[builder(setter(into, strip_option), default)]
+    pub background_image: Option<BackgroundImage>,
+    #[builder(setter(into, strip_option), default)]
     pub border_color: Option<(Color, Option<Transition>)>,
     #[builder(setter(into, strip_option), default)]
     pub border_width: Option<(f32, Option<Transition>)>,
@@ -73,11 +75,27 @@ pub struct LayerTree {
     pub shadow_color: Option<(Color, Option<Transition>)>,
     #[builder(setter(into, strip_option), default)]
     pub shadow_spread: Option<(f32, Option<Transition>)>,
+    #[builder(setter(into, strip_option), default)]
+    pub shadows: Option<(Vec<Shadow>, Option<Transition>)>,
     #[builder(setter(custom))]
     pub content: Option<ContentDrawFunction>,
     #[builder(setter(into, strip_option), default)]
     pub blend_mode: Option<BlendMode>,
     #[builder(setter(into, strip_option), default)]
+    pub backdrop_effect: Option<(BackdropEffect, Option<Transition>)>,
+    #[builder(setter(into, strip_option), default)]
+    pub filters: Option<(Vec<Filter>, Option<Transition>)>,
+    #[builder(setter(into, strip_option), default)]
+    pub transform: Option<(Transform, Option<Transition>)>,
+    #[builder(setter(into, strip_option), default)]
+    pub sublayer_transform: Option<(Transform, Option<Transition>)>,
+    #[builder(setter(into, strip_option), default)]
+    pub perspective: Option<(f32, Option<Transition>)>,
+    #[builder(setter(into, strip_option), default)]
+    pub perspective_origin: Option<(Point, Option<Transition>)>,
+    #[builder(setter(into, strip_option), default)]
+    pub preserve_3d: Option<bool>,
+    #[builder(setter(into, strip_option), default)]
     pub layout_style: Option<taffy::Style>,
     #[builder(setter(into, strip_option))]
     pub opacity: Option<(f32, Option<Transition>)>,
@@ -210,6 +228,9 @@ impl fmt::Debug for LayerTree {
         if let Some(background_color) = &self.background_color {
             ds.field("background_color", &background_color);
         }
+        if let Some(background_image) = &self.background_image {
+            ds.field("background_image", &background_image.mode);
+        }
         if let Some(border_color) = &self.border_color {
             ds.field("border_color", &border_color);
         }
@@ -243,9 +264,33 @@ impl fmt::Debug for LayerTree {
         if let Some(shadow_spread) = &self.shadow_spread {
             ds.field("shadow_spread", &shadow_spread);
         }
+        if let Some(shadows) = &self.shadows {
+            ds.field("shadows", &shadows);
+        }
         if let Some(blend_mode) = &self.blend_mode {
             ds.field("blend_mode", &blend_mode);
         }
+        if let Some(backdrop_effect) = &self.backdrop_effect {
+            ds.field("backdrop_effect", &backdrop_effect);
+        }
+        if let Some(filters) = &self.filters {
+            ds.field("filters", &filters);
+        }
+        if let Some(transform) = &self.transform {
+            ds.field("transform", &transform);
+        }
+        if let Some(sublayer_transform) = &self.sublayer_transform {
+            ds.field("sublayer_transform", &sublayer_transform);
+        }
+        if let Some(perspective) = &self.perspective {
+            ds.field("perspective", &perspective);
+        }
+        if let Some(perspective_origin) = &self.perspective_origin {
+            ds.field("perspective_origin", &perspective_origin);
+        }
+        if let Some(preserve_3d) = &self.preserve_3d {
+            ds.field("preserve_3d", &preserve_3d);
+        }
         if let Some(layout_style) = &self.layout_style {
             ds.field("layout_style", &layout_style);
         }
@@ -313,6 +358,30 @@ impl From<Point> for (Point, Option<Transition>) {
     }
 }
 
+impl From<Vec<Shadow>> for (Vec<Shadow>, Option<Transition>) {
+    fn from(val: Vec<Shadow>) -> Self {
+        (val, None)
+    }
+}
+
+impl From<BackdropEffect> for (BackdropEffect, Option<Transition>) {
+    fn from(val: BackdropEffect) -> Self {
+        (val, None)
+    }
+}
+
+impl From<Vec<Filter>> for (Vec<Filter>, Option<Transition>) {
+    fn from(val: Vec<Filter>) -> Self {
+        (val, None)
+    }
+}
+
+impl From<Transform> for (Transform, Option<Transition>) {
+    fn from(val: Transform) -> Self {
+        (val, None)
+    }
+}
+
 // Add specific implementations for other types if needed
 
 #[allow(clippy::from_over_into)]
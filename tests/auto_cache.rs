@@ -0,0 +1,139 @@
+use layers::engine::AutoCacheDecision;
+use layers::prelude::*;
+use layers::types::Size;
+
+fn auto_cache_decision(engine: &Engine, layer: &Layer) -> Option<AutoCacheDecision> {
+    engine
+        .scene_get_node(layer.id())
+        .unwrap()
+        .get()
+        .auto_cache_decision()
+}
+
+fn is_image_cached(engine: &Engine, layer: &Layer) -> bool {
+    engine
+        .scene_get_node(layer.id())
+        .unwrap()
+        .get()
+        .is_image_cached()
+}
+
+// an absolutely positioned layer in the root of the scene
+fn root_layer(engine: &Engine, size: (f32, f32)) -> Layer {
+    if engine.scene_root().is_none() {
+        let root = engine.new_layer();
+        root.set_size(Size::points(1000.0, 1000.0), None);
+        engine.add_layer(&root).unwrap();
+    }
+    let layer = engine.new_layer();
+    layer.set_layout_style(layers::taffy::Style {
+        position: layers::taffy::Position::Absolute,
+        ..Default::default()
+    });
+    layer.set_size(Size::points(size.0, size.1), None);
+    layer.set_background_color(Color::new_hex("#ffffff"), None);
+    engine.add_layer(&layer).unwrap();
+    layer
+}
+
+// a parent drawing four children
+fn panel(engine: &Engine) -> (Layer, Vec<Layer>) {
+    let parent = root_layer(engine, (400.0, 400.0));
+
+    let children = (0..4)
+        .map(|i| {
+            let child = engine.new_layer();
+            child.set_position((10.0, 10.0 + i as f32 * 60.0), None);
+            child.set_size(Size::points(50.0, 50.0), None);
+            child.set_background_color(Color::new_hex("#ff0000"), None);
+            parent.add_sublayer(&child).unwrap();
+            child
+        })
+        .collect();
+    (parent, children)
+}
+
+#[test]
+fn stable_subtree_is_promoted_then_demoted() {
+    let engine = Engine::create(1000.0, 1000.0);
+    engine.set_auto_image_caching(true);
+    let (parent, children) = panel(&engine);
+
+    for _ in 0..16 {
+        engine.update(0.016);
+        assert!(!is_image_cached(&engine, &parent));
+    }
+    engine.update(0.016);
+    assert_eq!(
+        auto_cache_decision(&engine, &parent),
+        Some(AutoCacheDecision::Promoted)
+    );
+    assert!(is_image_cached(&engine, &parent));
+    // the children are drawn in the parent cache
+    assert_eq!(auto_cache_decision(&engine, &children[0]), None);
+
+    let snapshot = engine.scene().snapshot();
+    assert_eq!(
+        snapshot.nodes[0].children[0].auto_cache,
+        Some(AutoCacheDecision::Promoted)
+    );
+    assert!(snapshot.nodes[0].children[0].image_cached);
+
+    // a child changing on every update demotes the parent
+    for i in 0..4 {
+        children[0].set_position((20.0 + i as f32, 10.0), None);
+        engine.update(0.016);
+    }
+    assert_eq!(
+        auto_cache_decision(&engine, &parent),
+        Some(AutoCacheDecision::Demoted)
+    );
+    assert!(!is_image_cached(&engine, &parent));
+}
+
+#[test]
+fn disabling_auto_caching_resets_the_decisions() {
+    let engine = Engine::create(1000.0, 1000.0);
+    engine.set_auto_image_caching(true);
+    let (parent, _) = panel(&engine);
+    for _ in 0..17 {
+        engine.update(0.016);
+    }
+    assert!(is_image_cached(&engine, &parent));
+
+    engine.set_auto_image_caching(false);
+    assert!(!is_image_cached(&engine, &parent));
+    assert_eq!(auto_cache_decision(&engine, &parent), None);
+
+    // explicitly cached layers are not affected
+    parent.set_image_cached(true);
+    engine.set_auto_image_caching(true);
+    engine.set_auto_image_caching(false);
+    assert!(is_image_cached(&engine, &parent));
+}
+
+#[test]
+fn root_and_sparse_subtrees_are_not_promoted() {
+    let engine = Engine::create(1000.0, 1000.0);
+    engine.set_auto_image_caching(true);
+    let (parent, _) = panel(&engine);
+    let root = engine.scene_root().unwrap();
+
+    // a few layers spread over a large area
+    let sparse = root_layer(&engine, (3000.0, 3000.0));
+    for i in 0..4 {
+        let child = engine.new_layer();
+        child.set_position((i as f32 * 700.0, 0.0), None);
+        child.set_size(Size::points(50.0, 50.0), None);
+        child.set_background_color(Color::new_hex("#ff0000"), None);
+        sparse.add_sublayer(&child.id).unwrap();
+    }
+
+    for _ in 0..17 {
+        engine.update(0.016);
+    }
+    assert!(is_image_cached(&engine, &parent));
+    assert_eq!(auto_cache_decision(&engine, &sparse), None);
+    let root_node = engine.scene_get_node(root).unwrap();
+    assert_eq!(root_node.get().auto_cache_decision(), None);
+}
@@ -0,0 +1,172 @@
+use layers::prelude::*;
+use layers::renderer::skia_image::SkiaImageRenderer;
+use layers::types::Size;
+use skia_safe::Contains;
+
+fn checkerboard() -> Image {
+    let mut surface = skia_safe::surfaces::raster_n32_premul((12, 12)).unwrap();
+    let canvas = surface.canvas();
+    canvas.clear(skia_safe::Color::WHITE);
+    let paint = skia_safe::Paint::new(skia_safe::Color4f::new(0.0, 0.0, 0.0, 1.0), None);
+    canvas.draw_rect(skia_safe::Rect::from_xywh(0.0, 0.0, 6.0, 6.0), &paint);
+    canvas.draw_rect(skia_safe::Rect::from_xywh(6.0, 6.0, 6.0, 6.0), &paint);
+    surface.image_snapshot()
+}
+
+// a blue image with a red top left corner and a green bottom right corner,
+// 4 pixels wide
+fn corners() -> Image {
+    let mut surface = skia_safe::surfaces::raster_n32_premul((12, 12)).unwrap();
+    let canvas = surface.canvas();
+    canvas.clear(skia_safe::Color::BLUE);
+    let red = skia_safe::Paint::new(skia_safe::Color4f::new(1.0, 0.0, 0.0, 1.0), None);
+    canvas.draw_rect(skia_safe::Rect::from_xywh(0.0, 0.0, 4.0, 4.0), &red);
+    let green = skia_safe::Paint::new(skia_safe::Color4f::new(0.0, 1.0, 0.0, 1.0), None);
+    canvas.draw_rect(skia_safe::Rect::from_xywh(8.0, 8.0, 4.0, 4.0), &green);
+    surface.image_snapshot()
+}
+
+// renders a 100x50 layer at 100,100 filled with the image
+fn render(image: BackgroundImage) -> SkiaImageRenderer {
+    let engine = Engine::create(300.0, 300.0);
+    let layer = engine.new_layer();
+    layer.set_position((100.0, 100.0), None);
+    layer.set_size(Size::points(100.0, 50.0), None);
+    layer.set_background_image(image);
+    engine.add_layer(&layer).unwrap();
+    engine.update(0.016);
+
+    let renderer = SkiaImageRenderer::new(300, 300, "tests/background_image.png");
+    renderer.draw_scene(engine.scene(), engine.scene_root().unwrap(), None);
+    renderer
+}
+
+fn pixel_at(renderer: &SkiaImageRenderer, x: i32, y: i32) -> skia_safe::Color {
+    renderer
+        .surface()
+        .image_snapshot()
+        .peek_pixels()
+        .unwrap()
+        .get_color((x, y))
+}
+
+#[test]
+fn background_image_is_drawn() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    layer.set_position((100.0, 100.0), None);
+    layer.set_size(Size::points(100.0, 50.0), None);
+    engine.add_layer(&layer).unwrap();
+    engine.update(0.016);
+    engine.clear_damage();
+
+    let image = BackgroundImage::nine_slice(checkerboard(), 4.0, 4.0, 4.0, 4.0);
+    layer.set_background_image(image.clone());
+    engine.update(0.016);
+
+    assert_eq!(layer.background_image(), Some(image.clone()));
+    let render_layer = engine.render_layer(&layer).unwrap();
+    assert_eq!(
+        render_layer
+            .background_image
+            .as_ref()
+            .map(|image| image.mode),
+        Some(BackgroundImageMode::NineSlice {
+            top: 4.0,
+            right: 4.0,
+            bottom: 4.0,
+            left: 4.0,
+        })
+    );
+    // the image fills the layer bounds
+    assert_eq!(
+        engine.damage(),
+        skia_safe::Rect::from_xywh(100.0, 100.0, 100.0, 50.0)
+    );
+    // the image may be transparent, it does not occlude
+    assert!(!render_layer.is_fully_opaque());
+    engine.clear_damage();
+
+    layer.set_background_image(None);
+    engine.update(0.016);
+    assert!(engine
+        .render_layer(&layer)
+        .unwrap()
+        .background_image
+        .is_none());
+    assert!(engine
+        .damage()
+        .contains(skia_safe::Rect::from_xywh(100.0, 100.0, 100.0, 50.0)));
+}
+
+#[test]
+fn background_image_from_layer_tree() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    engine.add_layer(&layer).unwrap();
+
+    let tree = LayerTreeBuilder::default()
+        .key("button")
+        .size((Size::points(80.0, 30.0), None))
+        .background_image(BackgroundImage::tile(checkerboard()))
+        .build()
+        .unwrap();
+    layer.build_layer_tree(&tree);
+    engine.update(0.016);
+
+    let render_layer = engine.render_layer(&layer).unwrap();
+    assert_eq!(
+        render_layer.background_image.map(|image| image.mode),
+        Some(BackgroundImageMode::Tile)
+    );
+}
+
+#[test]
+fn background_image_stretch_is_rendered() {
+    let renderer = render(BackgroundImage::stretch(corners()));
+    // the corners are scaled with the image
+    assert_eq!(pixel_at(&renderer, 101, 101), skia_safe::Color::RED);
+    assert_eq!(pixel_at(&renderer, 110, 101), skia_safe::Color::RED);
+    assert_eq!(pixel_at(&renderer, 150, 125), skia_safe::Color::BLUE);
+    assert_eq!(pixel_at(&renderer, 195, 145), skia_safe::Color::GREEN);
+}
+
+#[test]
+fn background_image_tile_is_rendered() {
+    let renderer = render(BackgroundImage::tile(corners()));
+    assert_eq!(pixel_at(&renderer, 101, 101), skia_safe::Color::RED);
+    assert_eq!(pixel_at(&renderer, 106, 101), skia_safe::Color::BLUE);
+    assert_eq!(pixel_at(&renderer, 109, 109), skia_safe::Color::GREEN);
+    // the image repeats at its own size
+    assert_eq!(pixel_at(&renderer, 113, 101), skia_safe::Color::RED);
+    assert_eq!(pixel_at(&renderer, 121, 121), skia_safe::Color::GREEN);
+}
+
+#[test]
+fn background_image_nine_slice_is_rendered() {
+    let renderer = render(BackgroundImage::nine_slice(corners(), 4.0, 4.0, 4.0, 4.0));
+    // the corners keep their size
+    assert_eq!(pixel_at(&renderer, 101, 101), skia_safe::Color::RED);
+    assert_eq!(pixel_at(&renderer, 198, 148), skia_safe::Color::GREEN);
+    // the edges and the center stretch
+    assert_eq!(pixel_at(&renderer, 110, 101), skia_safe::Color::BLUE);
+    assert_eq!(pixel_at(&renderer, 150, 125), skia_safe::Color::BLUE);
+    assert_eq!(pixel_at(&renderer, 150, 148), skia_safe::Color::BLUE);
+}
+
+#[test]
+fn background_image_nine_slice_insets_larger_than_the_image() {
+    // the insets leave no center, they are scaled down to fit the image
+    let renderer = render(BackgroundImage::nine_slice(
+        corners(),
+        10.0,
+        10.0,
+        10.0,
+        10.0,
+    ));
+    assert_eq!(pixel_at(&renderer, 101, 101), skia_safe::Color::RED);
+    assert_eq!(pixel_at(&renderer, 198, 148), skia_safe::Color::GREEN);
+    // the image is not stretched as a whole, the top left corner keeps its size
+    assert_eq!(pixel_at(&renderer, 110, 101), skia_safe::Color::BLUE);
+    assert_eq!(pixel_at(&renderer, 150, 125), skia_safe::Color::BLUE);
+}
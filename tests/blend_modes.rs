@@ -0,0 +1,48 @@
+use layers::prelude::*;
+use layers::renderer::skia_image::SkiaImageRenderer;
+use layers::types::{BlendMode, Size};
+
+fn pixel_at(renderer: &SkiaImageRenderer, x: i32, y: i32) -> skia_safe::Color {
+    renderer
+        .surface()
+        .image_snapshot()
+        .peek_pixels()
+        .unwrap()
+        .get_color((x, y))
+}
+
+fn is_opaque(color: skia_safe::Color, r: u8, g: u8, b: u8) -> bool {
+    let close = |a: u8, b: u8| a.abs_diff(b) <= 2;
+    color.a() == 255 && close(color.r(), r) && close(color.g(), g) && close(color.b(), b)
+}
+
+#[test]
+fn blend_modes_only_affect_the_layer_area() {
+    for blend_mode in [BlendMode::SrcIn, BlendMode::Clear] {
+        let engine = Engine::create(200.0, 200.0);
+        let backdrop = engine.new_layer();
+        backdrop.set_size(Size::points(200.0, 200.0), None);
+        backdrop.set_background_color(Color::new_rgba(1.0, 0.0, 0.0, 1.0), None);
+        engine.add_layer(&backdrop).unwrap();
+
+        let layer = engine.new_layer();
+        layer.set_position((50.0, 50.0), None);
+        layer.set_size(Size::points(50.0, 50.0), None);
+        layer.set_background_color(Color::new_rgba(0.0, 0.0, 1.0, 1.0), None);
+        layer.set_blend_mode(blend_mode);
+        engine.add_layer(&layer).unwrap();
+        engine.update(0.016);
+
+        let renderer = SkiaImageRenderer::new(200, 200, "");
+        renderer.draw_scene(engine.scene(), engine.scene_root().unwrap(), None);
+
+        // the backdrop outside of the layer is untouched
+        assert!(is_opaque(pixel_at(&renderer, 10, 10), 255, 0, 0));
+        assert!(is_opaque(pixel_at(&renderer, 150, 150), 255, 0, 0));
+        if blend_mode == BlendMode::Clear {
+            assert_eq!(pixel_at(&renderer, 75, 75).a(), 0);
+        } else {
+            assert!(is_opaque(pixel_at(&renderer, 75, 75), 0, 0, 255));
+        }
+    }
+}
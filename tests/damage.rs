@@ -2,6 +2,7 @@
 mod tests {
     use layers::{
         drawing::draw_layer,
+        engine::DAMAGE_HISTORY_SIZE,
         prelude::*,
         renderer::skia_image::SkiaImageRenderer,
         types::{Color, PaintColor, Size},
@@ -1191,7 +1192,7 @@ mod tests {
     //   && premultiplied_opacity == 1.0
     //   && blend_mode == Normal
     //   && !hidden
-    // Occluder shape = the layer's global_transformed_bounds.
+    // Occluder shape = the layer's global_opaque_bounds.
     //
     // See `project_occlusion_damage_plan.md` for full design.
     // -------------------------------------------------------------------
@@ -1257,4 +1258,81 @@ mod tests {
             scene_damage
         );
     }
+
+    #[test]
+    pub fn damage_region_keeps_distant_changes_apart() {
+        let engine = Engine::create(1000.0, 1000.0);
+        let cursor = engine.new_layer();
+        cursor.set_position((10.0, 10.0), None);
+        cursor.set_size(Size::points(2.0, 20.0), None);
+        engine.add_layer(&cursor).unwrap();
+
+        let clock = engine.new_layer();
+        clock.set_position((900.0, 950.0), None);
+        clock.set_size(Size::points(80.0, 20.0), None);
+        engine.add_layer(&clock).unwrap();
+
+        engine.update(0.016);
+        engine.clear_damage();
+        assert!(engine.damage_region().is_empty());
+
+        cursor.set_background_color(Color::new_rgba(0.0, 0.0, 0.0, 1.0), None);
+        clock.set_background_color(Color::new_rgba(1.0, 1.0, 1.0, 1.0), None);
+        engine.update(0.016);
+
+        let region = engine.damage_region();
+        assert!(region.contains_rect(skia_safe::IRect::from_xywh(10, 10, 2, 20)));
+        assert!(region.contains_rect(skia_safe::IRect::from_xywh(900, 950, 80, 20)));
+        // the area between the two layers is not damaged
+        assert!(!region.intersects_rect(skia_safe::IRect::from_xywh(100, 100, 700, 700)));
+        // the damage rect is the bounds of the region
+        assert_eq!(
+            engine.damage(),
+            skia_safe::Rect::from_irect(*region.bounds())
+        );
+
+        engine.clear_damage();
+        assert!(engine.damage_region().is_empty());
+    }
+
+    #[test]
+    pub fn damage_since_unions_the_previous_frames() {
+        let engine = Engine::create(1000.0, 1000.0);
+        let layers: Vec<_> = (0..3)
+            .map(|i| {
+                let layer = engine.new_layer();
+                layer.set_position((i as f32 * 200.0, 0.0), None);
+                layer.set_size(Size::points(100.0, 100.0), None);
+                engine.add_layer(&layer).unwrap();
+                layer
+            })
+            .collect();
+        engine.update(0.016);
+        engine.clear_damage();
+        let start = engine.frame_count();
+
+        // one layer changes in each frame
+        for layer in layers.iter() {
+            layer.set_background_color(Color::new_rgba(1.0, 0.0, 0.0, 1.0), None);
+            engine.update(0.016);
+            engine.clear_damage();
+        }
+        assert_eq!(engine.frame_count(), start + 3);
+
+        let rect = |i: i32| skia_safe::IRect::from_xywh(i * 200, 0, 100, 100);
+        // nothing changed since the last frame
+        assert!(engine.damage_since(1).unwrap().is_empty());
+        // a buffer two frames old misses the last frame
+        let region = engine.damage_since(2).unwrap();
+        assert!(region.contains_rect(rect(2)));
+        assert!(!region.intersects_rect(rect(1)));
+        // a triple buffer misses the last two frames
+        let region = engine.damage_since(3).unwrap();
+        assert!(region.contains_rect(rect(1)) && region.contains_rect(rect(2)));
+        assert!(!region.intersects_rect(rect(0)));
+
+        // unknown or too old buffers are repainted in full
+        assert!(engine.damage_since(0).is_none());
+        assert!(engine.damage_since(DAMAGE_HISTORY_SIZE + 2).is_none());
+    }
 }
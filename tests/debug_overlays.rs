@@ -0,0 +1,60 @@
+use layers::prelude::*;
+use layers::renderer::skia_image::SkiaImageRenderer;
+use layers::types::Size;
+
+fn pixel_at(renderer: &SkiaImageRenderer, x: i32, y: i32) -> skia_safe::Color {
+    renderer
+        .surface()
+        .image_snapshot()
+        .peek_pixels()
+        .unwrap()
+        .get_color((x, y))
+}
+
+#[test]
+fn damage_overlay_tints_the_damaged_area() {
+    let engine = Engine::create(200.0, 200.0);
+    let layer = engine.new_layer();
+    layer.set_size(Size::points(100.0, 100.0), None);
+    layer.set_background_color(Color::new_hex("#ffffff"), None);
+    engine.add_layer(&layer).unwrap();
+    engine.set_debug_overlays(DebugOverlays::DAMAGE);
+    assert_eq!(engine.debug_overlays(), DebugOverlays::DAMAGE);
+    engine.update(0.016);
+
+    let renderer = SkiaImageRenderer::new(200, 200, "tests/debug_overlays.png");
+    renderer.draw_scene(engine.scene(), engine.scene_root().unwrap(), None);
+    let tinted = pixel_at(&renderer, 50, 50);
+    assert_eq!(tinted.r(), 255);
+    assert!(tinted.g() < 255 && tinted.b() < 255);
+
+    // without damage the layer is drawn as is
+    engine.clear_damage();
+    renderer.draw_scene(engine.scene(), engine.scene_root().unwrap(), None);
+    assert_eq!(pixel_at(&renderer, 50, 50), skia_safe::Color::WHITE);
+}
+
+#[test]
+fn cache_overlay_outlines_image_cached_layers() {
+    let engine = Engine::create(200.0, 200.0);
+    let layer = engine.new_layer();
+    layer.set_size(Size::points(100.0, 100.0), None);
+    layer.set_background_color(Color::new_hex("#ffffff"), None);
+    layer.set_image_cached(true);
+    engine.add_layer(&layer).unwrap();
+    engine.update(0.016);
+    engine.set_debug_overlays(DebugOverlays::CACHE | DebugOverlays::LAYOUT);
+
+    let renderer = SkiaImageRenderer::new(200, 200, "tests/debug_overlays.png");
+    renderer.draw_scene(engine.scene(), engine.scene_root().unwrap(), None);
+    assert_eq!(
+        pixel_at(&renderer, 0, 50),
+        skia_safe::Color::from_rgb(0xff, 0x95, 0x00)
+    );
+    // the anchor point is at the top left corner
+    assert_eq!(
+        pixel_at(&renderer, 1, 1),
+        skia_safe::Color::from_rgb(0x00, 0x7a, 0xff)
+    );
+    assert_eq!(pixel_at(&renderer, 50, 50), skia_safe::Color::WHITE);
+}
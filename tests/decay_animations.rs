@@ -0,0 +1,83 @@
+use layers::prelude::*;
+
+#[test]
+fn decay_timing_decelerates_towards_projection() {
+    let decay = Decay::with_rate(4.0, 2.0);
+    assert!((decay.projected_progress() - 2.0).abs() < 0.001);
+
+    let (p0, v0) = decay.update_pos_vel_at(0.0);
+    assert!(p0.abs() < 0.001, "progress at t=0 should be 0.0, got {p0}");
+    assert!((v0 - 4.0).abs() < 0.001, "velocity at t=0 should be 4.0");
+
+    let (p1, v1) = decay.update_pos_vel_at(0.5);
+    let (p2, v2) = decay.update_pos_vel_at(1.0);
+    assert!(p1 > p0 && p2 > p1, "progress should keep increasing");
+    assert!(v2 < v1 && v1 < v0, "velocity should keep decreasing");
+    assert!(p2 < 2.0, "progress should never exceed the projection");
+}
+
+#[test]
+fn decay_timing_done() {
+    let tf = TimingFunction::Decay(Decay::with_rate(1.0, 10.0));
+    assert!(!tf.done(0.0, 0.1), "should not be done at 0.1s");
+    assert!(tf.done(0.0, 2.0), "should be done once at rest");
+}
+
+#[test]
+fn decay_timing_negative_elapsed_stays_at_start() {
+    let mut tf = TimingFunction::decay(3.0);
+    let (progress, _) = tf.update_at(-0.2);
+    assert!(progress.abs() < 0.001, "delayed decay should not move");
+}
+
+#[test]
+fn decay_with_bounds_springs_back_to_bound() {
+    // projected progress is 5.0, well past the upper bound
+    let decay = Decay::with_bounds(10.0, 0.0, 1.0);
+    assert!((decay.projected_progress() - 5.0).abs() < 0.01);
+    assert!((decay.resting_progress() - 1.0).abs() < 0.001);
+
+    let max = (0..300)
+        .map(|i| decay.update_pos_vel_at(i as f32 * 0.01).0)
+        .fold(f32::MIN, f32::max);
+    assert!(max > 1.0, "the decay should overshoot the bound, max {max}");
+
+    let (progress, _) = decay.update_pos_vel_at(5.0);
+    assert!(
+        (progress - 1.0).abs() < 0.01,
+        "should settle on the bound, got {progress}"
+    );
+    assert!(decay.done(5.0));
+}
+
+#[test]
+fn decay_with_bounds_within_range_is_plain_decay() {
+    let bounded = Decay::with_bounds(1.0, -1.0, 1.0);
+    let free = Decay::new(1.0);
+    for i in 0..10 {
+        let t = i as f32 * 0.1;
+        assert!((bounded.update_pos_vel_at(t).0 - free.update_pos_vel_at(t).0).abs() < 0.0001);
+    }
+}
+
+#[test]
+fn decay_transition_moves_layer() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    engine.add_layer(&layer).unwrap();
+
+    layer.set_position((100.0, 0.0), Transition::decay_with_bounds(6.0, 0.0, 1.0));
+
+    engine.update(0.1);
+    let x = layer.position().x;
+    assert!(x > 0.0, "layer should start moving, got {x}");
+
+    for _ in 0..100 {
+        engine.update(0.05);
+    }
+    let x = layer.position().x;
+    assert!(
+        (x - 100.0).abs() < 1.0,
+        "layer should rest on the bound, got {x}"
+    );
+}
@@ -0,0 +1,200 @@
+use layers::prelude::*;
+use layers::types::Size;
+
+#[test]
+fn transaction_shares_one_animation() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let a = engine.new_layer();
+    let b = engine.new_layer();
+    engine.add_layer(&a).unwrap();
+    engine.add_layer(&b).unwrap();
+
+    let mut transactions = Vec::new();
+    let animation = engine.transaction(
+        |_| {
+            transactions.push(a.set_position((100.0, 0.0), None));
+            // the explicit transition is replaced by the shared animation
+            transactions.push(b.set_opacity(0.0, Transition::linear(10.0)));
+        },
+        Transition::linear(1.0),
+    );
+
+    // nothing is applied before the next update
+    assert_eq!(a.position(), Point::new(0.0, 0.0));
+    assert_eq!(b.opacity(), 1.0);
+
+    for tr in transactions {
+        let change = engine.get_transaction(tr).unwrap();
+        assert_eq!(
+            format!("{:?}", change.animation_id.unwrap()),
+            format!("{:?}", animation)
+        );
+    }
+
+    engine.update(0.5);
+    assert!((a.position().x - 50.0).abs() < 1.0);
+    assert!((b.opacity() - 0.5).abs() < 0.02);
+
+    engine.update(0.6);
+    assert_eq!(a.position(), Point::new(100.0, 0.0));
+    assert_eq!(b.opacity(), 0.0);
+}
+
+#[test]
+fn transaction_without_transition_commits_on_update() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    engine.add_layer(&layer).unwrap();
+
+    engine.transaction(
+        |_| {
+            layer.set_position((10.0, 10.0), None);
+            layer.set_size(Size::points(50.0, 50.0), None);
+        },
+        None,
+    );
+    assert_eq!(layer.position(), Point::new(0.0, 0.0));
+
+    engine.update(0.016);
+    assert_eq!(layer.position(), Point::new(10.0, 10.0));
+}
+
+#[test]
+fn border_style_is_batched_in_transactions() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    engine.add_layer(&layer).unwrap();
+
+    engine.transaction(
+        |_| {
+            layer.set_border_width(2.0, None);
+            layer.set_border_style(BorderStyle::Dashed);
+        },
+        None,
+    );
+    assert_eq!(layer.border_style(), BorderStyle::Solid);
+
+    engine.update(0.016);
+    assert_eq!(layer.border_style(), BorderStyle::Dashed);
+}
+
+#[test]
+fn discrete_changes_are_batched_in_transactions() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    let mask = engine.new_layer();
+    engine.add_layer(&layer).unwrap();
+
+    let shadow = Shadow::new((0.0, 4.0), 12.0, 0.0, Color::new_rgba(0.0, 0.0, 0.0, 0.3));
+    engine.transaction(
+        |_| {
+            layer.set_blend_mode(BlendMode::Multiply);
+            layer.set_preserve_3d(true);
+            layer.set_shadows(vec![shadow], None);
+            layer.set_mask(&mask).unwrap();
+        },
+        None,
+    );
+    assert_eq!(layer.blend_mode(), BlendMode::Normal);
+    assert!(!layer.preserve_3d());
+    assert!(layer.shadows().is_empty());
+    assert!(layer.mask().is_none());
+
+    engine.update(0.016);
+    assert_eq!(layer.blend_mode(), BlendMode::Multiply);
+    assert!(layer.preserve_3d());
+    assert_eq!(layer.shadows().len(), 1);
+    assert_eq!(layer.mask().unwrap().id, mask.id);
+}
+
+#[test]
+fn nested_transaction_joins_the_outer_one() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    engine.add_layer(&layer).unwrap();
+
+    let mut inner = None;
+    let outer = engine.transaction(
+        |_| {
+            inner = Some(engine.transaction(
+                |_| {
+                    layer.set_opacity(0.0, None);
+                },
+                Transition::linear(10.0),
+            ));
+            // the inner changes are not committed before the outer ones
+            assert_eq!(layer.opacity(), 1.0);
+        },
+        Transition::linear(1.0),
+    );
+    assert_eq!(format!("{:?}", inner.unwrap()), format!("{:?}", outer));
+    assert_eq!(layer.opacity(), 1.0);
+
+    engine.update(1.1);
+    assert_eq!(layer.opacity(), 0.0);
+}
+
+#[test]
+fn panicking_transaction_commits_nothing() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    engine.add_layer(&layer).unwrap();
+
+    let mut animation = None;
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        engine.transaction(
+            |t| {
+                animation = Some(t);
+                layer.set_opacity(0.0, None);
+                panic!("transaction failed");
+            },
+            Transition::linear(1.0),
+        );
+    }));
+    assert!(result.is_err());
+    // the animation is dropped with the recorded changes
+    assert!(engine.get_animation(animation.unwrap()).is_none());
+
+    engine.update(1.1);
+    assert_eq!(layer.opacity(), 1.0);
+    // changes made after the panic are applied as usual
+    layer.set_opacity(0.5, None);
+    assert_eq!(layer.opacity(), 0.5);
+}
+
+#[test]
+fn transaction_handle_finishes() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    engine.add_layer(&layer).unwrap();
+
+    let finished = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let f = finished.clone();
+    engine.transaction(
+        |t| {
+            layer.set_opacity(0.0, None);
+            t.on_finish(
+                move |_: f32| {
+                    f.store(true, std::sync::atomic::Ordering::SeqCst);
+                },
+                true,
+            );
+        },
+        Transition::linear(0.2),
+    );
+    engine.update(0.1);
+    assert!(!finished.load(std::sync::atomic::Ordering::SeqCst));
+    engine.update(0.2);
+    assert!(finished.load(std::sync::atomic::Ordering::SeqCst));
+}
+
+#[test]
+fn changes_after_transaction_are_not_recorded() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    engine.add_layer(&layer).unwrap();
+
+    engine.transaction(|_| {}, Transition::linear(1.0));
+    layer.set_opacity(0.3, None);
+    assert_eq!(layer.opacity(), 0.3);
+}
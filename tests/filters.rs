@@ -0,0 +1,90 @@
+use layers::prelude::*;
+use layers::types::Size;
+
+#[test]
+fn filter_lists_interpolate_from_identity() {
+    let from = vec![Filter::Blur(0.0)];
+    let to = vec![Filter::Blur(20.0), Filter::Grayscale(1.0)];
+
+    let halfway = from.interpolate(&to, 0.5);
+    assert_eq!(halfway, vec![Filter::Blur(10.0), Filter::Grayscale(0.5)]);
+    assert_eq!(from.interpolate(&to, 1.0), to);
+
+    // filters of different kinds snap halfway
+    let from = vec![Filter::Brightness(0.5)];
+    let to = vec![Filter::Contrast(2.0)];
+    assert_eq!(from.interpolate(&to, 0.4), from);
+    assert_eq!(from.interpolate(&to, 0.6), to);
+}
+
+#[test]
+fn identity_filters_leave_colors_unchanged() {
+    let identity = Filter::ColorMatrix([0.0; 20])
+        .identity()
+        .color_matrix()
+        .unwrap();
+    for filter in [
+        Filter::Brightness(1.0),
+        Filter::Contrast(1.0),
+        Filter::Saturate(1.0),
+        Filter::HueRotate(0.0),
+        Filter::Grayscale(0.0),
+    ] {
+        let matrix = filter.color_matrix().unwrap();
+        for (a, b) in matrix.iter().zip(identity.iter()) {
+            assert!((a - b).abs() < 0.001, "{filter:?}");
+        }
+    }
+    assert!(Filter::Blur(4.0).color_matrix().is_none());
+}
+
+#[test]
+fn blur_animates_as_a_transition() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    engine.add_layer(&layer).unwrap();
+    layer.set_position((100.0, 100.0), None);
+    layer.set_size(Size::points(100.0, 100.0), None);
+    layer.set_background_color(Color::new_rgba(1.0, 0.0, 0.0, 1.0), None);
+    engine.update(0.016);
+    engine.clear_damage();
+
+    layer.set_filters(vec![Filter::Blur(20.0)], Transition::linear(1.0));
+    engine.update(0.5);
+    let render_layer = engine.render_layer(&layer).unwrap();
+    let [Filter::Blur(sigma)] = render_layer.filters[..] else {
+        panic!("expected a blur filter");
+    };
+    assert!((sigma - 10.0).abs() < 0.5);
+    // a filtered layer never occludes
+    assert!(!render_layer.is_fully_opaque());
+    // the blur draws outside of the layer bounds
+    let damage = engine.damage();
+    assert!(damage.left < 100.0 && damage.right > 200.0);
+
+    engine.update(0.6);
+    assert_eq!(layer.filters(), vec![Filter::Blur(20.0)]);
+}
+
+#[test]
+fn content_changes_under_a_blur_damage_the_blurred_area() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let parent = engine.new_layer();
+    engine.add_layer(&parent).unwrap();
+    parent.set_size(Size::points(1000.0, 1000.0), None);
+    parent.set_filters(vec![Filter::Blur(10.0)], None);
+    let layer = engine.new_layer();
+    parent.add_sublayer(&layer.id).unwrap();
+    layer.set_position((100.0, 100.0), None);
+    layer.set_size(Size::points(100.0, 100.0), None);
+    layer.set_background_color(Color::new_rgba(1.0, 0.0, 0.0, 1.0), None);
+    layer.set_filters(vec![Filter::Blur(5.0)], None);
+    engine.update(0.016);
+    engine.clear_damage();
+
+    layer.set_background_color(Color::new_rgba(0.0, 0.0, 1.0, 1.0), None);
+    engine.update(0.016);
+    // the blur of the layer and of its parent spread the change
+    let damage = engine.damage();
+    assert!(damage.left <= 100.0 - 45.0 && damage.right >= 200.0 + 45.0);
+}
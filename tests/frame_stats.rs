@@ -0,0 +1,83 @@
+use layers::engine::FRAME_STATS_HISTORY_SIZE;
+use layers::prelude::*;
+use layers::renderer::skia_image::SkiaImageRenderer;
+use layers::types::Size;
+
+#[test]
+fn update_records_the_frame_stats() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    layer.set_position((100.0, 100.0), None);
+    layer.set_size(Size::points(100.0, 50.0), None);
+    layer.set_background_color(Color::new_hex("#ff0000"), None);
+    engine.add_layer(&layer).unwrap();
+    engine.update(0.016);
+
+    let stats = engine.frame_stats();
+    assert!(stats.nodes_updated > 0);
+    assert_eq!(stats.nodes_repainted, 1);
+    assert_eq!(stats.damage_area, 100 * 50);
+    assert_eq!(stats.timings.total(), {
+        let t = stats.timings;
+        t.animations + t.transactions + t.layout + t.nodes + t.callbacks + t.cleanup
+    });
+
+    // an update without changes does no work
+    engine.update(0.016);
+    let stats = engine.frame_stats();
+    assert_eq!(stats.nodes_updated, 0);
+    assert_eq!(stats.nodes_repainted, 0);
+    assert_eq!(stats.damage_area, 0);
+    assert_eq!(engine.frame_stats_history().len(), 2);
+}
+
+#[test]
+fn frame_stats_history_is_bounded() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    engine.add_layer(&layer).unwrap();
+    for _ in 0..FRAME_STATS_HISTORY_SIZE + 10 {
+        engine.update(0.016);
+    }
+    assert_eq!(engine.frame_stats_history().len(), FRAME_STATS_HISTORY_SIZE);
+}
+
+#[test]
+fn drawing_is_attributed_to_the_last_update() {
+    let engine = Engine::create(500.0, 500.0);
+    let root = engine.new_layer();
+    root.set_size(Size::points(500.0, 500.0), None);
+    engine.add_layer(&root).unwrap();
+
+    let layer = engine.new_layer();
+    layer.set_position((300.0, 300.0), None);
+    layer.set_size(Size::points(100.0, 100.0), None);
+    layer.set_background_color(Color::new_hex("#00ff00"), None);
+    layer.set_image_cached(true);
+    root.add_sublayer(&layer).unwrap();
+
+    // an opaque layer covering another one
+    for color in ["#ff0000", "#0000ff"] {
+        let square = engine.new_layer();
+        square.set_size(Size::points(100.0, 100.0), None);
+        square.set_background_color(Color::new_hex(color), None);
+        root.add_sublayer(&square).unwrap();
+    }
+
+    let renderer = SkiaImageRenderer::new(500, 500, "tests/frame_stats.png");
+    engine.update(0.016);
+    engine.compute_occlusion(engine.scene_root().unwrap());
+    renderer.draw_scene(engine.scene(), engine.scene_root().unwrap(), None);
+    let stats = engine.frame_stats();
+    assert_eq!(stats.cache_misses, 1);
+    assert_eq!(stats.cache_hits, 0);
+    assert!(stats.occluded_nodes > 0);
+
+    layer.set_opacity(0.5, None);
+    engine.update(0.016);
+    renderer.draw_scene(engine.scene(), engine.scene_root().unwrap(), None);
+    let history = engine.frame_stats_history();
+    let (previous, last) = (history[history.len() - 2], history[history.len() - 1]);
+    assert_eq!((previous.cache_hits, previous.cache_misses), (0, 1));
+    assert_eq!((last.cache_hits, last.cache_misses), (1, 0));
+}
@@ -0,0 +1,106 @@
+use layers::prelude::*;
+use layers::types::{GradientConic, GradientDirection, GradientLinear, GradientRadial, TileMode};
+
+fn linear(colors: Vec<Color>, points: Vec<f32>) -> PaintColor {
+    GradientLinear {
+        colors,
+        points,
+        direction: GradientDirection::Angle(90.0),
+        tile_mode: TileMode::Clamp,
+    }
+    .into()
+}
+
+#[test]
+fn gradient_stops_interpolate_between_different_sizes() {
+    let red = Color::new_rgba(1.0, 0.0, 0.0, 1.0);
+    let blue = Color::new_rgba(0.0, 0.0, 1.0, 1.0);
+    let green = Color::new_rgba(0.0, 1.0, 0.0, 1.0);
+
+    let from = linear(vec![red, blue], vec![]);
+    let to = linear(vec![red, green, blue], vec![0.0, 0.25, 1.0]);
+
+    // the ends of the animation are exactly the two gradients
+    assert_eq!(from.interpolate(&to, 0.0), from);
+    assert_eq!(from.interpolate(&to, 1.0), to);
+
+    // halfway, both gradients are sampled on the union of the stops
+    let PaintColor::GradientLinear(halfway) = from.interpolate(&to, 0.5) else {
+        panic!("expected a linear gradient");
+    };
+    assert_eq!(halfway.points, vec![0.0, 0.25, 1.0]);
+    let expected = red.interpolate(&blue, 0.25).interpolate(&green, 0.5);
+    assert_eq!(halfway.colors[1], expected);
+}
+
+#[test]
+fn gradient_stops_of_the_same_size_slide() {
+    let black = Color::new_rgba(0.0, 0.0, 0.0, 1.0);
+    let white = Color::new_rgba(1.0, 1.0, 1.0, 1.0);
+    let from = linear(vec![black, white], vec![0.0, 0.5]);
+    let to = linear(vec![black, white], vec![0.5, 1.0]);
+
+    let PaintColor::GradientLinear(halfway) = from.interpolate(&to, 0.5) else {
+        panic!("expected a linear gradient");
+    };
+    assert_eq!(halfway.points, vec![0.25, 0.75]);
+}
+
+#[test]
+fn solid_color_interpolates_into_gradient() {
+    let white = Color::new_rgba(1.0, 1.0, 1.0, 1.0);
+    let black = Color::new_rgba(0.0, 0.0, 0.0, 1.0);
+    let solid = PaintColor::from(white);
+    let conic: PaintColor = GradientConic {
+        center: Point::new(50.0, 50.0),
+        start_angle: 90.0,
+        colors: vec![black, white, black],
+        ..Default::default()
+    }
+    .into();
+
+    let PaintColor::GradientConic(halfway) = solid.interpolate(&conic, 0.5) else {
+        panic!("expected a conic gradient");
+    };
+    assert_eq!(halfway.start_angle, 90.0);
+    assert_eq!(halfway.colors[1], white);
+    assert_eq!(halfway.colors[0], white.interpolate(&black, 0.5));
+
+    // and back
+    assert_eq!(conic.interpolate(&solid, 1.0), solid);
+}
+
+#[test]
+fn gradient_background_is_visible() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    engine.add_layer(&layer).unwrap();
+    layer.set_size(layers::types::Size::points(100.0, 100.0), None);
+
+    let transparent = Color::new_rgba(0.0, 0.0, 0.0, 0.0);
+    let red = Color::new_rgba(1.0, 0.0, 0.0, 1.0);
+    layer.set_background_color(linear(vec![transparent, red], vec![]), None);
+    engine.update(0.016);
+
+    let render_layer = engine.render_layer(&layer).unwrap();
+    assert!(matches!(
+        render_layer.background_color,
+        PaintColor::GradientLinear(_)
+    ));
+    // the transparent stop keeps the layer from occluding
+    assert!(!render_layer.is_fully_opaque());
+}
+
+#[test]
+fn gradient_constructors_use_the_defaults() {
+    let black = Color::new_rgba(0.0, 0.0, 0.0, 1.0);
+    let white = Color::new_rgba(1.0, 1.0, 1.0, 1.0);
+
+    let linear = GradientLinear::new(vec![black, white], vec![0.0, 1.0]);
+    assert!(matches!(linear.direction, GradientDirection::Angle(a) if a == 180.0));
+    assert_eq!(linear.tile_mode, TileMode::Clamp);
+
+    let radial = GradientRadial::new((50.0, 50.0), 25.0, vec![black, white], vec![0.0, 1.0]);
+    assert_eq!(radial.center, Point::new(50.0, 50.0));
+    assert_eq!(radial.tile_mode, TileMode::Clamp);
+}
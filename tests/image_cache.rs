@@ -89,3 +89,90 @@ pub fn image_cache() {
 
     assert_eq!(result.score, 1.0);
 }
+
+// Image caching on a raster surface reuses the offscreen surface between frames
+#[test]
+pub fn image_cache_raster_surface() {
+    let engine = Engine::create(500.0, 500.0);
+    let layer = engine.new_layer();
+    engine.add_layer(&layer).unwrap();
+    layer.set_position((50.0, 50.0), None);
+    layer.set_size(Size::points(100.0, 100.0), None);
+    layer.set_background_color(Color::new_hex("#ff0000"), None);
+    layer.set_image_cached(true);
+    engine.update(0.01);
+
+    let renderer = SkiaImageRenderer::new(500, 500, "tests/image_cache/render_raster.png");
+    let pixel_at = |x: i32, y: i32| {
+        renderer
+            .surface()
+            .image_snapshot()
+            .peek_pixels()
+            .unwrap()
+            .get_color((x, y))
+    };
+
+    renderer.draw_scene(engine.scene(), engine.scene_root().unwrap(), None);
+    let stats = engine.surface_cache_stats();
+    assert_eq!(stats.misses, 1);
+    assert_eq!(stats.surfaces, 1);
+    assert!(stats.bytes > 0);
+    assert_eq!(pixel_at(100, 100), skia_safe::Color::RED);
+
+    // the cached surface is reused
+    renderer.draw_scene(engine.scene(), engine.scene_root().unwrap(), None);
+    assert_eq!(engine.surface_cache_stats().hits, 1);
+    assert_eq!(pixel_at(100, 100), skia_safe::Color::RED);
+
+    // a change repaints the cached surface
+    layer.set_background_color(Color::new_hex("#0000ff"), None);
+    engine.update(0.01);
+    renderer.draw_scene(engine.scene(), engine.scene_root().unwrap(), None);
+    let stats = engine.surface_cache_stats();
+    assert_eq!(stats.hits, 2);
+    assert_eq!(stats.surfaces, 1);
+    assert_eq!(pixel_at(100, 100), skia_safe::Color::BLUE);
+
+    engine.purge_caches();
+    assert_eq!(engine.surface_cache_stats().surfaces, 0);
+}
+
+// Layers too large for a single surface are cached in tiles, only the visible
+// tiles are drawn and they are reused when the layer scrolls
+#[test]
+pub fn image_cache_large_layer_tiles() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    engine.add_layer(&layer).unwrap();
+    layer.set_size(Size::points(5000.0, 600.0), None);
+    layer.set_background_color(Color::new_hex("#ff0000"), None);
+    layer.set_image_cached(true);
+    engine.update(0.01);
+
+    let renderer = SkiaImageRenderer::new(1000, 1000, "tests/image_cache/render_tiles.png");
+    let pixel_at = |x: i32, y: i32| {
+        renderer
+            .surface()
+            .image_snapshot()
+            .peek_pixels()
+            .unwrap()
+            .get_color((x, y))
+    };
+
+    renderer.draw_scene(engine.scene(), engine.scene_root().unwrap(), None);
+    // two columns and two rows of 512px tiles are visible
+    let stats = engine.surface_cache_stats();
+    assert_eq!(stats.surfaces, 4);
+    assert_eq!(stats.bytes, 4 * 512 * 512 * 4);
+    assert_eq!(pixel_at(100, 100), skia_safe::Color::RED);
+    assert_eq!(pixel_at(900, 500), skia_safe::Color::RED);
+
+    // scroll by 1000px: the second column of tiles is still visible
+    layer.set_position((-1000.0, 0.0), None);
+    engine.update(0.01);
+    renderer.draw_scene(engine.scene(), engine.scene_root().unwrap(), None);
+    let stats = engine.surface_cache_stats();
+    assert_eq!(stats.hits, 2);
+    assert_eq!(stats.surfaces, 8);
+    assert_eq!(pixel_at(100, 100), skia_safe::Color::RED);
+}
@@ -0,0 +1,77 @@
+use layers::prelude::*;
+
+#[test]
+fn implicit_transition_animates_setter_without_transition() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    engine.add_layer(&layer).unwrap();
+
+    layer.set_implicit_transition("position", Transition::linear(1.0));
+    layer.set_position((100.0, 100.0), None);
+
+    // the value is animated rather than applied immediately
+    assert_eq!(layer.position(), Point::new(0.0, 0.0));
+    engine.update(0.5);
+    let position = layer.position();
+    assert!(position.x > 0.0 && position.x < 100.0, "got {position:?}");
+
+    // other attributes are not affected
+    layer.set_opacity(0.5, None);
+    assert_eq!(layer.opacity(), 0.5);
+}
+
+#[test]
+fn explicit_transition_overrides_implicit() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    engine.add_layer(&layer).unwrap();
+
+    layer.set_implicit_transition("opacity", Transition::linear(10.0));
+    layer.set_opacity(0.0, Transition::linear(1.0));
+    engine.update(0.5);
+    assert!((layer.opacity() - 0.5).abs() < 0.05);
+}
+
+#[test]
+fn without_implicit_transitions_applies_immediately() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    engine.add_layer(&layer).unwrap();
+
+    layer.set_implicit_transition("position", Transition::spring(0.3, 0.1));
+
+    engine.without_implicit_transitions(|| {
+        layer.set_position((100.0, 100.0), None);
+        // nested scopes keep implicit transitions disabled
+        engine.without_implicit_transitions(|| {
+            layer.set_position((50.0, 50.0), None);
+        });
+        layer.set_position((20.0, 20.0), None);
+    });
+    assert_eq!(layer.position(), Point::new(20.0, 20.0));
+
+    // enabled again once the scope is over
+    layer.set_position((100.0, 100.0), None);
+    assert_eq!(layer.position(), Point::new(20.0, 20.0));
+}
+
+#[test]
+fn implicit_transition_can_be_removed() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    engine.add_layer(&layer).unwrap();
+
+    layer.set_implicit_transition("size", Transition::linear(1.0));
+    assert!(layer.implicit_transition("size").is_some());
+    layer.set_implicit_transition("size", None);
+    assert!(layer.implicit_transition("size").is_none());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "unknown attribute")]
+fn unknown_implicit_transition_attribute_panics() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    layer.set_implicit_transition("opactiy", Transition::linear(1.0));
+}
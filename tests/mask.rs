@@ -0,0 +1,91 @@
+use layers::prelude::*;
+use layers::types::Size;
+use skia_safe::Contains;
+
+fn masked_scene(engine: &std::sync::Arc<Engine>) -> (Layer, Layer, Layer) {
+    let back = engine.new_layer();
+    back.set_size(Size::points(100.0, 100.0), None);
+    back.set_background_color(Color::new_rgba(1.0, 0.0, 0.0, 1.0), None);
+    engine.add_layer(&back).unwrap();
+
+    let front = engine.new_layer();
+    front.set_size(Size::points(200.0, 200.0), None);
+    front.set_background_color(Color::new_rgba(0.0, 0.0, 1.0, 1.0), None);
+    engine.add_layer(&front).unwrap();
+
+    let mask = engine.new_layer();
+    mask.set_position((10.0, 10.0), None);
+    mask.set_size(Size::points(50.0, 50.0), None);
+    mask.set_background_color(Color::new_rgba(0.0, 0.0, 0.0, 1.0), None);
+    front.set_mask(&mask).unwrap();
+
+    (back, front, mask)
+}
+
+#[test]
+fn mask_is_attached_to_the_masked_layer() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let (_, front, mask) = masked_scene(&engine);
+    engine.update(0.016);
+
+    assert_eq!(front.mask().unwrap().id, mask.id);
+    assert_eq!(engine.render_layer(&front).unwrap().mask, Some(mask.id));
+    // the mask is positioned in the masked layer coordinates
+    let mask_bounds = engine
+        .render_layer(&mask)
+        .unwrap()
+        .global_transformed_bounds;
+    assert_eq!(
+        mask_bounds,
+        skia_safe::Rect::from_xywh(10.0, 10.0, 50.0, 50.0)
+    );
+
+    front.set_mask(None).unwrap();
+    engine.update(0.016);
+    assert!(front.mask().is_none());
+    assert!(engine.render_layer(&front).unwrap().mask.is_none());
+    // the mask layer is detached, not deleted
+    assert!(engine.is_layer_alive(&mask.id));
+    assert!(!engine.node_children(&front.id).contains(&mask.id));
+
+    front.set_mask(&mask).unwrap();
+    engine.update(0.016);
+    assert_eq!(engine.render_layer(&front).unwrap().mask, Some(mask.id));
+}
+
+#[test]
+fn masked_layer_does_not_occlude() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let (back, _, _) = masked_scene(&engine);
+
+    engine.update(0.016);
+    engine.clear_occlusion();
+    engine.compute_occlusion(engine.scene_root().unwrap());
+
+    let occ_map = engine.scene().occlusion_map().unwrap();
+    let root = engine.scene_root().unwrap();
+    let occluded = occ_map.get(&root).unwrap();
+    assert!(!occluded.contains(&back.id));
+}
+
+#[test]
+fn mask_changes_damage_the_masked_area() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let (_, front, mask) = masked_scene(&engine);
+    engine.update(0.016);
+    engine.clear_damage();
+
+    mask.set_position((100.0, 100.0), None);
+    engine.update(0.016);
+    let damage = engine.damage();
+    assert!(damage.contains(skia_safe::Rect::from_xywh(10.0, 10.0, 50.0, 50.0)));
+    assert!(damage.contains(skia_safe::Rect::from_xywh(100.0, 100.0, 50.0, 50.0)));
+    engine.clear_damage();
+
+    // removing the mask shows the whole subtree again
+    front.set_mask(None).unwrap();
+    engine.update(0.016);
+    assert!(engine
+        .damage()
+        .contains(skia_safe::Rect::from_xywh(0.0, 0.0, 200.0, 200.0)));
+}
@@ -0,0 +1,73 @@
+use layers::prelude::*;
+
+fn l_path() -> skia_safe::Path {
+    let mut path = skia_safe::Path::new();
+    path.move_to((0.0, 0.0));
+    path.line_to((100.0, 0.0));
+    path.line_to((100.0, 100.0));
+    path
+}
+
+#[test]
+fn motion_path_follows_distance() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    engine.add_layer(&layer).unwrap();
+
+    layer.set_position_along_path(&l_path(), Transition::linear(1.0), false);
+
+    // halfway through the time is the corner of the path
+    engine.update(0.5);
+    let position = layer.position();
+    assert!(
+        (position.x - 100.0).abs() < 0.5 && position.y.abs() < 0.5,
+        "got {position:?}"
+    );
+
+    engine.update(0.25);
+    let position = layer.position();
+    assert!(
+        (position.x - 100.0).abs() < 0.5 && (position.y - 50.0).abs() < 0.5,
+        "got {position:?}"
+    );
+
+    engine.update(0.5);
+    let position = layer.position();
+    assert!(
+        (position.x - 100.0).abs() < 0.5 && (position.y - 100.0).abs() < 0.5,
+        "got {position:?}"
+    );
+}
+
+#[test]
+fn motion_path_rotates_along_tangent() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    engine.add_layer(&layer).unwrap();
+
+    layer.set_position_along_path(&l_path(), Transition::linear(1.0), true);
+
+    engine.update(0.25);
+    assert!(layer.rotation().z.abs() < 0.001);
+
+    engine.update(0.5);
+    let angle = layer.rotation().z;
+    assert!(
+        (angle - std::f32::consts::FRAC_PI_2).abs() < 0.001,
+        "got {angle}"
+    );
+}
+
+#[test]
+fn motion_path_without_transition_jumps_to_end() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    engine.add_layer(&layer).unwrap();
+
+    layer.set_position_along_path(&l_path(), None, false);
+    let position = layer.position();
+    assert!(
+        (position.x - 100.0).abs() < 0.5 && (position.y - 100.0).abs() < 0.5,
+        "got {position:?}"
+    );
+}
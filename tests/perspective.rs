@@ -0,0 +1,88 @@
+use layers::prelude::*;
+use layers::types::{Point3d, Size};
+
+fn rotated_child(engine: &std::sync::Arc<Engine>) -> (Layer, Layer) {
+    let parent = engine.new_layer();
+    parent.set_size(Size::points(400.0, 400.0), None);
+    engine.add_layer(&parent).unwrap();
+
+    let child = engine.new_layer();
+    child.set_position((100.0, 100.0), None);
+    child.set_size(Size::points(200.0, 200.0), None);
+    child.set_background_color(Color::new_rgba(1.0, 0.0, 0.0, 1.0), None);
+    child.set_rotation(
+        Point3d {
+            x: 0.0,
+            y: 0.5,
+            z: 0.0,
+        },
+        None,
+    );
+    parent.add_sublayer(&child).unwrap();
+
+    (parent, child)
+}
+
+#[test]
+fn flat_parent_does_not_add_perspective() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let (_, child) = rotated_child(&engine);
+    engine.update(0.016);
+
+    let render_layer = engine.render_layer(&child).unwrap();
+    assert!(!render_layer.transform_33.has_perspective());
+}
+
+#[test]
+fn perspective_applies_to_sublayers() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let (parent, child) = rotated_child(&engine);
+    engine.update(0.016);
+    engine.clear_damage();
+
+    parent.set_perspective(800.0, None);
+    engine.update(0.016);
+
+    let render_layer = engine.render_layer(&child).unwrap();
+    assert!(render_layer.transform_33.has_perspective());
+    // a layer drawn with perspective never occludes
+    assert!(!render_layer.is_fully_opaque());
+    // the parent change damages the projected sublayer
+    assert!(!engine.damage().is_empty());
+
+    // the parent itself is not projected
+    let parent_layer = engine.render_layer(&parent).unwrap();
+    assert!(!parent_layer.transform_33.has_perspective());
+}
+
+#[test]
+fn preserve_3d_is_set_on_the_render_layer() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let (parent, _) = rotated_child(&engine);
+    parent.set_preserve_3d(true);
+    engine.update(0.016);
+
+    assert!(parent.preserve_3d());
+    assert!(engine.render_layer(&parent).unwrap().preserve_3d);
+}
+
+#[test]
+fn layer_tree_sets_the_perspective() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    engine.add_layer(&layer).unwrap();
+
+    let tree = LayerTreeBuilder::default()
+        .key("stage")
+        .perspective((800.0, None))
+        .perspective_origin((Point::new(0.0, 0.5), None))
+        .preserve_3d(true)
+        .build()
+        .unwrap();
+    layer.build_layer_tree(&tree);
+    engine.update(0.016);
+
+    assert_eq!(layer.perspective(), 800.0);
+    assert_eq!(layer.perspective_origin(), Point::new(0.0, 0.5));
+    assert!(layer.preserve_3d());
+}
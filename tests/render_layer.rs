@@ -417,4 +417,128 @@ mod tests {
             skia_safe::Rect::from_xywh(0.0, 0.0, 350.0, 350.0)
         );
     }
+
+    #[test]
+    pub fn render_layer_border_style() {
+        let engine = Engine::create(1000.0, 1000.0);
+        let layer = engine.new_layer();
+        engine.add_layer(&layer).unwrap();
+
+        layer.set_size(Size::points(100.0, 100.0), None);
+        layer.set_border_width(2.0, None);
+        engine.update(0.016);
+        assert!(engine
+            .render_layer(&layer)
+            .unwrap()
+            .border_dash_intervals()
+            .is_none());
+
+        layer.set_border_style(BorderStyle::Dashed);
+        layer.set_border_dash_phase(4.0, None);
+        engine.update(0.016);
+        let rl = engine.render_layer(&layer).unwrap();
+        assert!(matches!(rl.border_style, BorderStyle::Dashed));
+        assert_eq!(rl.border_dash_phase, 4.0);
+        // derived from the border width
+        assert_eq!(rl.border_dash_intervals(), Some([6.0, 4.0]));
+
+        layer.set_border_style(BorderStyle::Dotted);
+        layer.set_border_dash_gap(10.0, None);
+        engine.update(0.016);
+        let rl = engine.render_layer(&layer).unwrap();
+        assert_eq!(rl.border_dash_intervals(), Some([0.0, 10.0]));
+    }
+
+    #[test]
+    pub fn render_layer_shadows() {
+        let engine = Engine::create(1000.0, 1000.0);
+        let layer = engine.new_layer();
+        engine.add_layer(&layer).unwrap();
+        layer.set_size(Size::points(100.0, 100.0), None);
+
+        let black = Color::new_rgba(0.0, 0.0, 0.0, 0.5);
+        layer.set_shadows(
+            vec![
+                Shadow::new((0.0, 4.0), 10.0, 0.0, black),
+                Shadow::inset((0.0, 2.0), 4.0, 1.0, black),
+            ],
+            None,
+        );
+        engine.update(0.016);
+        let rl = engine.render_layer(&layer).unwrap();
+        assert_eq!(rl.shadows.len(), 2);
+        assert!(!rl.shadows[0].inset);
+        assert!(rl.shadows[1].inset);
+
+        // each shadow animates on its own
+        layer.set_shadow(
+            0,
+            Shadow::new((0.0, 20.0), 10.0, 0.0, black),
+            Transition::linear(1.0),
+        );
+        layer.set_shadow(1, Shadow::inset((0.0, 2.0), 4.0, 1.0, black), None);
+        engine.update(0.5);
+        let shadows = layer.shadows();
+        assert!((shadows[0].offset.y - 12.0).abs() < 0.5);
+        assert_eq!(shadows[1].offset.y, 2.0);
+        assert!(layer.set_shadow(2, Shadow::default(), None).is_none());
+
+        // new shadows fade in, removed ones are dropped
+        layer.set_shadows(
+            vec![
+                Shadow::new((0.0, 20.0), 10.0, 0.0, black),
+                Shadow::inset((0.0, 2.0), 4.0, 1.0, black),
+                Shadow::new((4.0, 4.0), 2.0, 0.0, black),
+            ],
+            Transition::linear(1.0),
+        );
+        engine.update(0.5);
+        let shadows = layer.shadows();
+        assert_eq!(shadows.len(), 3);
+        assert!(shadows[2].color.alpha > 0.0 && shadows[2].color.alpha < 0.5);
+
+        layer.set_shadows(vec![], None);
+        engine.update(0.016);
+        assert!(engine.render_layer(&layer).unwrap().shadows.is_empty());
+    }
+
+    #[test]
+    pub fn render_layer_backdrop_effect() {
+        let engine = Engine::create(1000.0, 1000.0);
+        let parent = engine.new_layer();
+        let menu = engine.new_layer();
+        engine.add_layer(&parent).unwrap();
+        engine.append_layer(&menu.id, Some(parent.id)).unwrap();
+        menu.set_size(Size::points(100.0, 100.0), None);
+        menu.set_blend_mode(BlendMode::BackgroundBlur);
+
+        let material = BackdropEffect {
+            blur_sigma: 20.0,
+            saturation: 1.8,
+            brightness: 1.0,
+            tint: Color::new_rgba(1.0, 1.0, 1.0, 0.2),
+            noise: 0.0,
+            downsample: 0.25,
+        };
+        menu.set_backdrop_effect(material, Transition::linear(1.0));
+        engine.update(0.5);
+        let effect = engine.render_layer(&menu).unwrap().backdrop_effect;
+        assert!((effect.blur_sigma - 30.0).abs() < 0.5);
+        assert!(effect.tint.alpha > 0.0 && effect.tint.alpha < 0.2);
+
+        engine.update(0.6);
+        assert_eq!(
+            engine.render_layer(&menu).unwrap().backdrop_effect,
+            material
+        );
+
+        // the effect travels with the blurred region to the ancestors
+        let regions = engine
+            .render_layer(&parent)
+            .unwrap()
+            .backdrop_blur_region
+            .unwrap();
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].1, material);
+    }
 }
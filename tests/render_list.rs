@@ -508,4 +508,223 @@ mod tests {
             "back layer should NOT be occluded when front is not opaque"
         );
     }
+
+    #[test]
+    pub fn occlusion_blend_mode_does_not_occlude() {
+        let engine = Engine::create(1000.0, 1000.0);
+
+        let back = engine.new_layer();
+        back.set_size(Size::points(100.0, 100.0), None);
+        back.set_background_color(Color::new_rgba(1.0, 0.0, 0.0, 1.0), None);
+        engine.add_layer(&back).unwrap();
+
+        // Front layer is opaque but multiplied onto the back layer
+        let front = engine.new_layer();
+        front.set_size(Size::points(200.0, 200.0), None);
+        front.set_background_color(Color::new_rgba(0.0, 0.0, 1.0, 1.0), None);
+        front.set_blend_mode(BlendMode::Multiply);
+        engine.add_layer(&front).unwrap();
+
+        // an opaque child is composited within the blended group
+        let child = engine.new_layer();
+        child.set_size(Size::points(200.0, 200.0), None);
+        child.set_background_color(Color::new_rgba(0.0, 1.0, 0.0, 1.0), None);
+        front.add_sublayer(&child).unwrap();
+
+        engine.update(0.016);
+        engine.clear_occlusion();
+        engine.compute_occlusion(engine.scene_root().unwrap());
+
+        let occ_map = engine.scene().occlusion_map().unwrap();
+        let root = engine.scene_root().unwrap();
+        let occluded = occ_map.get(&root).unwrap();
+
+        assert!(
+            !occluded.contains(&back.id),
+            "back layer should not be occluded by a blended subtree"
+        );
+    }
+
+    /// An absolutely positioned opaque layer in the scene root
+    fn opaque_layer(engine: &Engine, position: (f32, f32), size: (f32, f32)) -> Layer {
+        if engine.scene_root().is_none() {
+            let root = engine.new_layer();
+            root.set_size(Size::points(1000.0, 1000.0), None);
+            engine.add_layer(&root).unwrap();
+        }
+        let layer = engine.new_layer();
+        layer.set_layout_style(layers::taffy::Style {
+            position: layers::taffy::Position::Absolute,
+            ..Default::default()
+        });
+        layer.set_position(position, None);
+        layer.set_size(Size::points(size.0, size.1), None);
+        layer.set_background_color(Color::new_rgba(1.0, 0.0, 0.0, 1.0), None);
+        engine.add_layer(&layer).unwrap();
+        layer
+    }
+
+    fn compute_occluded(engine: &Engine) -> std::sync::Arc<std::collections::HashSet<NodeRef>> {
+        engine.update(0.016);
+        engine.clear_occlusion();
+        let root = engine.scene_root().unwrap();
+        engine.compute_occlusion(root);
+        engine.scene().occluded_set(root).unwrap()
+    }
+
+    #[test]
+    pub fn occlusion_rounded_rect_occludes_inner_rect() {
+        let engine = Engine::create(1000.0, 1000.0);
+        // inside the inner rect of the front layer
+        let inner = opaque_layer(&engine, (120.0, 120.0), (160.0, 160.0));
+        // under the rounded top left corner
+        let corner = opaque_layer(&engine, (100.0, 100.0), (10.0, 10.0));
+
+        let front = opaque_layer(&engine, (100.0, 100.0), (200.0, 200.0));
+        front.set_border_corner_radius(BorderRadius::new_single(20.0), None);
+
+        let occluded = compute_occluded(&engine);
+        assert!(occluded.contains(&inner.id));
+        assert!(!occluded.contains(&corner.id));
+    }
+
+    #[test]
+    pub fn occlusion_axis_aligned_transforms_occlude_mapped_bounds() {
+        let engine = Engine::create(1000.0, 1000.0);
+        let back = opaque_layer(&engine, (120.0, 150.0), (60.0, 100.0));
+        let scaled_back = opaque_layer(&engine, (400.0, 400.0), (150.0, 150.0));
+
+        // rotated by 90 degrees around its top left corner,
+        // it covers x: 100..200, y: 100..300
+        let rotated = opaque_layer(&engine, (200.0, 100.0), (200.0, 100.0));
+        rotated.set_rotation(
+            Point3d {
+                x: 0.0,
+                y: 0.0,
+                z: std::f32::consts::FRAC_PI_2,
+            },
+            None,
+        );
+        let scaled = opaque_layer(&engine, (400.0, 400.0), (100.0, 100.0));
+        scaled.set_scale((2.0, 2.0), None);
+
+        let occluded = compute_occluded(&engine);
+        assert!(occluded.contains(&back.id));
+        assert!(occluded.contains(&scaled_back.id));
+    }
+
+    #[test]
+    pub fn occlusion_rotated_layer_does_not_occlude_its_bounding_box() {
+        let engine = Engine::create(1000.0, 1000.0);
+        // inside the bounding box of the rotated layer, but not under it
+        let back = opaque_layer(&engine, (260.0, 100.0), (20.0, 20.0));
+
+        let front = opaque_layer(&engine, (400.0, 100.0), (200.0, 200.0));
+        front.set_rotation(
+            Point3d {
+                x: 0.0,
+                y: 0.0,
+                z: std::f32::consts::FRAC_PI_4,
+            },
+            None,
+        );
+
+        let occluded = compute_occluded(&engine);
+        assert!(!occluded.contains(&back.id));
+    }
+
+    #[test]
+    pub fn occlusion_content_opaque_custom_shape_occludes_inner_rect() {
+        let engine = Engine::create(1000.0, 1000.0);
+        let center = opaque_layer(&engine, (180.0, 180.0), (40.0, 40.0));
+        // outside the circle, in the corner of its bounds
+        let corner = opaque_layer(&engine, (100.0, 100.0), (20.0, 20.0));
+
+        let front = opaque_layer(&engine, (100.0, 100.0), (200.0, 200.0));
+        front.set_background_color(Color::new_rgba(0.0, 0.0, 0.0, 0.0), None);
+        front.set_content_opaque(true);
+        front.shape(Shape::from_path(&skia_safe::Path::circle(
+            (100.0, 100.0),
+            100.0,
+            None,
+        )));
+
+        let occluded = compute_occluded(&engine);
+        assert!(occluded.contains(&center.id));
+        assert!(!occluded.contains(&corner.id));
+    }
+
+    /// The occlusion updated from the changed nodes matches the occlusion
+    /// computed from scratch
+    fn assert_occlusion_is_up_to_date(
+        engine: &Engine,
+    ) -> std::sync::Arc<std::collections::HashSet<NodeRef>> {
+        let occluded = compute_occluded(engine);
+        let root = engine.scene_root().unwrap();
+        let expected = engine
+            .scene()
+            .with_arena(|arena| layers::engine::occlusion::compute_occlusion(root, arena));
+        assert_eq!(*occluded, expected);
+        occluded
+    }
+
+    #[test]
+    pub fn occlusion_is_updated_from_the_changed_nodes() {
+        let engine = Engine::create(1000.0, 1000.0);
+        let moving = opaque_layer(&engine, (500.0, 500.0), (50.0, 50.0));
+        moving.set_opacity(0.5, None);
+        let back = opaque_layer(&engine, (150.0, 150.0), (50.0, 50.0));
+        let front = opaque_layer(&engine, (100.0, 100.0), (200.0, 200.0));
+        let occluded = assert_occlusion_is_up_to_date(&engine);
+        assert!(occluded.contains(&back.id));
+        assert!(!occluded.contains(&moving.id));
+        let root = engine.scene_root().unwrap();
+        let rebuilds = engine.occlusion_rebuilds(root);
+        assert!(rebuilds > 0);
+
+        // a layer that doesn't occlude moves behind the front layer and back
+        moving.set_position((120.0, 120.0), None);
+        let occluded = assert_occlusion_is_up_to_date(&engine);
+        assert!(occluded.contains(&moving.id));
+        moving.set_position((400.0, 120.0), None);
+        let occluded = assert_occlusion_is_up_to_date(&engine);
+        assert!(!occluded.contains(&moving.id));
+
+        // repainting in place
+        back.set_background_color(Color::new_rgba(0.0, 1.0, 0.0, 1.0), None);
+        let occluded = assert_occlusion_is_up_to_date(&engine);
+        assert!(occluded.contains(&back.id));
+        // only the changed nodes were updated
+        assert_eq!(engine.occlusion_rebuilds(root), rebuilds);
+
+        // the occluder moves away
+        front.set_position((600.0, 100.0), None);
+        let occluded = assert_occlusion_is_up_to_date(&engine);
+        assert!(!occluded.contains(&back.id));
+        assert!(engine.occlusion_rebuilds(root) > rebuilds);
+
+        // a transparent layer becomes an occluder
+        let cover = opaque_layer(&engine, (100.0, 100.0), (200.0, 200.0));
+        cover.set_opacity(0.0, None);
+        assert_occlusion_is_up_to_date(&engine);
+        cover.set_opacity(1.0, None);
+        let occluded = assert_occlusion_is_up_to_date(&engine);
+        assert!(occluded.contains(&back.id));
+
+        // a content opaque layer stops occluding
+        cover.set_background_color(Color::new_rgba(0.0, 0.0, 0.0, 0.0), None);
+        cover.set_content_opaque(true);
+        let occluded = assert_occlusion_is_up_to_date(&engine);
+        assert!(occluded.contains(&back.id));
+        cover.set_content_opaque(false);
+        let occluded = assert_occlusion_is_up_to_date(&engine);
+        assert!(!occluded.contains(&back.id));
+
+        // hiding a layer changes the tree
+        cover.set_content_opaque(true);
+        assert_occlusion_is_up_to_date(&engine);
+        cover.set_hidden(true);
+        let occluded = assert_occlusion_is_up_to_date(&engine);
+        assert!(!occluded.contains(&back.id));
+    }
 }
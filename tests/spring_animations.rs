@@ -0,0 +1,163 @@
+use layers::prelude::*;
+
+#[test]
+fn spring_rest_thresholds_control_done() {
+    let loose = Spring::with_duration_and_bounce(1.0, 0.2).with_rest_thresholds(0.1, 0.5);
+    let tight = Spring::with_duration_and_bounce(1.0, 0.2).with_rest_thresholds(0.0001, 0.0001);
+
+    let first_done = |spring: &Spring| {
+        (0..1000)
+            .map(|i| i as f32 * 0.01)
+            .find(|t| spring.done(*t))
+            .unwrap()
+    };
+    assert!(
+        first_done(&loose) < first_done(&tight),
+        "looser thresholds should settle earlier"
+    );
+}
+
+#[test]
+fn spring_settling_time_estimate() {
+    let springs = [
+        Spring::with_duration_and_bounce(0.5, 0.3),
+        Spring::critically_damped(0.5),
+        Spring::overdamped(0.5, 2.0),
+    ];
+    for spring in springs {
+        let estimate = spring.settling_time();
+        assert!(estimate > 0.0, "{spring:?} estimate {estimate}");
+        let (position, _) = spring.update_pos_vel_at(estimate);
+        assert!(
+            (position - 1.0).abs() <= spring.rest_position * 1.01,
+            "{spring:?} should be within the rest position at {estimate}s, got {position}"
+        );
+    }
+}
+
+#[test]
+fn spring_damping_helpers() {
+    let critical = Spring::critically_damped(0.4);
+    assert_eq!(critical.damping_ratio(), 1.0);
+
+    let overdamped = Spring::overdamped(0.4, 0.5);
+    assert_eq!(overdamped.damping_ratio(), 1.0, "ratio is clamped to 1.0");
+
+    let overdamped = Spring::overdamped(0.4, 3.0);
+    assert!((overdamped.damping_ratio() - 3.0).abs() < 0.001);
+
+    // neither should overshoot when starting at rest
+    for spring in [critical, overdamped] {
+        for i in 0..200 {
+            let (position, _) = spring.update_pos_vel_at(i as f32 * 0.01);
+            assert!(
+                position <= 1.0 + 0.0001,
+                "{spring:?} overshoots: {position}"
+            );
+        }
+    }
+}
+
+#[test]
+fn overdamped_spring_keeps_initial_velocity() {
+    let mut spring = Spring::overdamped(1.0, 2.0);
+    spring.initial_velocity = 3.0;
+    let (position, velocity) = spring.update_pos_vel_at(0.0);
+    assert!(position.abs() < 0.0001);
+    assert!((velocity - 3.0).abs() < 0.001, "got {velocity}");
+}
+
+#[test]
+fn spring_2d_shares_velocity_vector() {
+    let spring = Spring::critically_damped(0.5);
+    let from = Point::new(0.0, 0.0);
+    let to = Point::new(100.0, 0.0);
+    let motion = Spring2d::new(spring, from, to, Point::new(0.0, 500.0));
+
+    let (start, velocity) = motion.update_pos_vel_at(0.0);
+    assert!(start.x.abs() < 0.001 && start.y.abs() < 0.001);
+    assert!((velocity.y - 500.0).abs() < 0.01);
+
+    // the perpendicular velocity moves the point off the from -> to segment
+    let (position, _) = motion.update_pos_vel_at(0.1);
+    assert!(position.y > 1.0, "got {position:?}");
+
+    let (end, _) = motion.update_pos_vel_at(5.0);
+    assert!(
+        (end.x - 100.0).abs() < 0.1 && end.y.abs() < 0.1,
+        "got {end:?}"
+    );
+    assert!(motion.done(5.0));
+}
+
+#[test]
+fn spring_2d_layer_position() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    engine.add_layer(&layer).unwrap();
+
+    layer.set_position_spring(
+        (100.0, 0.0),
+        Spring::critically_damped(0.5),
+        Point::new(0.0, 500.0),
+    );
+    engine.update(0.05);
+    let position = layer.position();
+    assert!(position.x > 0.0 && position.y > 0.0, "got {position:?}");
+
+    // retarget without a velocity: the running velocity vector is carried over
+    let tr = layer.set_position_spring((0.0, 0.0), Spring::critically_damped(0.5), None);
+    let animation = engine
+        .get_transaction(tr)
+        .and_then(|t| t.animation_id)
+        .and_then(|a| engine.get_animation(a))
+        .unwrap();
+    match animation.animation.timing {
+        TimingFunction::Spring2d(s) => assert!(s.initial_velocity.y > 0.0),
+        _ => panic!("expected a 2d spring"),
+    }
+
+    for _ in 0..100 {
+        engine.update(0.05);
+    }
+    let position = layer.position();
+    assert!(
+        position.x.abs() < 0.5 && position.y.abs() < 0.5,
+        "got {position:?}"
+    );
+}
+
+#[test]
+fn spring_2d_inside_a_transaction_follows_the_transaction() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    engine.add_layer(&layer).unwrap();
+
+    let mut transaction = None;
+    let animation = engine.transaction(
+        |_| {
+            transaction = Some(layer.set_position_spring(
+                (100.0, 0.0),
+                Spring::critically_damped(0.5),
+                Point::new(0.0, 500.0),
+            ));
+        },
+        Transition::linear(1.0),
+    );
+    let change = engine.get_transaction(transaction.unwrap()).unwrap();
+    assert_eq!(
+        format!("{:?}", change.animation_id.unwrap()),
+        format!("{:?}", animation)
+    );
+
+    // the value is interpolated with the linear progress, the spring velocity is ignored
+    engine.update(0.5);
+    let position = layer.position();
+    assert!(
+        (position.x - 50.0).abs() < 1.0 && position.y == 0.0,
+        "got {position:?}"
+    );
+
+    engine.update(0.6);
+    assert_eq!(layer.position(), Point::new(100.0, 0.0));
+}
@@ -0,0 +1,106 @@
+use layers::prelude::*;
+use layers::types::{Point3d, Size, M44};
+
+fn translate(x: f32, y: f32) -> TransformOperation {
+    TransformOperation::Translate(Point3d { x, y, z: 0.0 })
+}
+
+fn rotate_z(angle: f32) -> TransformOperation {
+    TransformOperation::Rotate {
+        axis: Point3d {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        },
+        angle,
+    }
+}
+
+fn assert_matrix_eq(a: &M44, b: &M44) {
+    for r in 0..4 {
+        for c in 0..4 {
+            assert!((a.rc(r, c) - b.rc(r, c)).abs() < 0.001, "{a:?} != {b:?}");
+        }
+    }
+}
+
+#[test]
+fn matching_operations_interpolate_their_parameters() {
+    let from = Transform::new(vec![translate(0.0, 0.0), rotate_z(0.0)]);
+    let to = Transform::new(vec![translate(100.0, 50.0), rotate_z(1.0)]);
+
+    let halfway = from.interpolate(&to, 0.5);
+    assert_eq!(
+        halfway,
+        Transform::new(vec![translate(50.0, 25.0), rotate_z(0.5)])
+    );
+    // an empty transform animates from the identity
+    assert_eq!(Transform::default().interpolate(&to, 0.5), halfway);
+}
+
+#[test]
+fn different_operations_interpolate_by_decomposition() {
+    let from = Transform::from(rotate_z(0.0));
+    let rotation = Transform::from(rotate_z(std::f32::consts::FRAC_PI_2)).to_m44();
+    let to = Transform::from(M44::concat(&M44::translate(100.0, 0.0, 0.0), &rotation));
+
+    let halfway = from.interpolate(&to, 0.5).to_m44();
+    let expected = Transform::new(vec![
+        translate(50.0, 0.0),
+        rotate_z(std::f32::consts::FRAC_PI_4),
+    ])
+    .to_m44();
+    assert_matrix_eq(&halfway, &expected);
+    assert_eq!(from.interpolate(&to, 1.0), to);
+}
+
+#[test]
+fn transform_applies_to_the_layer() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let layer = engine.new_layer();
+    layer.set_position((100.0, 100.0), None);
+    layer.set_size(Size::points(100.0, 100.0), None);
+    engine.add_layer(&layer).unwrap();
+
+    layer.set_transform(translate(50.0, 20.0), None);
+    engine.update(0.016);
+
+    let render_layer = engine.render_layer(&layer).unwrap();
+    assert_eq!(
+        render_layer.global_transformed_bounds,
+        skia_safe::Rect::from_xywh(150.0, 120.0, 100.0, 100.0)
+    );
+}
+
+#[test]
+fn sublayer_transform_applies_to_the_children() {
+    let engine = Engine::create(1000.0, 1000.0);
+    let parent = engine.new_layer();
+    parent.set_size(Size::points(200.0, 200.0), None);
+    engine.add_layer(&parent).unwrap();
+
+    let child = engine.new_layer();
+    child.set_position((10.0, 10.0), None);
+    child.set_size(Size::points(50.0, 50.0), None);
+    parent.add_sublayer(&child).unwrap();
+    engine.update(0.016);
+
+    parent.set_sublayer_transform(translate(30.0, 0.0), None);
+    engine.update(0.016);
+
+    assert_eq!(
+        engine
+            .render_layer(&child)
+            .unwrap()
+            .global_transformed_bounds,
+        skia_safe::Rect::from_xywh(40.0, 10.0, 50.0, 50.0)
+    );
+    // the parent itself is not moved
+    assert_eq!(
+        engine
+            .render_layer(&parent)
+            .unwrap()
+            .global_transformed_bounds,
+        skia_safe::Rect::from_xywh(0.0, 0.0, 200.0, 200.0)
+    );
+}